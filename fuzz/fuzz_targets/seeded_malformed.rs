@@ -0,0 +1,50 @@
+#![no_main]
+
+use std::cell::Cell;
+
+use libfuzzer_sys::fuzz_target;
+use serde::de::DeserializeSeed;
+use serde_state::de::{DeserializeState, Seed};
+use serde_state::ser::Seeded;
+use serde_state::shared::{SerSharedSeed, SharedSeed};
+use serde_state_fuzz::tokens::{mutate, tokens, Mutation};
+use serde_state_fuzz::{build_graph, Counter, GraphNode, Record};
+use serde_test::Configure;
+
+type Input = (
+    Record,
+    Vec<(char, Option<u8>, Option<u8>)>,
+    Vec<(u16, Mutation)>,
+);
+
+// Arbitrary input has to be rejected with an error. Dangling, duplicate or
+// forward references in particular must not panic.
+fuzz_target!(|input: (&[u8], Input)| {
+    let (data, (record, shape, mutations)) = input;
+
+    let mut deserializer = serde_json::Deserializer::from_slice(data);
+    let _ = Record::deserialize_state(&mut Counter::default(), &mut deserializer);
+
+    let mut deserializer = serde_json::Deserializer::from_slice(data);
+    let _ = GraphNode::deserialize_state(&mut SharedSeed::new(), &mut deserializer);
+
+    // Valid token streams with corrupted scalars reach the derived visitors
+    // with well formed structure but wrong field names, values and shared ids
+    let mut record_tokens = tokens(&Seeded::new(&Cell::new(0), &record));
+    mutate(&mut record_tokens, &mutations);
+    let mut deserializer = serde_test::Deserializer::new(&record_tokens);
+    let mut seed = Counter::default();
+    let _ = Seed::<_, Record>::new(&mut seed)
+        .readable()
+        .deserialize(&mut deserializer);
+
+    if let Some(root) = build_graph(&shape) {
+        let mut graph_tokens = tokens(&Seeded::new(&SerSharedSeed::new(), &*root));
+        mutate(&mut graph_tokens, &mutations);
+        let mut deserializer = serde_test::Deserializer::new(&graph_tokens);
+        let mut seed = SharedSeed::new();
+        let _ = Seed::<_, GraphNode>::new(&mut seed)
+            .readable()
+            .deserialize(&mut deserializer);
+    }
+});
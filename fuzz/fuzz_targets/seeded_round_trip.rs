@@ -0,0 +1,40 @@
+#![no_main]
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use libfuzzer_sys::fuzz_target;
+use serde_state::de::DeserializeState;
+use serde_state::ser::Seeded;
+use serde_state::shared::{SerSharedSeed, SharedSeed};
+use serde_state_fuzz::{build_graph, Counter, GraphNode, Record};
+
+fuzz_target!(|input: (Record, Vec<(char, Option<u8>, Option<u8>)>)| {
+    let (record, shape) = input;
+
+    let ser_seed = Cell::new(0);
+    let json = serde_json::to_vec(&Seeded::new(&ser_seed, &record)).unwrap();
+    assert_eq!(ser_seed.get(), record.counted());
+
+    let mut de_seed = Counter::default();
+    let mut deserializer = serde_json::Deserializer::from_slice(&json);
+    let decoded = Record::deserialize_state(&mut de_seed, &mut deserializer).unwrap();
+    assert_eq!(decoded, record);
+    assert_eq!(de_seed.0, record.counted());
+
+    if let Some(root) = build_graph(&shape) {
+        let json = serde_json::to_vec(&Seeded::new(&SerSharedSeed::new(), &*root)).unwrap();
+
+        let mut deserializer = serde_json::Deserializer::from_slice(&json);
+        let decoded = GraphNode::deserialize_state(&mut SharedSeed::new(), &mut deserializer).unwrap();
+        assert_eq!(decoded, *root);
+
+        // Sharing must survive the round trip, not just the values
+        if let (Some(left), Some(right)) = (&root.left, &root.right) {
+            assert_eq!(
+                Rc::ptr_eq(left, right),
+                Rc::ptr_eq(decoded.left.as_ref().unwrap(), decoded.right.as_ref().unwrap())
+            );
+        }
+    }
+});
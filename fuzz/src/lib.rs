@@ -0,0 +1,123 @@
+//! Seeded types exercised by the fuzz targets.
+
+use std::cell::Cell;
+use std::rc::Rc;
+
+use arbitrary::Arbitrary;
+use serde::de::{Deserialize, Deserializer};
+use serde::ser::{Serialize, Serializer};
+use serde_derive_state::{DeserializeState, SerializeState};
+use serde_state::de::DeserializeState;
+use serde_state::ser::SerializeState;
+use serde_state::shared::{SerSharedSeed, SharedSeed};
+
+pub mod tokens;
+
+/// Counts the `Counted` values seen while deserializing.
+#[derive(Default)]
+pub struct Counter(pub u32);
+
+/// A leaf which bumps the seed whenever it is serialized or deserialized.
+#[derive(Arbitrary, Debug, PartialEq)]
+pub struct Counted(pub u8);
+
+impl SerializeState<Cell<u32>> for Counted {
+    fn serialize_state<S>(&self, serializer: S, seed: &Cell<u32>) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        seed.set(seed.get() + 1);
+        self.0.serialize(serializer)
+    }
+}
+
+impl<'de> DeserializeState<'de, Counter> for Counted {
+    fn deserialize_state<D>(seed: &mut Counter, deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        seed.0 += 1;
+        u8::deserialize(deserializer).map(Counted)
+    }
+}
+
+#[derive(Arbitrary, Debug, PartialEq, SerializeState, DeserializeState)]
+#[serde(serialize_state = "Cell<u32>", deserialize_state = "Counter")]
+pub enum Choice {
+    Empty,
+    One(#[serde(state)] Counted),
+    Pair(u16, #[serde(state)] Counted),
+    Named {
+        #[serde(state)]
+        value: Counted,
+        flag: bool,
+    },
+}
+
+#[derive(Arbitrary, Debug, PartialEq, SerializeState, DeserializeState)]
+#[serde(serialize_state = "Cell<u32>", deserialize_state = "Counter")]
+pub struct Record {
+    #[serde(state)]
+    pub first: Counted,
+    pub plain: i32,
+    #[serde(state)]
+    pub many: Vec<Counted>,
+    #[serde(state)]
+    pub maybe: Option<Counted>,
+    #[serde(state)]
+    pub choice: Choice,
+}
+
+impl Record {
+    /// The number of `Counted` values in the record, which is how far the seed
+    /// should advance during a round trip.
+    pub fn counted(&self) -> u32 {
+        let choice = match self.choice {
+            Choice::Empty => 0,
+            Choice::One(_) | Choice::Pair(..) | Choice::Named { .. } => 1,
+        };
+        1 + self.many.len() as u32 + self.maybe.is_some() as u32 + choice
+    }
+}
+
+#[derive(Debug, PartialEq, SerializeState, DeserializeState)]
+#[serde(serialize_state = "SerSharedSeed")]
+#[serde(deserialize_state = "SharedSeed<Rc<GraphNode>>")]
+pub struct GraphNode {
+    pub data: char,
+    #[serde(
+        serialize_state_with = "serde_state::shared::serialize_option_shared",
+        deserialize_state_with = "serde_state::shared::deserialize_option_shared"
+    )]
+    pub left: Option<Rc<GraphNode>>,
+    #[serde(
+        serialize_state_with = "serde_state::shared::serialize_option_shared",
+        deserialize_state_with = "serde_state::shared::deserialize_option_shared"
+    )]
+    pub right: Option<Rc<GraphNode>>,
+}
+
+/// Comparing a graph walks every path through it, so keep graphs small.
+pub const MAX_GRAPH_NODES: usize = 16;
+
+/// Builds a graph in which node `i` may point at any of the nodes before it,
+/// so nodes are shared between parents but there are no cycles. Returns the
+/// last node built.
+pub fn build_graph(shape: &[(char, Option<u8>, Option<u8>)]) -> Option<Rc<GraphNode>> {
+    let mut nodes: Vec<Rc<GraphNode>> = Vec::new();
+    for &(data, left, right) in shape.iter().take(MAX_GRAPH_NODES) {
+        let pick = |child: Option<u8>| match child {
+            Some(child) if !nodes.is_empty() => {
+                Some(Rc::clone(&nodes[child as usize % nodes.len()]))
+            }
+            _ => None,
+        };
+        let node = GraphNode {
+            data,
+            left: pick(left),
+            right: pick(right),
+        };
+        nodes.push(Rc::new(node));
+    }
+    nodes.pop()
+}
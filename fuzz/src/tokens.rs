@@ -0,0 +1,381 @@
+//! Records what a value serializes to as `serde_test` tokens, so the targets
+//! can replay and corrupt it through `serde_test::Deserializer`.
+
+use serde::de::value::Error;
+use serde::ser::{self, Error as _, Serialize, Serializer};
+use serde_test::Token;
+
+/// The field names of every fuzzed type, plus one none of them know. A field
+/// name can only be replaced by another `'static` name, so mutations draw
+/// from this list.
+pub const FIELD_NAMES: &[&str] = &[
+    "first", "plain", "many", "maybe", "choice", "value", "flag", "data", "left", "right", "other",
+];
+
+/// Serializes `value` into the tokens `serde_test` would expect for it.
+pub fn tokens<T: ?Sized + Serialize>(value: &T) -> Vec<Token> {
+    let mut tokens = Tokens(Vec::new());
+    value.serialize(&mut tokens).unwrap();
+    tokens.0
+}
+
+/// A replacement for one scalar token.
+#[derive(arbitrary::Arbitrary, Debug)]
+pub enum Mutation {
+    Bool(bool),
+    U8(u8),
+    U16(u16),
+    I32(i32),
+    U32(u32),
+    Char(char),
+    Field(u8),
+}
+
+impl Mutation {
+    fn token(&self) -> Token {
+        match *self {
+            Mutation::Bool(v) => Token::Bool(v),
+            Mutation::U8(v) => Token::U8(v),
+            Mutation::U16(v) => Token::U16(v),
+            Mutation::I32(v) => Token::I32(v),
+            Mutation::U32(v) => Token::U32(v),
+            Mutation::Char(v) => Token::Char(v),
+            Mutation::Field(i) => Token::Str(FIELD_NAMES[i as usize % FIELD_NAMES.len()]),
+        }
+    }
+}
+
+/// Replaces scalar tokens, including field names and shared ids, while
+/// leaving the compound tokens alone. `serde_test::Deserializer` panics on
+/// a broken token structure, but a wrong scalar has to come back as an error
+/// from the deserializer under test.
+pub fn mutate(tokens: &mut [Token], mutations: &[(u16, Mutation)]) {
+    if tokens.is_empty() {
+        return;
+    }
+    for &(index, ref mutation) in mutations {
+        let token = &mut tokens[index as usize % tokens.len()];
+        match *token {
+            Token::Bool(_)
+            | Token::U8(_)
+            | Token::U16(_)
+            | Token::I32(_)
+            | Token::U32(_)
+            | Token::Char(_)
+            | Token::Str(_) => *token = mutation.token(),
+            _ => {}
+        }
+    }
+}
+
+struct Tokens(Vec<Token>);
+
+struct Compound<'a> {
+    tokens: &'a mut Tokens,
+    end: Token,
+}
+
+impl<'a> Serializer for &'a mut Tokens {
+    type Ok = ();
+    type Error = Error;
+
+    type SerializeSeq = Compound<'a>;
+    type SerializeTuple = Compound<'a>;
+    type SerializeTupleStruct = Compound<'a>;
+    type SerializeTupleVariant = Compound<'a>;
+    type SerializeMap = Compound<'a>;
+    type SerializeStruct = Compound<'a>;
+    type SerializeStructVariant = Compound<'a>;
+
+    fn serialize_bool(self, v: bool) -> Result<(), Error> {
+        self.0.push(Token::Bool(v));
+        Ok(())
+    }
+
+    fn serialize_i8(self, v: i8) -> Result<(), Error> {
+        self.0.push(Token::I8(v));
+        Ok(())
+    }
+
+    fn serialize_i16(self, v: i16) -> Result<(), Error> {
+        self.0.push(Token::I16(v));
+        Ok(())
+    }
+
+    fn serialize_i32(self, v: i32) -> Result<(), Error> {
+        self.0.push(Token::I32(v));
+        Ok(())
+    }
+
+    fn serialize_i64(self, v: i64) -> Result<(), Error> {
+        self.0.push(Token::I64(v));
+        Ok(())
+    }
+
+    fn serialize_u8(self, v: u8) -> Result<(), Error> {
+        self.0.push(Token::U8(v));
+        Ok(())
+    }
+
+    fn serialize_u16(self, v: u16) -> Result<(), Error> {
+        self.0.push(Token::U16(v));
+        Ok(())
+    }
+
+    fn serialize_u32(self, v: u32) -> Result<(), Error> {
+        self.0.push(Token::U32(v));
+        Ok(())
+    }
+
+    fn serialize_u64(self, v: u64) -> Result<(), Error> {
+        self.0.push(Token::U64(v));
+        Ok(())
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), Error> {
+        self.0.push(Token::F32(v));
+        Ok(())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), Error> {
+        self.0.push(Token::F64(v));
+        Ok(())
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), Error> {
+        self.0.push(Token::Char(v));
+        Ok(())
+    }
+
+    // Tokens only hold `'static` strings, and none of the fuzzed types
+    // serialize anything else.
+    fn serialize_str(self, _: &str) -> Result<(), Error> {
+        Err(Error::custom("borrowed strings cannot be recorded"))
+    }
+
+    fn serialize_bytes(self, _: &[u8]) -> Result<(), Error> {
+        Err(Error::custom("borrowed bytes cannot be recorded"))
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        self.0.push(Token::None);
+        Ok(())
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(self, value: &T) -> Result<(), Error> {
+        self.0.push(Token::Some);
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        self.0.push(Token::Unit);
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, name: &'static str) -> Result<(), Error> {
+        self.0.push(Token::UnitStruct { name });
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        self,
+        name: &'static str,
+        _: u32,
+        variant: &'static str,
+    ) -> Result<(), Error> {
+        self.0.push(Token::UnitVariant { name, variant });
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.0.push(Token::NewtypeStruct { name });
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        self,
+        name: &'static str,
+        _: u32,
+        variant: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.0.push(Token::NewtypeVariant { name, variant });
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Compound<'a>, Error> {
+        self.0.push(Token::Seq { len });
+        Ok(self.compound(Token::SeqEnd))
+    }
+
+    fn serialize_tuple(self, len: usize) -> Result<Compound<'a>, Error> {
+        self.0.push(Token::Tuple { len });
+        Ok(self.compound(Token::TupleEnd))
+    }
+
+    fn serialize_tuple_struct(self, name: &'static str, len: usize) -> Result<Compound<'a>, Error> {
+        self.0.push(Token::TupleStruct { name, len });
+        Ok(self.compound(Token::TupleStructEnd))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        name: &'static str,
+        _: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Compound<'a>, Error> {
+        self.0.push(Token::TupleVariant { name, variant, len });
+        Ok(self.compound(Token::TupleVariantEnd))
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Compound<'a>, Error> {
+        self.0.push(Token::Map { len });
+        Ok(self.compound(Token::MapEnd))
+    }
+
+    fn serialize_struct(self, name: &'static str, len: usize) -> Result<Compound<'a>, Error> {
+        self.0.push(Token::Struct { name, len });
+        Ok(self.compound(Token::StructEnd))
+    }
+
+    fn serialize_struct_variant(
+        self,
+        name: &'static str,
+        _: u32,
+        variant: &'static str,
+        len: usize,
+    ) -> Result<Compound<'a>, Error> {
+        self.0.push(Token::StructVariant { name, variant, len });
+        Ok(self.compound(Token::StructVariantEnd))
+    }
+}
+
+impl Tokens {
+    fn compound(&mut self, end: Token) -> Compound<'_> {
+        Compound { tokens: self, end }
+    }
+}
+
+impl<'a> Compound<'a> {
+    fn element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(&mut *self.tokens)
+    }
+
+    fn field<T: ?Sized + Serialize>(&mut self, key: &'static str, value: &T) -> Result<(), Error> {
+        self.tokens.0.push(Token::Str(key));
+        self.element(value)
+    }
+
+    fn finish(self) -> Result<(), Error> {
+        self.tokens.0.push(self.end);
+        Ok(())
+    }
+}
+
+impl<'a> ser::SerializeSeq for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeTuple for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeTupleStruct for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeTupleVariant for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeMap for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Error> {
+        self.element(key)
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        self.element(value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeStruct for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish()
+    }
+}
+
+impl<'a> ser::SerializeStructVariant for Compound<'a> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.field(key, value)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        self.finish()
+    }
+}
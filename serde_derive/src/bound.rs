@@ -1,5 +1,7 @@
 use std::collections::HashSet;
+use std::mem;
 
+use quote::ToTokens;
 use syn;
 use syn::punctuated::{Pair, Punctuated};
 
@@ -41,6 +43,21 @@ pub fn with_where_predicates(
     generics
 }
 
+// Container and field `bound` attributes may both spell out the same
+// predicate, such as a bound on the seed type. Only the first occurrence of
+// each predicate is kept.
+pub fn without_duplicate_predicates(generics: &syn::Generics) -> syn::Generics {
+    let mut generics = generics.clone();
+    if let Some(where_clause) = &mut generics.where_clause {
+        let mut seen = HashSet::new();
+        where_clause.predicates = mem::replace(&mut where_clause.predicates, Punctuated::new())
+            .into_iter()
+            .filter(|predicate| seen.insert(predicate.to_token_stream().to_string()))
+            .collect();
+    }
+    generics
+}
+
 pub fn with_where_predicates_from_fields(
     cont: &Container,
     generics: &syn::Generics,
@@ -38,7 +38,24 @@ pub fn expand_derive_deserialize(
     let delife = params.borrowed.de_lifetime();
     let serde = cont.attrs.serde_path();
 
-    let impl_block = if let Some(remote) = cont.attrs.remote() {
+    let impl_block = if let (Some(remote), true) = (cont.attrs.remote(), seeded) {
+        let vis = &input.vis;
+        let used = pretend::pretend_used(&cont);
+        let seed_ty = cont.attrs.deserialize_state().unwrap();
+        quote! {
+            impl #de_impl_generics #ident #ty_generics #where_clause {
+                // Same signature as the functions expected by `deserialize_state_with`
+                #[allow(unused_variables)]
+                #vis fn deserialize_state<__D>(__seed: &mut #seed_ty, __deserializer: __D) -> _serde::__private::Result<#remote #ty_generics, __D::Error>
+                where
+                    __D: _serde::Deserializer<#delife>,
+                {
+                    #used
+                    #body
+                }
+            }
+        }
+    } else if let Some(remote) = cont.attrs.remote() {
         let vis = &input.vis;
         let used = pretend::pretend_used(&cont);
         quote! {
@@ -57,15 +74,33 @@ pub fn expand_derive_deserialize(
         let (de_impl_generics, _, ty_generics, where_clause) = split_with_de_lifetime(&params);
         if seeded {
             let seed_ty = cont.attrs.deserialize_state().unwrap();
+            // The hook runs once the value is complete, so it can register the
+            // value in the seed.
+            let body = match cont.attrs.deserialize_state_finish() {
+                Some(finish) => quote! {
+                    match { #body } {
+                        _serde::__private::Ok(mut __value) => {
+                            #finish(__seed, &mut __value);
+                            _serde::__private::Ok(__value)
+                        }
+                        _serde::__private::Err(__err) => _serde::__private::Err(__err),
+                    }
+                },
+                None => quote!(#body),
+            };
+            let fn_deserialize_state_in_place = deserialize_state_in_place_body(&cont, &params);
             quote! {
                 #[automatically_derived]
                 impl #de_impl_generics _serde::de::DeserializeState<#delife, #seed_ty> for #ident #ty_generics #where_clause {
-
+                    // Unit structs and structs without seeded fields never touch the seed.
+                    #[allow(unused_variables)]
                     fn deserialize_state<__D>(__seed: &mut #seed_ty, __deserializer: __D) -> _serde::__private::Result<Self, __D::Error>
                         where __D: _serde::Deserializer<#delife>
                     {
                         #body
                     }
+
+                    #fn_deserialize_state_in_place
                 }
             }
         } else {
@@ -84,14 +119,86 @@ pub fn expand_derive_deserialize(
         }
     };
 
+    let inherent_impl = if seeded {
+        deserialize_inherent_impl(&cont, &params)
+    } else {
+        None
+    };
+    let impl_block = quote! {
+        #impl_block
+
+        #inherent_impl
+    };
+
     Ok(dummy::wrap_in_const(
         cont.attrs.custom_serde_path(),
-        "DESERIALIZE",
+        "DESERIALIZE_STATE",
         ident,
         impl_block,
     ))
 }
 
+// Helpers which are requested through container attributes and live in an
+// inherent impl next to the derived `DeserializeState` impl.
+fn deserialize_inherent_impl(cont: &Container, params: &Parameters) -> Option<TokenStream> {
+    let mut items = Vec::new();
+
+    if let (Data::Struct(Style::Struct, fields), true) = (&cont.data, cont.attrs.expose_fields()) {
+        let field_names = fields
+            .iter()
+            .filter(|field| !field.attrs.skip_deserializing())
+            .map(|field| field.attrs.name().deserialize_name());
+        items.push(quote! {
+            /// The names of the fields accepted by the derived `DeserializeState` impl
+            pub const FIELDS: &'static [&'static str] = &[ #(#field_names),* ];
+        });
+    }
+
+    if cont.attrs.deserialize_state_by_value() {
+        let seed_ty = cont.attrs.deserialize_state().unwrap();
+        let mut lifetimes = Vec::new();
+        let mut others = Vec::new();
+        for param in params.de_parameters.iter().flat_map(|params| params.iter()) {
+            match param {
+                syn::GenericParam::Lifetime(_) => lifetimes.push(param),
+                _ => others.push(param),
+            }
+        }
+        items.push(quote! {
+            /// Deserializes `Self` with a seed which is passed by value, returning the updated seed
+            /// together with the value
+            pub fn deserialize_state_by_value<#(#lifetimes,)* 'de, #(#others,)* __D>(
+                mut __seed: #seed_ty,
+                __deserializer: __D,
+            ) -> _serde::__private::Result<(Self, #seed_ty), __D::Error>
+            where
+                Self: _serde::de::DeserializeState<'de, #seed_ty>,
+                __D: _serde::Deserializer<'de>,
+            {
+                let __value = try!(<Self as _serde::de::DeserializeState<'de, #seed_ty>>::deserialize_state(
+                    &mut __seed,
+                    __deserializer,
+                ));
+                _serde::__private::Ok((__value, __seed))
+            }
+        });
+    }
+
+    if items.is_empty() {
+        return None;
+    }
+
+    let ident = &cont.ident;
+    let generics = bound::without_defaults(cont.generics);
+    let (impl_generics, ty_generics, where_clause) = generics.split_for_impl();
+    Some(quote! {
+        #[automatically_derived]
+        impl #impl_generics #ident #ty_generics #where_clause {
+            #(#items)*
+        }
+    })
+}
+
 fn precondition(cx: &Ctxt, cont: &Container) {
     precondition_sized(cx, cont);
     precondition_no_de_lifetime(cx, cont);
@@ -144,6 +251,10 @@ struct Parameters {
     /// remote type has a private field.
     has_getter: bool,
 
+    /// The seed is checkpointed before speculative attempts and restored if
+    /// they fail, as requested by serde(deserialize_state_checkpoint).
+    checkpoint: bool,
+
     de_parameters: Option<Vec<syn::GenericParam>>,
 }
 
@@ -164,7 +275,8 @@ impl Parameters {
             generics,
             borrowed,
             has_getter,
-            de_parameters: cont.attrs.de_parameters().map(|params| params.to_owned()),
+            checkpoint: seeded && cont.attrs.deserialize_state_checkpoint(),
+            de_parameters: de_parameters(cont, seeded),
         }
     }
 
@@ -186,7 +298,7 @@ fn build_generics(cont: &Container, borrowed: &BorrowedLifetimes, seeded: bool)
     let generics =
         bound::with_where_predicates_from_variants(cont, &generics, attr::Variant::de_bound);
 
-    match cont.attrs.de_bound() {
+    let generics = match cont.attrs.de_bound() {
         Some(predicates) => bound::with_where_predicates(&generics, predicates),
         None => {
             let mut generics = match *cont.attrs.default() {
@@ -215,7 +327,9 @@ fn build_generics(cont: &Container, borrowed: &BorrowedLifetimes, seeded: bool)
                 &parse_quote!(_serde::__private::Default),
             )
         }
-    }
+    };
+
+    bound::without_duplicate_predicates(&generics)
 }
 
 // Fields with a `skip_deserializing` or `deserialize_with` attribute, or which
@@ -297,6 +411,8 @@ fn borrowed_lifetimes(cont: &Container) -> BorrowedLifetimes {
 fn deserialize_body(cont: &Container, params: &Parameters) -> Fragment {
     if cont.attrs.transparent() {
         deserialize_transparent(cont, params)
+    } else if let Some(state_from) = cont.attrs.state_from() {
+        deserialize_state_from(state_from)
     } else if let Some(type_from) = cont.attrs.type_from() {
         deserialize_from(type_from)
     } else if let Some(type_try_from) = cont.attrs.type_try_from() {
@@ -370,6 +486,182 @@ fn deserialize_in_place_body(_cont: &Container, _params: &Parameters) -> Option<
     None
 }
 
+// Seeded structs with named fields deserialize each field into the existing
+// value, so that fields such as `Vec` keep their allocations. Everything else
+// uses the default `deserialize_state_in_place`, which assigns a freshly
+// deserialized value.
+fn deserialize_state_in_place_body(cont: &Container, params: &Parameters) -> Option<TokenStream> {
+    let cattrs = &cont.attrs;
+    let seed_ty = cattrs.deserialize_state()?;
+    let fields = match &cont.data {
+        Data::Struct(Style::Struct, fields) => fields,
+        _ => return None,
+    };
+    if params.has_getter
+        || cattrs.transparent()
+        || cattrs.type_from().is_some()
+        || cattrs.type_try_from().is_some()
+        || cattrs.state_from().is_some()
+        || cattrs.has_flatten()
+    {
+        return None;
+    }
+
+    let this = &params.this;
+    let (de_impl_generics, de_ty_generics, ty_generics, where_clause) =
+        split_with_de_and_seed_lifetime(params);
+    let delife = params.borrowed.de_lifetime();
+
+    let expecting = format!("struct {}", params.type_name());
+    let expecting = cattrs.expecting().unwrap_or(&expecting);
+
+    let fields_names: Vec<_> = fields
+        .iter()
+        .enumerate()
+        .filter(|&(_, field)| !field.attrs.skip_deserializing())
+        .map(|(i, field)| (field, field_i(i)))
+        .collect();
+
+    let field_names_idents: Vec<_> = fields_names
+        .iter()
+        .map(|&(field, ref name)| {
+            (
+                field.attrs.name().deserialize_name(),
+                name.clone(),
+                field.attrs.aliases(),
+            )
+        })
+        .collect();
+    let field_visitor = Stmts(deserialize_generated_identifier(
+        &field_names_idents,
+        cattrs,
+        false,
+        None,
+    ));
+    let field_names = field_names_idents.iter().map(|(name, _, _)| name);
+
+    let write_elements =
+        deserialize_seq_fields(params, fields, cattrs, expecting, FieldSlots::Place);
+
+    let let_flags = fields_names.iter().map(|&(_, ref name)| {
+        quote! {
+            let mut #name: bool = false;
+        }
+    });
+    let read_fields = deserialize_map_fields(params, fields, cattrs, FieldSlots::Place);
+
+    let check_flags = fields_names.iter().map(|&(field, ref name)| {
+        let member = &field.member;
+        let missing_expr = expr_is_missing(params, field, cattrs);
+        // If missing_expr unconditionally returns an error, don't try to
+        // assign its value to self.place.
+        if field.attrs.default().is_none()
+            && field.attrs.default_state().is_none()
+            && cattrs.default().is_none()
+            && (field.attrs.deserialize_with().is_some()
+                || field.attrs.deserialize_state_with().is_some())
+        {
+            let missing_expr = Stmts(missing_expr);
+            quote! {
+                if !#name {
+                    #missing_expr;
+                }
+            }
+        } else {
+            let missing_expr = Expr(missing_expr);
+            quote! {
+                if !#name {
+                    self.place.#member = #missing_expr;
+                };
+            }
+        }
+    });
+
+    let let_default = match cattrs.default() {
+        attr::Default::Default => Some(quote!(
+            let __default: #this #ty_generics = _serde::__private::Default::default();
+        )),
+        attr::Default::Path(path) => Some(quote!(
+            let __default: #this #ty_generics = #path();
+        )),
+        attr::Default::None => None,
+    };
+
+    let visitor_var = if fields_names.is_empty() {
+        quote!(_)
+    } else {
+        quote!(mut __seq)
+    };
+    let type_name = cattrs.name().deserialize_name();
+
+    let finish = cattrs.deserialize_state_finish().map(|finish| {
+        quote! {
+            #finish(__seed, __place);
+        }
+    });
+
+    Some(quote! {
+        fn deserialize_state_in_place<__D>(__seed: &mut #seed_ty, __deserializer: __D, __place: &mut Self) -> _serde::__private::Result<(), __D::Error>
+            where __D: _serde::Deserializer<#delife>
+        {
+            #field_visitor
+
+            struct __Visitor #de_impl_generics #where_clause {
+                seed: &'seed mut #seed_ty,
+                place: &'seed mut #this #ty_generics,
+                lifetime: _serde::__private::PhantomData<&#delife ()>,
+            }
+
+            impl #de_impl_generics _serde::de::Visitor<#delife> for __Visitor #de_ty_generics #where_clause {
+                type Value = ();
+
+                fn expecting(&self, __formatter: &mut _serde::__private::Formatter) -> _serde::__private::fmt::Result {
+                    _serde::__private::Formatter::write_str(__formatter, #expecting)
+                }
+
+                #[inline]
+                #[allow(unused_mut)]
+                fn visit_seq<__A>(mut self, #visitor_var: __A) -> _serde::__private::Result<Self::Value, __A::Error>
+                where
+                    __A: _serde::de::SeqAccess<#delife>,
+                {
+                    #let_default
+                    #(#write_elements)*
+                    _serde::__private::Ok(())
+                }
+
+                #[inline]
+                #[allow(unused_mut)]
+                fn visit_map<__A>(mut self, mut __map: __A) -> _serde::__private::Result<Self::Value, __A::Error>
+                where
+                    __A: _serde::de::MapAccess<#delife>,
+                {
+                    #(#let_flags)*
+                    #read_fields
+                    #let_default
+                    #(#check_flags)*
+                    _serde::__private::Ok(())
+                }
+            }
+
+            const FIELDS: &'static [&'static str] = &[ #(#field_names),* ];
+
+            try!(_serde::Deserializer::deserialize_struct(
+                __deserializer,
+                #type_name,
+                FIELDS,
+                __Visitor {
+                    seed: &mut *__seed,
+                    place: &mut *__place,
+                    lifetime: _serde::__private::PhantomData,
+                },
+            ));
+            #finish
+            _serde::__private::Ok(())
+        }
+    })
+}
+
 fn deserialize_transparent(cont: &Container, params: &Parameters) -> Fragment {
     let fields = match &cont.data {
         Data::Struct(_, fields) => fields,
@@ -379,12 +671,17 @@ fn deserialize_transparent(cont: &Container, params: &Parameters) -> Fragment {
     let this = &params.this;
     let transparent_field = fields.iter().find(|f| f.attrs.transparent()).unwrap();
 
-    let path = match transparent_field.attrs.deserialize_with() {
-        Some(path) => quote!(#path),
-        None => {
-            let span = transparent_field.original.span();
-            quote_spanned!(span=> _serde::Deserialize::deserialize)
-        }
+    let span = transparent_field.original.span();
+    let deserialize = if transparent_field.attrs.deserialize_state() {
+        let func = quote_spanned!(span=> _serde::de::DeserializeState::deserialize_state);
+        quote!(#func(__seed, __deserializer))
+    } else if let Some(path) = transparent_field.attrs.deserialize_state_with() {
+        quote_spanned!(path.span()=> #path(__seed, __deserializer))
+    } else if let Some(path) = transparent_field.attrs.deserialize_with() {
+        quote!(#path(__deserializer))
+    } else {
+        let func = quote_spanned!(span=> _serde::Deserialize::deserialize);
+        quote!(#func(__deserializer))
     };
 
     let assign = fields.iter().map(|field| {
@@ -403,7 +700,7 @@ fn deserialize_transparent(cont: &Container, params: &Parameters) -> Fragment {
 
     quote_block! {
         _serde::__private::Result::map(
-            #path(__deserializer),
+            #deserialize,
             |__transparent| #this { #(#assign),* })
     }
 }
@@ -416,6 +713,20 @@ fn deserialize_from(type_from: &syn::Type) -> Fragment {
     }
 }
 
+// The shadow type is deserialized with the seed, which is then handed to the
+// conversion as well.
+fn deserialize_state_from(state_from: &syn::Type) -> Fragment {
+    quote_block! {
+        let __shadow: #state_from = try!(_serde::de::DeserializeState::deserialize_state(
+            &mut *__seed,
+            __deserializer,
+        ));
+        _serde::__private::Result::map_err(
+            _serde::de::FromState::from_state(__shadow, __seed),
+            _serde::de::Error::custom)
+    }
+}
+
 fn deserialize_try_from(type_try_from: &syn::Type) -> Fragment {
     quote_block! {
         _serde::__private::Result::and_then(
@@ -686,39 +997,7 @@ fn deserialize_seq(
     };
     let expecting = cattrs.expecting().unwrap_or(&expecting);
 
-    let mut index_in_seq = 0_usize;
-    let let_values = vars.clone().zip(fields).map(|(var, field)| {
-        if field.attrs.skip_deserializing() {
-            let default = Expr(expr_is_missing(params, &field, cattrs));
-            quote! {
-                let #var = #default;
-            }
-        } else {
-            let (wrapper, wrapper_value) =
-                wrap_deserialize(params, field, cattrs.deserialize_state());
-            let visit = quote!({
-                    #wrapper
-                    try!(_serde::de::SeqAccess::next_element_seed(&mut __seq, #wrapper_value))
-                });
-            let value_if_none = match field.attrs.default() {
-                attr::Default::Default => quote!(_serde::__private::Default::default()),
-                attr::Default::Path(path) => quote!(#path()),
-                attr::Default::None => quote!(
-                    return _serde::__private::Err(_serde::de::Error::invalid_length(#index_in_seq, &#expecting));
-                ),
-            };
-            let assign = quote! {
-                let #var = match #visit {
-                    _serde::__private::Some(__value) => __value,
-                    _serde::__private::None => {
-                        #value_if_none
-                    }
-                };
-            };
-            index_in_seq += 1;
-            assign
-        }
-    });
+    let let_values = deserialize_seq_fields(params, fields, cattrs, expecting, FieldSlots::Locals);
 
     let mut result = if is_struct {
         let names = fields.iter().map(|f| &f.member);
@@ -759,6 +1038,89 @@ fn deserialize_seq(
     }
 }
 
+// Reads the fields from `__seq` in declaration order, into a local named after
+// each field or into `self.place`. Elements missing from the end of the
+// sequence take their field default, if there is one.
+fn deserialize_seq_fields(
+    params: &Parameters,
+    fields: &[Field],
+    cattrs: &attr::Container,
+    expecting: &str,
+    slots: FieldSlots,
+) -> Vec<TokenStream> {
+    let mut index_in_seq = 0_usize;
+    fields
+        .iter()
+        .enumerate()
+        .map(|(i, field)| {
+            let var = field_i(i);
+            let member = &field.member;
+            if field.attrs.skip_deserializing() {
+                let default = Expr(expr_is_missing(params, field, cattrs));
+                return match slots {
+                    FieldSlots::Locals => quote!(let #var = #default;),
+                    FieldSlots::Place => quote!(self.place.#member = #default;),
+                };
+            }
+            let default = if let Some(path) = field.attrs.default_state() {
+                Some(quote!(#path(&mut *self.seed)))
+            } else {
+                match field.attrs.default() {
+                    attr::Default::Default => Some(quote!(_serde::__private::Default::default())),
+                    attr::Default::Path(path) => Some(quote!(#path())),
+                    attr::Default::None => None,
+                }
+            };
+            let invalid_length = quote! {
+                return _serde::__private::Err(_serde::de::Error::invalid_length(#index_in_seq, &#expecting));
+            };
+            index_in_seq += 1;
+            let on_error = field_error(field, quote!(__err));
+            match slots {
+                FieldSlots::Locals => {
+                    let (wrapper, seed) = wrap_deserialize(params, field, cattrs.deserialize_state());
+                    let value_if_none = default.unwrap_or(invalid_length);
+                    quote! {
+                        let #var = {
+                            #wrapper
+                            match _serde::de::SeqAccess::next_element_seed(&mut __seq, #seed) {
+                                _serde::__private::Ok(_serde::__private::Some(__value)) => __value,
+                                _serde::__private::Ok(_serde::__private::None) => {
+                                    #value_if_none
+                                }
+                                _serde::__private::Err(__err) => {
+                                    return _serde::__private::Err(#on_error);
+                                }
+                            }
+                        };
+                    }
+                }
+                FieldSlots::Place => {
+                    let (wrapper, seed, value, write) = deserialize_in_place_seed(params, field, cattrs);
+                    let write_if_none = match default {
+                        Some(default) => quote!(self.place.#member = #default;),
+                        None => invalid_length,
+                    };
+                    quote!({
+                        #wrapper
+                        match _serde::de::SeqAccess::next_element_seed(&mut __seq, #seed) {
+                            _serde::__private::Ok(_serde::__private::Some(#value)) => {
+                                #write
+                            }
+                            _serde::__private::Ok(_serde::__private::None) => {
+                                #write_if_none
+                            }
+                            _serde::__private::Err(__err) => {
+                                return _serde::__private::Err(#on_error);
+                            }
+                        }
+                    })
+                }
+            }
+        })
+        .collect()
+}
+
 fn deserialize_seq_in_place(
     params: &Parameters,
     fields: &[Field],
@@ -1718,18 +2080,53 @@ fn deserialize_untagged_enum(
     variants: &[Variant],
     cattrs: &attr::Container,
 ) -> Fragment {
+    // Failed attempts may already have used the seed, so roll it back before
+    // trying the next variant.
+    let (checkpoint, restore) = if params.checkpoint {
+        (
+            Some(quote! {
+                let __checkpoint = _serde::de::Checkpoint::checkpoint(&*__seed);
+            }),
+            Some(quote! {
+                _serde::de::Checkpoint::restore(&mut *__seed, __checkpoint);
+            }),
+        )
+    } else {
+        (None, None)
+    };
+
+    // A custom buffer replays the input through `IntoDeserializer` for a
+    // reference to it.
+    let (buffer, replay) = match cattrs.untagged_buffer() {
+        Some(path) => (
+            quote!(#path(&mut *__seed, __deserializer)),
+            quote!(_serde::de::IntoDeserializer::<__D::Error>::into_deserializer(&__content)),
+        ),
+        None => (
+            quote!(<_serde::__private::de::Content as _serde::Deserialize>::deserialize(__deserializer)),
+            quote!(
+                _serde::__private::de::ContentRefDeserializer::<__D::Error>::new(&__content)
+            ),
+        ),
+    };
+
     let attempts = variants
         .iter()
         .filter(|variant| !variant.attrs.skip_deserializing())
         .map(|variant| {
-            Expr(deserialize_untagged_variant(
+            let attempt = Expr(deserialize_untagged_variant(
                 params,
                 variant,
                 cattrs,
-                quote!(
-                    _serde::__private::de::ContentRefDeserializer::<__D::Error>::new(&__content)
-                ),
-            ))
+                replay.clone(),
+            ));
+            quote! {
+                #checkpoint
+                if let _serde::__private::Ok(__ok) = #attempt {
+                    return _serde::__private::Ok(__ok);
+                }
+                #restore
+            }
         });
 
     // TODO this message could be better by saving the errors from the failed
@@ -1745,13 +2142,9 @@ fn deserialize_untagged_enum(
     let fallthrough_msg = cattrs.expecting().unwrap_or(&fallthrough_msg);
 
     quote_block! {
-        let __content = try!(<_serde::__private::de::Content as _serde::Deserialize>::deserialize(__deserializer));
+        let __content = try!(#buffer);
 
-        #(
-            if let _serde::__private::Ok(__ok) = #attempts {
-                return _serde::__private::Ok(__ok);
-            }
-        )*
+        #(#attempts)*
 
         _serde::__private::Err(_serde::de::Error::custom(#fallthrough_msg))
     }
@@ -1945,7 +2338,7 @@ fn deserialize_untagged_newtype_variant(
     quote_block! {
         #wrapper
         _serde::__private::Result::map(
-            _serde::de::DeserializeSeed>::deserialize(#wrapper_value, #deserializer),
+            _serde::de::DeserializeSeed::deserialize(#wrapper_value, #deserializer),
             #this::#variant_ident,
         )
     }
@@ -2516,82 +2909,19 @@ fn deserialize_map(
         None
     };
 
-    // Match arms to extract a value for a field.
-    let value_arms = fields_names
+    let read_fields = deserialize_map_fields(params, fields, cattrs, FieldSlots::Locals);
+
+    let extract_values = fields_names
         .iter()
         .filter(|&&(field, _)| !field.attrs.skip_deserializing() && !field.attrs.flatten())
         .map(|(field, name)| {
-            let deser_name = field.attrs.name().deserialize_name();
+            let missing_expr = Match(expr_is_missing(params, &field, cattrs));
 
-            let (wrapper, wrapper_value) =
-                wrap_deserialize(params, field, cattrs.deserialize_state());
-            let visit = quote!({
-                #wrapper
-                match _serde::de::MapAccess::next_value_seed(&mut __map, #wrapper_value) {
-                    _serde::__private::Ok(__wrapper) => __wrapper,
-                    _serde::__private::Err(__err) => {
-                        return _serde::__private::Err(__err);
-                    }
-                }
-            });
             quote! {
-                __Field::#name => {
-                    if _serde::__private::Option::is_some(&#name) {
-                        return _serde::__private::Err(<__A::Error as _serde::de::Error>::duplicate_field(#deser_name));
-                    }
-                    #name = _serde::__private::Some(#visit);
-                }
-            }
-        });
-
-    // Visit ignored values to consume them
-    let ignored_arm = if cattrs.has_flatten() {
-        Some(quote! {
-            __Field::__other(__name) => {
-                __collect.push(_serde::__private::Some((
-                    __name,
-                    try!(_serde::de::MapAccess::next_value(&mut __map)))));
-            }
-        })
-    } else if cattrs.deny_unknown_fields() {
-        None
-    } else {
-        Some(quote! {
-            _ => { let _ = try!(_serde::de::MapAccess::next_value::<_serde::de::IgnoredAny>(&mut __map)); }
-        })
-    };
-
-    let all_skipped = fields.iter().all(|field| field.attrs.skip_deserializing());
-    let match_keys = if cattrs.deny_unknown_fields() && all_skipped {
-        quote! {
-            // FIXME: Once we drop support for Rust 1.15:
-            // let _serde::__private::None::<__Field> = try!(_serde::de::MapAccess::next_key(&mut __map));
-            _serde::__private::Option::map(
-                try!(_serde::de::MapAccess::next_key::<__Field>(&mut __map)),
-                |__impossible| match __impossible {});
-        }
-    } else {
-        quote! {
-            while let _serde::__private::Some(__key) = try!(_serde::de::MapAccess::next_key::<__Field>(&mut __map)) {
-                match __key {
-                    #(#value_arms)*
-                    #ignored_arm
-                }
-            }
-        }
-    };
-
-    let extract_values = fields_names
-        .iter()
-        .filter(|&&(field, _)| !field.attrs.skip_deserializing() && !field.attrs.flatten())
-        .map(|(field, name)| {
-            let missing_expr = Match(expr_is_missing(params, &field, cattrs));
-
-            quote! {
-                let #name = match #name {
-                    _serde::__private::Some(#name) => #name,
-                    _serde::__private::None => #missing_expr
-                };
+                let #name = match #name {
+                    _serde::__private::Some(#name) => #name,
+                    _serde::__private::None => #missing_expr
+                };
             }
         });
 
@@ -2600,15 +2930,23 @@ fn deserialize_map(
         .filter(|&&(field, _)| field.attrs.flatten() && !field.attrs.skip_deserializing())
         .map(|(field, name)| {
             let field_ty = field.ty;
-            let func = match field.attrs.deserialize_with() {
-                None => {
-                    let span = field.original.span();
-                    quote_spanned!(span=> _serde::de::Deserialize::deserialize)
-                }
-                Some(path) => quote!(#path),
+            let span = field.original.span();
+            // Seeded flattened fields get the seed as their first argument
+            let (func, seed) = if field.attrs.deserialize_state() {
+                (
+                    quote_spanned!(span=> _serde::de::DeserializeState::deserialize_state),
+                    Some(quote!(&mut *self.seed,)),
+                )
+            } else if let Some(path) = field.attrs.deserialize_state_with() {
+                (quote!(#path), Some(quote_spanned!(path.span()=> &mut *self.seed,)))
+            } else if let Some(path) = field.attrs.deserialize_with() {
+                (quote!(#path), None)
+            } else {
+                (quote_spanned!(span=> _serde::de::Deserialize::deserialize), None)
             };
             quote! {
                 let #name: #field_ty = try!(#func(
+                    #seed
                     _serde::__private::de::FlatMapDeserializer(
                         &mut __collect,
                         _serde::__private::PhantomData)));
@@ -2670,7 +3008,7 @@ fn deserialize_map(
 
         #let_collect
 
-        #match_keys
+        #read_fields
 
         #let_default
 
@@ -2684,6 +3022,191 @@ fn deserialize_map(
     }
 }
 
+// Reads every key of `__map`, storing the fields in `slots`.
+//
+// Fields which use the seed, including to produce their default, are
+// deserialized in declaration order so that the seed observes the same
+// sequence of mutations whatever order the keys arrive in. A seeded field which
+// arrives before an earlier seeded field is buffered and deserialized once the
+// map has been consumed, after the defaults of any missing fields before it.
+fn deserialize_map_fields(
+    params: &Parameters,
+    fields: &[Field],
+    cattrs: &attr::Container,
+    slots: FieldSlots,
+) -> TokenStream {
+    let fields_names: Vec<_> = fields
+        .iter()
+        .enumerate()
+        .filter(|&(_, field)| !field.attrs.skip_deserializing() && !field.attrs.flatten())
+        .map(|(i, field)| (field, field_i(i)))
+        .collect();
+
+    let seeded: Vec<_> = if cattrs.deserialize_state().is_some() {
+        fields_names
+            .iter()
+            .filter(|&&(field, _)| uses_seed(field) || field.attrs.default_state().is_some())
+            .collect()
+    } else {
+        Vec::new()
+    };
+    let ordered_seed = seeded.len() > 1;
+    let seeded_position = |name: &Ident| {
+        if ordered_seed {
+            seeded.iter().position(|&&(_, ref seeded_name)| seeded_name == name)
+        } else {
+            None
+        }
+    };
+    let buffered = |name: &Ident| format_ident!("{}_buffered", name);
+
+    let let_buffered = seeded.iter().skip(1).map(|&&(_, ref name)| {
+        let buffered = buffered(name);
+        quote! {
+            let mut #buffered: _serde::__private::Option<_serde::private::de::BufferedContent> = _serde::__private::None;
+        }
+    });
+    let let_next_seeded = if ordered_seed {
+        Some(quote! {
+            let mut __next_seeded: usize = 0;
+        })
+    } else {
+        None
+    };
+
+    // Match arms to extract a value for a field.
+    let value_arms = fields_names.iter().map(|&(field, ref name)| {
+        let deser_name = field.attrs.name().deserialize_name();
+        let is_set = slots.is_set(name);
+        let store = slots.store(params, field, cattrs, name, &|seed| {
+            quote!(_serde::de::MapAccess::next_value_seed(&mut __map, #seed))
+        });
+        match seeded_position(name) {
+            Some(0) => quote! {
+                __Field::#name => {
+                    if #is_set {
+                        return _serde::__private::Err(<__A::Error as _serde::de::Error>::duplicate_field(#deser_name));
+                    }
+                    #store
+                    __next_seeded += 1;
+                }
+            },
+            Some(position) => {
+                let buffered = buffered(name);
+                quote! {
+                    __Field::#name => {
+                        if #is_set || _serde::__private::Option::is_some(&#buffered) {
+                            return _serde::__private::Err(<__A::Error as _serde::de::Error>::duplicate_field(#deser_name));
+                        }
+                        if __next_seeded == #position {
+                            #store
+                            __next_seeded += 1;
+                        } else {
+                            #buffered = _serde::__private::Some(try!(_serde::de::MapAccess::next_value(&mut __map)));
+                        }
+                    }
+                }
+            }
+            None => quote! {
+                __Field::#name => {
+                    if #is_set {
+                        return _serde::__private::Err(<__A::Error as _serde::de::Error>::duplicate_field(#deser_name));
+                    }
+                    #store
+                }
+            },
+        }
+    });
+
+    // Deserialize the buffered seeded fields and the defaults of missing ones,
+    // in declaration order
+    let extract_buffered = if ordered_seed {
+        seeded
+            .iter()
+            .enumerate()
+            .map(|(position, &&(field, ref name))| {
+                let extract = if position == 0 {
+                    None
+                } else {
+                    let buffered = buffered(name);
+                    let store = slots.store(params, field, cattrs, name, &|seed| {
+                        quote! {
+                            _serde::de::DeserializeSeed::deserialize(
+                                #seed,
+                                _serde::private::de::BufferedContent::into_deserializer::<__A::Error>(__content),
+                            )
+                        }
+                    });
+                    Some(quote! {
+                        if let _serde::__private::Some(__content) = #buffered {
+                            #store
+                        }
+                    })
+                };
+                let default = field.attrs.default_state().map(|path| {
+                    let is_set = slots.is_set(name);
+                    let write = slots.write(field, name, quote!(#path(&mut *self.seed)));
+                    quote! {
+                        if !#is_set {
+                            #write
+                        }
+                    }
+                });
+                quote! {
+                    #extract
+                    #default
+                }
+            })
+            .collect()
+    } else {
+        Vec::new()
+    };
+
+    // Visit ignored values to consume them
+    let ignored_arm = if cattrs.has_flatten() {
+        Some(quote! {
+            __Field::__other(__name) => {
+                __collect.push(_serde::__private::Some((
+                    __name,
+                    try!(_serde::de::MapAccess::next_value(&mut __map)))));
+            }
+        })
+    } else if cattrs.deny_unknown_fields() {
+        None
+    } else {
+        Some(quote! {
+            _ => { let _ = try!(_serde::de::MapAccess::next_value::<_serde::de::IgnoredAny>(&mut __map)); }
+        })
+    };
+
+    let all_skipped = fields.iter().all(|field| field.attrs.skip_deserializing());
+    let match_keys = if cattrs.deny_unknown_fields() && all_skipped {
+        quote! {
+            // FIXME: Once we drop support for Rust 1.15:
+            // let _serde::__private::None::<__Field> = try!(_serde::de::MapAccess::next_key(&mut __map));
+            _serde::__private::Option::map(
+                try!(_serde::de::MapAccess::next_key::<__Field>(&mut __map)),
+                |__impossible| match __impossible {});
+        }
+    } else {
+        quote! {
+            while let _serde::__private::Some(__key) = try!(_serde::de::MapAccess::next_key::<__Field>(&mut __map)) {
+                match __key {
+                    #(#value_arms)*
+                    #ignored_arm
+                }
+            }
+        }
+    };
+
+    quote! {
+        #(#let_buffered)*
+        #let_next_seeded
+        #match_keys
+        #(#extract_buffered)*
+    }
+}
+
 fn deserialize_struct_as_struct_in_place_visitor(
     params: &Parameters,
     fields: &[Field],
@@ -2877,6 +3400,25 @@ fn wrap_deserialize(
     params: &Parameters,
     field: &Field,
     seed_ty: Option<&syn::Type>,
+) -> (TokenStream, TokenStream) {
+    let (wrapper, wrapper_value) = wrap_deserialize_value(params, field, seed_ty);
+    match field.attrs.deserialize_state_validate() {
+        Some(validate) => wrap_deserialize_state_validate(
+            params,
+            seed_ty.expect("deserialize_state"),
+            field.ty,
+            wrapper,
+            wrapper_value,
+            validate,
+        ),
+        None => (wrapper, wrapper_value),
+    }
+}
+
+fn wrap_deserialize_value(
+    params: &Parameters,
+    field: &Field,
+    seed_ty: Option<&syn::Type>,
 ) -> (TokenStream, TokenStream) {
     match (
         field.attrs.deserialize_state(),
@@ -2891,13 +3433,133 @@ fn wrap_deserialize(
             )
         }
         (true, _, _) => (quote!(), quote!(_serde::de::Seed::new(&mut *self.seed))),
-        (_, Some(path), _) => {
-            wrap_deserialize_state_with(params, seed_ty.expect("deserialize_state"), field.ty, path)
-        }
+        (_, Some(path), _) => wrap_deserialize_state_with(
+            params,
+            seed_ty.expect("deserialize_state"),
+            field.ty,
+            field.attrs.deserialize_state_try_from(),
+            path,
+        ),
         (_, _, Some(path)) => wrap_deserialize_field_with(params, field.ty, path),
     }
 }
 
+fn uses_seed(field: &Field) -> bool {
+    field.attrs.deserialize_state()
+        || field.attrs.deserialize_state_with().is_some()
+        || field.attrs.deserialize_state_validate().is_some()
+}
+
+// Errors from fields which are deserialized with the seed are prefixed with the
+// name of the field, since they often come from deep inside user code where the
+// path to the failing value is otherwise lost.
+fn field_error(field: &Field, err: TokenStream) -> TokenStream {
+    if uses_seed(field) {
+        let name = field.attrs.name().deserialize_name();
+        quote!(_serde::private::de::field_error(#err, #name))
+    } else {
+        err
+    }
+}
+
+// Where a seeded visitor stores the fields it reads: in `Option` locals named
+// after each field, or directly in `self.place` with a `bool` local recording
+// which fields have been written.
+#[derive(Clone, Copy)]
+enum FieldSlots {
+    Locals,
+    Place,
+}
+
+impl FieldSlots {
+    fn is_set(self, name: &Ident) -> TokenStream {
+        match self {
+            FieldSlots::Locals => quote!(_serde::__private::Option::is_some(&#name)),
+            FieldSlots::Place => quote!(#name),
+        }
+    }
+
+    // Stores `value` in the slot called `name`.
+    fn write(self, field: &Field, name: &Ident, value: TokenStream) -> TokenStream {
+        match self {
+            FieldSlots::Locals => quote!(#name = _serde::__private::Some(#value);),
+            FieldSlots::Place => {
+                let member = &field.member;
+                quote! {
+                    self.place.#member = #value;
+                    #name = true;
+                }
+            }
+        }
+    }
+
+    // Deserializes `field` with `deserialize`, given the seed to use, and
+    // stores the value in the slot called `name`.
+    fn store(
+        self,
+        params: &Parameters,
+        field: &Field,
+        cattrs: &attr::Container,
+        name: &Ident,
+        deserialize: &Fn(TokenStream) -> TokenStream,
+    ) -> TokenStream {
+        let (wrapper, seed, value, write) = match self {
+            FieldSlots::Locals => {
+                let (wrapper, seed) = wrap_deserialize(params, field, cattrs.deserialize_state());
+                let write = quote!(#name = _serde::__private::Some(__value););
+                (wrapper, seed, quote!(__value), write)
+            }
+            FieldSlots::Place => {
+                let (wrapper, seed, value, write) =
+                    deserialize_in_place_seed(params, field, cattrs);
+                (wrapper, seed, value, quote!(#write #name = true;))
+            }
+        };
+        let deserialize = deserialize(seed);
+        let on_error = field_error(field, quote!(__err));
+        quote!({
+            #wrapper
+            match #deserialize {
+                _serde::__private::Ok(#value) => {
+                    #write
+                }
+                _serde::__private::Err(__err) => {
+                    return _serde::__private::Err(#on_error);
+                }
+            }
+        })
+    }
+}
+
+// The seed writing `field` of `self.place`, after the wrapper it needs,
+// followed by the pattern for the value the seed produces and the statements
+// assigning that value to the field. Seeds which deserialize into the field
+// itself produce `()`.
+fn deserialize_in_place_seed(
+    params: &Parameters,
+    field: &Field,
+    cattrs: &attr::Container,
+) -> (TokenStream, TokenStream, TokenStream, TokenStream) {
+    let member = &field.member;
+    let plain = !uses_seed(field) && field.attrs.deserialize_with().is_none();
+    if plain {
+        let seed = quote!(_serde::private::de::InPlaceSeed(&mut self.place.#member));
+        (quote!(), seed, quote!(()), quote!())
+    } else if field.attrs.deserialize_state() && field.attrs.deserialize_state_validate().is_none()
+    {
+        let seed = quote!(_serde::private::de::InPlaceStateSeed::new(&mut *self.seed, &mut self.place.#member));
+        (quote!(), seed, quote!(()), quote!())
+    } else {
+        let (wrapper, seed) = wrap_deserialize(params, field, cattrs.deserialize_state());
+        (
+            wrapper,
+            seed,
+            quote!(__value),
+            quote!(self.place.#member = __value;),
+        )
+    }
+}
+
 /// This function wraps the expression in `#[serde(deserialize_with = "...")]`
 /// in a trait to prevent it from accessing the internal `Deserialize` state.
 fn wrap_deserialize_with(
@@ -2995,26 +3657,59 @@ fn wrap_deserialize_state_with(
     params: &Parameters,
     seed_ty: &syn::Type,
     field_ty: &syn::Type,
+    try_from: Option<&syn::Type>,
     deserialize_with: &syn::Path,
 ) -> (TokenStream, TokenStream) {
     let this = &params.this;
     let (de_impl_generics, de_ty_generics, ty_generics, where_clause) =
         split_with_de_and_seed_lifetime(params);
+    let delife = params.borrowed.de_lifetime();
+
+    // The seed argument carries the span of the attribute, so a function which
+    // expects a different seed type is reported at the `#[serde(...)]` that names it
+    let span = deserialize_with.span();
+    let reborrowed_seed = quote_spanned!(span=> &mut *self.seed);
+    let seed = quote_spanned!(span=> self.seed);
+
+    let value = match try_from {
+        // The seed may have been used before the conversion fails
+        Some(try_from) if params.checkpoint => quote! {
+            let __checkpoint = _serde::de::Checkpoint::checkpoint(&*self.seed);
+            let __raw: _serde::__private::Result<#try_from, __D::Error> =
+                #deserialize_with(#reborrowed_seed, __deserializer);
+            let __value = _serde::__private::Result::and_then(__raw, |__raw| {
+                _serde::__private::TryFrom::try_from(__raw)
+                    .map_err(<__D::Error as _serde::de::Error>::custom)
+            });
+            if _serde::__private::Result::is_err(&__value) {
+                _serde::de::Checkpoint::restore(self.seed, __checkpoint);
+            }
+            __value
+        },
+        Some(try_from) => quote! {
+            let __raw: #try_from = try!(#deserialize_with(#seed, __deserializer));
+            _serde::__private::TryFrom::try_from(__raw)
+                .map_err(<__D::Error as _serde::de::Error>::custom)
+        },
+        None => quote! {
+            _serde::__private::Ok(try!(#deserialize_with(#seed, __deserializer)))
+        },
+    };
 
     let wrapper = quote! {
         struct __DeserializeWith #de_impl_generics #where_clause {
             seed: &'seed mut #seed_ty,
             phantom: _serde::__private::PhantomData<#this #ty_generics>,
-            lifetime: _serde::__private::PhantomData<&'de ()>,
+            lifetime: _serde::__private::PhantomData<&#delife ()>,
         }
 
-        impl #de_impl_generics _serde::de::DeserializeSeed<'de> for __DeserializeWith #de_ty_generics #where_clause {
+        impl #de_impl_generics _serde::de::DeserializeSeed<#delife> for __DeserializeWith #de_ty_generics #where_clause {
             type Value = #field_ty;
 
             fn deserialize<__D>(self, __deserializer: __D) -> _serde::__private::Result<#field_ty, __D::Error>
-                where __D: _serde::Deserializer<'de>
+                where __D: _serde::Deserializer<#delife>
             {
-                _serde::__private::Ok(try!(#deserialize_with(self.seed, __deserializer)))
+                #value
             }
         }
     };
@@ -3030,7 +3725,67 @@ fn wrap_deserialize_state_with(
     (wrapper, wrapper_value)
 }
 
+/// This function wraps the deserialization of a field with
+/// `#[serde(deserialize_state_validate = "...")]` so that the decoded value is
+/// handed to the validation function together with the seed.
+fn wrap_deserialize_state_validate(
+    params: &Parameters,
+    seed_ty: &syn::Type,
+    field_ty: &syn::Type,
+    inner: TokenStream,
+    inner_value: TokenStream,
+    validate: &syn::ExprPath,
+) -> (TokenStream, TokenStream) {
+    let this = &params.this;
+    let (de_impl_generics, de_ty_generics, ty_generics, where_clause) =
+        split_with_de_and_seed_lifetime(params);
+    let delife = params.borrowed.de_lifetime();
+
+    let wrapper = quote! {
+        struct __DeserializeValidate #de_impl_generics #where_clause {
+            seed: &'seed mut #seed_ty,
+            phantom: _serde::__private::PhantomData<#this #ty_generics>,
+            lifetime: _serde::__private::PhantomData<&#delife ()>,
+        }
+
+        impl #de_impl_generics _serde::de::DeserializeSeed<#delife> for __DeserializeValidate #de_ty_generics #where_clause {
+            type Value = #field_ty;
+
+            #[allow(unused_mut)]
+            fn deserialize<__D>(mut self, __deserializer: __D) -> _serde::__private::Result<#field_ty, __D::Error>
+                where __D: _serde::Deserializer<#delife>
+            {
+                let __value = {
+                    #inner
+                    try!(_serde::de::DeserializeSeed::deserialize(#inner_value, __deserializer))
+                };
+                match #validate(self.seed, &__value) {
+                    _serde::__private::Ok(()) => _serde::__private::Ok(__value),
+                    _serde::__private::Err(__err) => {
+                        _serde::__private::Err(<__D::Error as _serde::de::Error>::custom(__err))
+                    }
+                }
+            }
+        }
+    };
+
+    let wrapper_value = quote! {
+        __DeserializeValidate {
+            seed: &mut *self.seed,
+            phantom: _serde::__private::PhantomData::<#this #ty_generics>,
+            lifetime: _serde::__private::PhantomData,
+        }
+    };
+
+    (wrapper, wrapper_value)
+}
+
 fn expr_is_missing(params: &Parameters, field: &Field, cattrs: &attr::Container) -> Fragment {
+    // Only reached from visitors of seeded structs, which hold the seed.
+    if let Some(path) = field.attrs.default_state() {
+        return quote_expr!(#path(&mut *self.seed));
+    }
+
     match field.attrs.default() {
         attr::Default::Default => {
             let span = field.original.span();
@@ -3077,14 +3832,60 @@ fn effective_style(variant: &Variant) -> Style {
     }
 }
 
+// Lifetimes which only appear in the seed type, as in
+// `deserialize_state = "&'a Interner"`, are declared on the impl as if they
+// had been listed in `de_parameters`.
+fn de_parameters(cont: &Container, seeded: bool) -> Option<Vec<syn::GenericParam>> {
+    let mut de_parameters = cont.attrs.de_parameters().map(|params| params.to_owned());
+    let seed_ty = match cont.attrs.deserialize_state() {
+        Some(seed_ty) if seeded => seed_ty,
+        _ => return de_parameters,
+    };
+
+    let mut lifetimes = BTreeSet::new();
+    attr::collect_lifetimes(seed_ty, &mut lifetimes);
+    for lifetime in lifetimes {
+        let declared = lifetime.ident == "static"
+            || lifetime.ident == "_"
+            || lifetime.ident == "de"
+            || cont.generics.lifetimes().any(|def| def.lifetime == lifetime)
+            || de_parameters.iter().flatten().any(|param| match param {
+                syn::GenericParam::Lifetime(def) => def.lifetime == lifetime,
+                _ => false,
+            });
+        if !declared {
+            de_parameters
+                .get_or_insert_with(Vec::new)
+                .push(syn::GenericParam::Lifetime(syn::LifetimeDef::new(lifetime)));
+        }
+    }
+    de_parameters
+}
+
+// The generics of the type followed by `de_parameters`. Lifetimes must be
+// declared before any other parameter, so lifetimes from `de_parameters` go
+// after the type's own lifetimes rather than at the end.
+fn with_de_parameters(params: &Parameters) -> syn::Generics {
+    let mut generics = params.generics.clone();
+    if let Some(ref de_parameters) = params.de_parameters {
+        for param in de_parameters {
+            match param {
+                syn::GenericParam::Lifetime(_) => {
+                    let index = generics.lifetimes().count();
+                    generics.params.insert(index, param.clone());
+                }
+                _ => generics.params.push(param.clone()),
+            }
+        }
+    }
+    generics
+}
+
 struct DeImplGenerics<'a>(&'a Parameters);
 
 impl<'a> ToTokens for DeImplGenerics<'a> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
-        let mut generics = self.0.generics.clone();
-        if let Some(ref params) = self.0.de_parameters {
-            generics.params.extend(params.iter().cloned());
-        }
+        let mut generics = with_de_parameters(self.0);
         if let Some(de_lifetime) = self.0.borrowed.de_lifetime_def() {
             generics.params = Some(syn::GenericParam::Lifetime(de_lifetime))
                 .into_iter()
@@ -3151,11 +3952,7 @@ struct DeTypeGenerics<'a>(&'a Parameters);
 
 impl<'a> ToTokens for DeTypeGenerics<'a> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
-        let mut generics = self.0.generics.clone();
-        if let Some(ref params) = self.0.de_parameters {
-            generics.params.extend(params.iter().cloned());
-        }
-        let mut generics = self.0.generics.clone();
+        let mut generics = with_de_parameters(self.0);
         if self.0.borrowed.de_lifetime_def().is_some() {
             generics.params.insert(0, lifetime("'de").into());
         }
@@ -3219,23 +4016,25 @@ struct DeSeedImplGenerics<'a>(&'a Parameters);
 
 impl<'a> ToTokens for DeSeedImplGenerics<'a> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
-        let mut generics = self.0.generics.clone();
-        if let Some(ref params) = self.0.de_parameters {
-            generics.params.extend(params.iter().cloned());
-        }
+        let mut generics = with_de_parameters(self.0);
+        // Everything the visitor holds must outlive the `&'seed mut` borrow of
+        // the seed, including lifetimes brought in through `de_parameters`.
+        let seed_lifetime = syn::Lifetime::new("'seed", Span::call_site());
         for param in &mut generics.params {
-            if let syn::GenericParam::Type(ref mut param) = *param {
-                param
-                    .bounds
-                    .push(syn::TypeParamBound::Lifetime(syn::Lifetime::new(
-                        "'seed",
-                        Span::call_site(),
-                    )));
+            match param {
+                syn::GenericParam::Lifetime(param) => {
+                    param.bounds.push(seed_lifetime.clone());
+                }
+                syn::GenericParam::Type(param) => {
+                    param
+                        .bounds
+                        .push(syn::TypeParamBound::Lifetime(seed_lifetime.clone()));
+                }
+                syn::GenericParam::Const(_) => {}
             }
         }
         if let Some(mut de) = self.0.borrowed.de_lifetime_def() {
-            de.bounds
-                .push(syn::Lifetime::new("'seed", Span::call_site()));
+            de.bounds.push(seed_lifetime);
             generics.params.insert(0, de.into());
         }
         generics.params.insert(0, lifetime("'seed").into());
@@ -3248,11 +4047,12 @@ struct DeSeedTypeGenerics<'a>(&'a Parameters);
 
 impl<'a> ToTokens for DeSeedTypeGenerics<'a> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
-        let mut generics = self.0.generics.clone();
-        if let Some(ref params) = self.0.de_parameters {
-            generics.params.extend(params.iter().cloned());
+        let mut generics = with_de_parameters(self.0);
+        // Borrowing from `'static` replaces `'de` entirely, matching the
+        // parameters declared by `DeSeedImplGenerics`.
+        if self.0.borrowed.de_lifetime_def().is_some() {
+            generics.params.insert(0, lifetime("'de").into());
         }
-        generics.params.insert(0, lifetime("'de").into());
         generics.params.insert(0, lifetime("'seed").into());
         let (_, ty_generics, _) = generics.split_for_impl();
         ty_generics.to_tokens(tokens);
@@ -91,6 +91,9 @@ impl<'a> Container<'a> {
                         field
                             .attrs
                             .rename_by_rules(variant.attrs.rename_all_rules());
+                        if attrs.deserialize_state_all() {
+                            field.attrs.default_deserialize_state();
+                        }
                     }
                 }
             }
@@ -100,6 +103,9 @@ impl<'a> Container<'a> {
                         has_flatten = true;
                     }
                     field.attrs.rename_by_rules(attrs.rename_all_rules());
+                    if attrs.deserialize_state_all() {
+                        field.attrs.default_deserialize_state();
+                    }
                 }
             }
         }
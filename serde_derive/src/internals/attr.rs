@@ -227,7 +227,20 @@ pub struct Container {
     expecting: Option<String>,
 
     deserialize_state: Option<syn::Type>,
+    deserialize_state_all: bool,
+    expose_fields: bool,
+    deserialize_state_finish: Option<syn::ExprPath>,
+    deserialize_state_by_value: bool,
+    deserialize_state_checkpoint: bool,
     serialize_state: Option<syn::Type>,
+    /// Serialize a struct with `serialize_map` rather than `serialize_struct`.
+    /// Fields skipped through `skip_serializing_if` are left out of the map
+    /// and out of its length.
+    serialize_state_as_map: bool,
+    state_from: Option<syn::Type>,
+    /// Function which buffers the input of an untagged enum before each
+    /// variant is tried against it.
+    untagged_buffer: Option<syn::ExprPath>,
     de_parameters: Option<Vec<syn::GenericParam>>,
     ser_parameters: Option<Vec<syn::GenericParam>>,
 }
@@ -315,7 +328,15 @@ impl Container {
         let mut expecting = Attr::none(cx, EXPECTING);
 
         let mut deserialize_state = Attr::none(cx, DESERIALIZE_STATE);
+        let mut deserialize_state_all = BoolAttr::none(cx, DESERIALIZE_STATE_ALL);
+        let mut expose_fields = BoolAttr::none(cx, EXPOSE_FIELDS);
+        let mut deserialize_state_finish = Attr::none(cx, DESERIALIZE_STATE_FINISH);
+        let mut deserialize_state_by_value = BoolAttr::none(cx, DESERIALIZE_STATE_BY_VALUE);
+        let mut deserialize_state_checkpoint = BoolAttr::none(cx, DESERIALIZE_STATE_CHECKPOINT);
         let mut serialize_state = Attr::none(cx, SERIALIZE_STATE);
+        let mut serialize_state_as_map = BoolAttr::none(cx, SERIALIZE_STATE_AS_MAP);
+        let mut state_from = Attr::none(cx, STATE_FROM);
+        let mut untagged_buffer = Attr::none(cx, UNTAGGED_BUFFER);
         let mut de_parameters = Attr::none(cx, DE_PARAMETERS);
         let mut ser_parameters = Attr::none(cx, SER_PARAMETERS);
 
@@ -535,6 +556,13 @@ impl Container {
                     }
                 }
 
+                // Parse `#[serde(state_from = "Type")]
+                Meta(NameValue(m)) if m.path == STATE_FROM => {
+                    if let Ok(from_ty) = parse_lit_into_ty(cx, STATE_FROM, &m.lit) {
+                        state_from.set_opt(&m.path, Some(from_ty));
+                    }
+                }
+
                 // Parse `#[serde(into = "Type")]
                 Meta(NameValue(m)) if m.path == INTO => {
                     if let Ok(into_ty) = parse_lit_into_ty(cx, INTO, &m.lit) {
@@ -591,6 +619,56 @@ impl Container {
                     }
                 }
 
+                // Parse `#[serde(serialize_state_as_map)]`
+                Meta(Path(word)) if word == SERIALIZE_STATE_AS_MAP => {
+                    match &item.data {
+                        syn::Data::Struct(syn::DataStruct {
+                            fields: syn::Fields::Named(_),
+                            ..
+                        }) => {
+                            serialize_state_as_map.set_true(word);
+                        }
+                        _ => {
+                            let msg = "#[serde(serialize_state_as_map)] can only be used on structs with named fields";
+                            cx.error_spanned_by(word, msg);
+                        }
+                    }
+                }
+
+                // Parse `#[serde(deserialize_state_all)]`
+                Meta(Path(word)) if word == DESERIALIZE_STATE_ALL => {
+                    deserialize_state_all.set_true(word);
+                }
+
+                // Parse `#[serde(expose_fields)]`
+                Meta(Path(word)) if word == EXPOSE_FIELDS => {
+                    expose_fields.set_true(word);
+                }
+
+                // Parse `#[serde(deserialize_state_finish = "...")]`
+                Meta(NameValue(ref m)) if m.path == DESERIALIZE_STATE_FINISH => {
+                    if let Ok(path) = parse_lit_into_expr_path(cx, DESERIALIZE_STATE_FINISH, &m.lit) {
+                        deserialize_state_finish.set(&m.path, path);
+                    }
+                }
+
+                // Parse `#[serde(untagged_buffer = "...")]`
+                Meta(NameValue(ref m)) if m.path == UNTAGGED_BUFFER => {
+                    if let Ok(path) = parse_lit_into_expr_path(cx, UNTAGGED_BUFFER, &m.lit) {
+                        untagged_buffer.set(&m.path, path);
+                    }
+                }
+
+                // Parse `#[serde(deserialize_state_by_value)]`
+                Meta(Path(word)) if word == DESERIALIZE_STATE_BY_VALUE => {
+                    deserialize_state_by_value.set_true(word);
+                }
+
+                // Parse `#[serde(deserialize_state_checkpoint)]`
+                Meta(Path(word)) if word == DESERIALIZE_STATE_CHECKPOINT => {
+                    deserialize_state_checkpoint.set_true(word);
+                }
+
                 Meta(NameValue(ref m)) if m.path == DE_PARAMETERS => {
                     if let Ok(path) = parse_lit_into_generics(cx, DE_PARAMETERS, &m.lit) {
                         de_parameters.set(&m.path, path);
@@ -658,7 +736,15 @@ impl Container {
             expecting: expecting.get(),
 
             deserialize_state: deserialize_state.get(),
+            deserialize_state_all: deserialize_state_all.get(),
+            expose_fields: expose_fields.get(),
+            deserialize_state_finish: deserialize_state_finish.get(),
+            deserialize_state_by_value: deserialize_state_by_value.get(),
+            deserialize_state_checkpoint: deserialize_state_checkpoint.get(),
             serialize_state: serialize_state.get(),
+            serialize_state_as_map: serialize_state_as_map.get(),
+            state_from: state_from.get(),
+            untagged_buffer: untagged_buffer.get(),
             de_parameters: de_parameters.get(),
             ser_parameters: ser_parameters.get(),
         }
@@ -750,6 +836,38 @@ impl Container {
         self.serialize_state.as_ref()
     }
 
+    pub fn deserialize_state_all(&self) -> bool {
+        self.deserialize_state_all
+    }
+
+    pub fn expose_fields(&self) -> bool {
+        self.expose_fields
+    }
+
+    pub fn deserialize_state_finish(&self) -> Option<&syn::ExprPath> {
+        self.deserialize_state_finish.as_ref()
+    }
+
+    pub fn deserialize_state_by_value(&self) -> bool {
+        self.deserialize_state_by_value
+    }
+
+    pub fn deserialize_state_checkpoint(&self) -> bool {
+        self.deserialize_state_checkpoint
+    }
+
+    pub fn serialize_state_as_map(&self) -> bool {
+        self.serialize_state_as_map
+    }
+
+    pub fn state_from(&self) -> Option<&syn::Type> {
+        self.state_from.as_ref()
+    }
+
+    pub fn untagged_buffer(&self) -> Option<&syn::ExprPath> {
+        self.untagged_buffer.as_ref()
+    }
+
     pub fn de_parameters(&self) -> Option<&[syn::GenericParam]> {
         self.de_parameters.as_ref().map(|x| &x[..])
     }
@@ -909,6 +1027,7 @@ pub struct Variant {
     serialize_with: Option<syn::ExprPath>,
     deserialize_with: Option<syn::ExprPath>,
     borrow: Option<syn::Meta>,
+    deserialize_stateless: bool,
 }
 
 impl Variant {
@@ -926,6 +1045,7 @@ impl Variant {
         let mut serialize_with = Attr::none(cx, SERIALIZE_WITH);
         let mut deserialize_with = Attr::none(cx, DESERIALIZE_WITH);
         let mut borrow = Attr::none(cx, BORROW);
+        let mut deserialize_stateless = BoolAttr::none(cx, DESERIALIZE);
 
         for meta_item in variant
             .attrs
@@ -1013,6 +1133,11 @@ impl Variant {
                     other.set_true(word);
                 }
 
+                // Parse `#[serde(deserialize)]`
+                Meta(Path(word)) if word == DESERIALIZE => {
+                    deserialize_stateless.set_true(word);
+                }
+
                 // Parse `#[serde(bound = "T: SomeBound")]`
                 Meta(NameValue(m)) if m.path == BOUND => {
                     if let Ok(where_predicates) = parse_lit_into_where(cx, BOUND, BOUND, &m.lit) {
@@ -1106,6 +1231,7 @@ impl Variant {
             serialize_with: serialize_with.get(),
             deserialize_with: deserialize_with.get(),
             borrow: borrow.get(),
+            deserialize_stateless: deserialize_stateless.get(),
         }
     }
 
@@ -1176,9 +1302,14 @@ pub struct Field {
     transparent: bool,
 
     deserialize_state_with: Option<syn::Path>,
+    deserialize_state_try_from: Option<syn::Type>,
+    deserialize_state_validate: Option<syn::ExprPath>,
     deserialize_state: bool,
+    deserialize_stateless: bool,
     serialize_state_with: Option<syn::Path>,
     serialize_state: bool,
+    serialize_state_order: i32,
+    default_state: Option<syn::ExprPath>,
 }
 
 /// Represents the default to use for a field when deserializing.
@@ -1224,9 +1355,14 @@ impl Field {
         let mut getter = Attr::none(cx, GETTER);
         let mut flatten = BoolAttr::none(cx, FLATTEN);
         let mut deserialize_state_with = Attr::none(cx, DESERIALIZE_STATE_WITH);
+        let mut deserialize_state_try_from = Attr::none(cx, TRY_FROM);
+        let mut deserialize_state_validate = Attr::none(cx, DESERIALIZE_STATE_VALIDATE);
         let mut deserialize_state = BoolAttr::none(cx, DESERIALIZE_STATE);
+        let mut deserialize_stateless = BoolAttr::none(cx, DESERIALIZE);
         let mut serialize_state_with = Attr::none(cx, SERIALIZE_STATE_WITH);
-        let mut serialize_state = BoolAttr::none(cx, SERIALIZE_WITH);
+        let mut serialize_state = BoolAttr::none(cx, SERIALIZE_STATE);
+        let mut serialize_state_order = Attr::none(cx, SERIALIZE_STATE_ORDER);
+        let mut default_state = Attr::none(cx, DEFAULT_STATE);
 
         let ident = match &field.ident {
             Some(ident) => unraw(ident),
@@ -1401,11 +1537,39 @@ impl Field {
                     }
                 }
 
-                // Parse `#[serde(deserialize_state_)]`
+                // Parse `#[serde(try_from = "Type")]`
+                Meta(NameValue(ref m)) if m.path == TRY_FROM => {
+                    if let Ok(ty) = parse_lit_into_ty(cx, TRY_FROM, &m.lit) {
+                        deserialize_state_try_from.set(&m.path, ty);
+                    }
+                }
+
+                // Parse `#[serde(deserialize_state_validate = "...")]`
+                Meta(NameValue(ref m)) if m.path == DESERIALIZE_STATE_VALIDATE => {
+                    if let Ok(path) =
+                        parse_lit_into_expr_path(cx, DESERIALIZE_STATE_VALIDATE, &m.lit)
+                    {
+                        deserialize_state_validate.set(&m.path, path);
+                    }
+                }
+
+                // Parse `#[serde(default_state = "...")]`
+                Meta(NameValue(ref m)) if m.path == DEFAULT_STATE => {
+                    if let Ok(path) = parse_lit_into_expr_path(cx, DEFAULT_STATE, &m.lit) {
+                        default_state.set(&m.path, path);
+                    }
+                }
+
+                // Parse `#[serde(deserialize_state)]`
                 Meta(Path(ref name)) if name == DESERIALIZE_STATE => {
                     deserialize_state.set_true(name);
                 }
 
+                // Parse `#[serde(deserialize)]`
+                Meta(Path(ref name)) if name == DESERIALIZE => {
+                    deserialize_stateless.set_true(name);
+                }
+
                 // Parse `#[serde(serialize_state_with = "...")]`
                 Meta(NameValue(ref m)) if m.path == SERIALIZE_STATE_WITH => {
                     if let Ok(path) = parse_lit_into_path(cx, SERIALIZE_STATE_WITH, &m.lit) {
@@ -1413,6 +1577,18 @@ impl Field {
                     }
                 }
 
+                // Parse `#[serde(serialize_state_order = 1)]`
+                Meta(NameValue(ref m)) if m.path == SERIALIZE_STATE_ORDER => match &m.lit {
+                    syn::Lit::Int(lit) => match lit.base10_parse() {
+                        Ok(order) => serialize_state_order.set(&m.path, order),
+                        Err(err) => cx.syn_error(err),
+                    },
+                    lit => cx.error_spanned_by(
+                        lit,
+                        "expected serde serialize_state_order attribute to be an integer: `serialize_state_order = 1`",
+                    ),
+                },
+
                 // Parse `#[serde(serialize_state)]`
                 Meta(Path(ref name)) if name == SERIALIZE_STATE => {
                     serialize_state.set_true(name);
@@ -1424,17 +1600,18 @@ impl Field {
                     deserialize_state.set_true(name);
                 }
 
+                // Parse `#[serde(state_with = "...")]`
                 Meta(NameValue(ref m)) if m.path == STATE_WITH => {
                     if let Ok(path) = parse_lit_into_path(cx, STATE_WITH, &m.lit) {
                         let mut ser_path = path.clone();
                         ser_path
                             .segments
-                            .push(syn::Ident::new("serialize", Span::call_site()).into());
+                            .push(syn::Ident::new("serialize_state", Span::call_site()).into());
                         serialize_state_with.set(&m.path, ser_path);
                         let mut de_path = path;
                         de_path
                             .segments
-                            .push(syn::Ident::new("deserialize", Span::call_site()).into());
+                            .push(syn::Ident::new("deserialize_state", Span::call_site()).into());
                         deserialize_state_with.set(&m.path, de_path);
                     }
                 }
@@ -1461,7 +1638,7 @@ impl Field {
         // different default is specified by `#[serde(default = "...")]` on
         // ourselves or our container (e.g. the struct we are in).
         if let Default::None = *container_default {
-            if skip_deserializing.0.value.is_some() {
+            if skip_deserializing.0.value.is_some() && default_state.0.value.is_none() {
                 default.set_if_none(Default::Default);
             }
         }
@@ -1512,12 +1689,50 @@ impl Field {
                 };
                 deserialize_with.set_if_none(expr);
             }
+
+            // The generic `DeserializeState` impl for `Cow` always allocates,
+            // so a seeded `Cow<str>` or `Cow<[u8]>` would lose the borrow.
+            let helper = if is_cow(&field.ty, is_str) {
+                Some("borrow_cow_str")
+            } else if is_cow(&field.ty, is_slice_u8) {
+                Some("borrow_cow_bytes")
+            } else {
+                None
+            };
+            if let (true, Some(helper)) = (deserialize_state.get(), helper) {
+                cx.error_spanned_by(
+                    deserialize_state.0.tokens.clone(),
+                    format!(
+                        "#[serde(deserialize_state)] does not borrow a Cow field, use #[serde(deserialize_state_with = \"serde_state::de::{}\")] instead",
+                        helper,
+                    ),
+                );
+            }
         } else if is_implicitly_borrowed(&field.ty) {
             // Types &str and &[u8] are always implicitly borrowed. No need for
             // a #[serde(borrow)].
             collect_lifetimes(&field.ty, &mut borrowed_lifetimes);
         }
 
+        let deserialize_state_with = deserialize_state_with.get();
+        let deserialize_state_try_from = deserialize_state_try_from.get();
+        if deserialize_state_try_from.is_some() && deserialize_state_with.is_none() {
+            cx.error_spanned_by(
+                field,
+                "#[serde(try_from = \"...\")] on a field requires #[serde(deserialize_state_with = \"...\")]",
+            );
+        }
+
+        // Fields of a variant with `#[serde(deserialize)]` never see the seed
+        let deserialize_state = deserialize_state.get();
+        let stateless_variant = attrs.map_or(false, |variant| variant.deserialize_stateless);
+        if stateless_variant && (deserialize_state || deserialize_state_with.is_some()) {
+            cx.error_spanned_by(
+                field,
+                "seeded fields are not allowed in a variant with #[serde(deserialize)]",
+            );
+        }
+
         Field {
             name: Name::from_attrs(ident, ser_name, de_name, Some(de_aliases)),
             skip_serializing: skip_serializing.get(),
@@ -1533,10 +1748,15 @@ impl Field {
             flatten: flatten.get(),
             transparent: false,
 
-            deserialize_state_with: deserialize_state_with.get(),
-            deserialize_state: deserialize_state.get(),
+            deserialize_state_with,
+            deserialize_state_try_from,
+            deserialize_state_validate: deserialize_state_validate.get(),
+            deserialize_state,
+            deserialize_stateless: deserialize_stateless.get() || stateless_variant,
             serialize_state_with: serialize_state_with.get(),
             serialize_state: serialize_state.get(),
+            serialize_state_order: serialize_state_order.get().unwrap_or(0),
+            default_state: default_state.get(),
         }
     }
 
@@ -1613,10 +1833,53 @@ impl Field {
         self.deserialize_state
     }
 
+    /// Seeds the field as if it had `#[serde(deserialize_state)]`, unless it
+    /// opted out with `#[serde(deserialize)]` or is deserialized some other
+    /// way. Used for containers with `#[serde(deserialize_state_all)]`.
+    pub fn default_deserialize_state(&mut self) {
+        if !self.deserialize_stateless
+            && !self.skip_deserializing
+            && self.deserialize_with.is_none()
+            && self.deserialize_state_with.is_none()
+        {
+            self.deserialize_state = true;
+        }
+    }
+
     pub fn deserialize_state_with(&self) -> Option<&syn::Path> {
         self.deserialize_state_with.as_ref()
     }
 
+    /// The type produced by `deserialize_state_with`, which is then converted
+    /// to the field's type with `TryFrom`.
+    pub fn deserialize_state_try_from(&self) -> Option<&syn::Type> {
+        self.deserialize_state_try_from.as_ref()
+    }
+
+    /// Called with the seed and the deserialized value, rejecting the value
+    /// if it returns an error.
+    pub fn deserialize_state_validate(&self) -> Option<&syn::ExprPath> {
+        self.deserialize_state_validate.as_ref()
+    }
+
+    /// Function called with the seed to produce the value of a missing field.
+    pub fn default_state(&self) -> Option<&syn::ExprPath> {
+        self.default_state.as_ref()
+    }
+
+    /// Position of the field when serializing a struct. Fields are emitted
+    /// from the lowest order to the highest, fields with the same order in
+    /// declaration order. Unannotated fields have order 0.
+    ///
+    /// Deserialization still expects declaration order, which only works
+    /// for formats that identify fields by name. Sequence formats such as
+    /// bincode write the values without their names and read them back in
+    /// declaration order, so reordered fields come back in the wrong place
+    /// or fail to deserialize.
+    pub fn serialize_state_order(&self) -> i32 {
+        self.serialize_state_order
+    }
+
     pub fn serialize_state(&self) -> bool {
         self.serialize_state
     }
@@ -1696,7 +1959,9 @@ fn get_where_predicates(
 }
 
 pub fn get_serde_meta_items(cx: &Ctxt, attr: &syn::Attribute) -> Result<Vec<syn::NestedMeta>, ()> {
-    if attr.path != SERDE {
+    // `#[serde_state(...)]` is only read by the state derives, so it can carry
+    // seed attributes on types which also derive `Serialize` or `Deserialize`.
+    if attr.path != SERDE && attr.path != SERDE_STATE {
         return Ok(Vec::new());
     }
 
@@ -1837,7 +2102,7 @@ fn parse_lit_into_generics(
 ) -> Result<Vec<syn::GenericParam>, ()> {
     let string = get_lit_str(cx, attr_name, lit)?;
     if string.value().is_empty() {
-        cx.error_spanned_by(lit, "at least one identifier must be defined");
+        cx.error_spanned_by(lit, "at least one generic parameter must be defined");
         return Err(());
     }
 
@@ -1853,7 +2118,7 @@ fn parse_lit_into_generics(
     }
     Err(cx.error_spanned_by(
         lit,
-        format!("failed to parse borrowed lifetimes: {:?}", string.value()),
+        format!("failed to parse generic parameters: {:?}", string.value()),
     ))
 }
 
@@ -2010,7 +2275,7 @@ fn borrowable_lifetimes(
     }
 }
 
-fn collect_lifetimes(ty: &syn::Type, out: &mut BTreeSet<syn::Lifetime>) {
+pub fn collect_lifetimes(ty: &syn::Type, out: &mut BTreeSet<syn::Lifetime>) {
     match ty {
         syn::Type::Slice(ty) => {
             collect_lifetimes(&ty.elem, out);
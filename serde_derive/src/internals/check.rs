@@ -14,6 +14,12 @@ pub fn check(cx: &Ctxt, cont: &mut Container, derive: Derive) {
     check_adjacent_tag_conflict(cx, cont);
     check_transparent(cx, cont, derive);
     check_from_and_try_from(cx, cont);
+    check_expose_fields(cx, cont);
+    check_deserialize_state_validate(cx, cont, derive);
+    check_deserialize_state_checkpoint(cx, cont);
+    check_default_state(cx, cont, derive);
+    check_untagged_buffer(cx, cont);
+    check_serialize_state_order(cx, cont);
 }
 
 /// Getters are only allowed inside structs (not enums) with the `remote`
@@ -329,6 +335,13 @@ fn check_transparent(cx: &Ctxt, cont: &mut Container, derive: Derive) {
         );
     }
 
+    if cont.attrs.state_from().is_some() {
+        cx.error_spanned_by(
+            cont.original,
+            "#[serde(transparent)] is not allowed with #[serde(state_from = \"...\")]",
+        );
+    }
+
     if cont.attrs.type_into().is_some() {
         cx.error_spanned_by(
             cont.original,
@@ -417,4 +430,159 @@ fn check_from_and_try_from(cx: &Ctxt, cont: &mut Container) {
             "#[serde(from = \"...\")] and #[serde(try_from = \"...\")] conflict with each other",
         );
     }
+
+    if cont.attrs.state_from().is_some() {
+        if cont.attrs.type_from().is_some() || cont.attrs.type_try_from().is_some() {
+            cx.error_spanned_by(
+                cont.original,
+                "#[serde(state_from = \"...\")] conflicts with #[serde(from = \"...\")] and #[serde(try_from = \"...\")]",
+            );
+        }
+        if cont.attrs.deserialize_state().is_none() {
+            cx.error_spanned_by(
+                cont.original,
+                "#[serde(state_from = \"...\")] requires #[serde(deserialize_state = \"...\")]",
+            );
+        }
+    }
+}
+
+/// `#[serde(expose_fields)]` lists the names of the fields of a struct, so it is
+/// meaningless for anything but a struct with named fields.
+fn check_expose_fields(cx: &Ctxt, cont: &Container) {
+    if !cont.attrs.expose_fields() {
+        return;
+    }
+
+    match cont.data {
+        Data::Struct(Style::Struct, _) => {}
+        _ => {
+            cx.error_spanned_by(
+                cont.original,
+                "#[serde(expose_fields)] is only allowed on structs with named fields",
+            );
+        }
+    }
+}
+
+/// Validation is handed the seed, so it only makes sense when the container
+/// derives `DeserializeState`.
+fn check_deserialize_state_validate(cx: &Ctxt, cont: &Container, derive: Derive) {
+    if let Derive::Serialize = derive {
+        return;
+    }
+    if cont.attrs.deserialize_state().is_some() {
+        return;
+    }
+
+    for field in cont.data.all_fields() {
+        if field.attrs.deserialize_state_validate().is_some() {
+            cx.error_spanned_by(
+                field.original,
+                "#[serde(deserialize_state_validate = \"...\")] requires #[serde(deserialize_state = \"...\")] on the container",
+            );
+        }
+    }
+}
+
+/// Checkpoints are taken of the seed, so the container has to have one.
+fn check_deserialize_state_checkpoint(cx: &Ctxt, cont: &Container) {
+    if cont.attrs.deserialize_state_checkpoint() && cont.attrs.deserialize_state().is_none() {
+        cx.error_spanned_by(
+            cont.original,
+            "#[serde(deserialize_state_checkpoint)] requires #[serde(deserialize_state = \"...\")]",
+        );
+    }
+}
+
+/// Seeded defaults are produced from the seed held by the struct visitor, so
+/// they need a seeded container and can only stand in for struct fields.
+fn check_default_state(cx: &Ctxt, cont: &Container, derive: Derive) {
+    if let Derive::Serialize = derive {
+        return;
+    }
+
+    let fields = match &cont.data {
+        Data::Enum(variants) => {
+            for field in variants.iter().flat_map(|variant| &variant.fields) {
+                if field.attrs.default_state().is_some() {
+                    cx.error_spanned_by(
+                        field.original,
+                        "#[serde(default_state = \"...\")] is only allowed on struct fields",
+                    );
+                }
+            }
+            return;
+        }
+        Data::Struct(_, fields) => fields,
+    };
+
+    for field in fields {
+        if field.attrs.default_state().is_none() {
+            continue;
+        }
+        if cont.attrs.deserialize_state().is_none() {
+            cx.error_spanned_by(
+                field.original,
+                "#[serde(default_state = \"...\")] requires #[serde(deserialize_state = \"...\")] on the container",
+            );
+        }
+        if !field.attrs.default().is_none() {
+            cx.error_spanned_by(
+                field.original,
+                "#[serde(default_state = \"...\")] conflicts with #[serde(default)]",
+            );
+        }
+    }
+}
+
+/// The buffer is only ever filled for untagged enums, and the function which
+/// fills it is handed the seed.
+fn check_untagged_buffer(cx: &Ctxt, cont: &Container) {
+    if cont.attrs.untagged_buffer().is_none() {
+        return;
+    }
+
+    match (&cont.data, cont.attrs.tag()) {
+        (Data::Enum(_), TagType::None) => {}
+        _ => {
+            cx.error_spanned_by(
+                cont.original,
+                "#[serde(untagged_buffer = \"...\")] is only allowed on untagged enums",
+            );
+        }
+    }
+
+    if cont.attrs.deserialize_state().is_none() {
+        cx.error_spanned_by(
+            cont.original,
+            "#[serde(untagged_buffer = \"...\")] requires #[serde(deserialize_state = \"...\")]",
+        );
+    }
+}
+
+/// Only named fields can be emitted out of declaration order, the position of
+/// a tuple field is what identifies it.
+fn check_serialize_state_order(cx: &Ctxt, cont: &Container) {
+    let fields: Vec<&Field> = match &cont.data {
+        Data::Enum(variants) => variants
+            .iter()
+            .filter(|variant| match variant.style {
+                Style::Struct => false,
+                _ => true,
+            })
+            .flat_map(|variant| &variant.fields)
+            .collect(),
+        Data::Struct(Style::Struct, _) => return,
+        Data::Struct(_, fields) => fields.iter().collect(),
+    };
+
+    for field in fields {
+        if field.attrs.serialize_state_order() != 0 {
+            cx.error_spanned_by(
+                field.original,
+                "#[serde(serialize_state_order = ...)] is only allowed on named fields",
+            );
+        }
+    }
 }
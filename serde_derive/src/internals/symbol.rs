@@ -10,6 +10,7 @@ pub const BOUND: Symbol = Symbol("bound");
 pub const CONTENT: Symbol = Symbol("content");
 pub const CRATE: Symbol = Symbol("crate");
 pub const DEFAULT: Symbol = Symbol("default");
+pub const DEFAULT_STATE: Symbol = Symbol("default_state");
 pub const DENY_UNKNOWN_FIELDS: Symbol = Symbol("deny_unknown_fields");
 pub const DESERIALIZE: Symbol = Symbol("deserialize");
 pub const DESERIALIZE_WITH: Symbol = Symbol("deserialize_with");
@@ -23,6 +24,7 @@ pub const REMOTE: Symbol = Symbol("remote");
 pub const RENAME: Symbol = Symbol("rename");
 pub const RENAME_ALL: Symbol = Symbol("rename_all");
 pub const SERDE: Symbol = Symbol("serde");
+pub const SERDE_STATE: Symbol = Symbol("serde_state");
 pub const SERIALIZE: Symbol = Symbol("serialize");
 pub const SERIALIZE_WITH: Symbol = Symbol("serialize_with");
 pub const SKIP: Symbol = Symbol("skip");
@@ -41,8 +43,18 @@ pub const DESERIALIZE_STATE_WITH: Symbol = Symbol("deserialize_state_with");
 pub const DESERIALIZE_STATE: Symbol = Symbol("deserialize_state");
 pub const SERIALIZE_STATE_WITH: Symbol = Symbol("serialize_state_with");
 pub const SERIALIZE_STATE: Symbol = Symbol("serialize_state");
+pub const SERIALIZE_STATE_AS_MAP: Symbol = Symbol("serialize_state_as_map");
+pub const SERIALIZE_STATE_ORDER: Symbol = Symbol("serialize_state_order");
 pub const STATE: Symbol = Symbol("state");
 pub const STATE_WITH: Symbol = Symbol("state_with");
+pub const STATE_FROM: Symbol = Symbol("state_from");
+pub const DESERIALIZE_STATE_ALL: Symbol = Symbol("deserialize_state_all");
+pub const EXPOSE_FIELDS: Symbol = Symbol("expose_fields");
+pub const DESERIALIZE_STATE_FINISH: Symbol = Symbol("deserialize_state_finish");
+pub const DESERIALIZE_STATE_BY_VALUE: Symbol = Symbol("deserialize_state_by_value");
+pub const DESERIALIZE_STATE_CHECKPOINT: Symbol = Symbol("deserialize_state_checkpoint");
+pub const DESERIALIZE_STATE_VALIDATE: Symbol = Symbol("deserialize_state_validate");
+pub const UNTAGGED_BUFFER: Symbol = Symbol("untagged_buffer");
 pub const DE_PARAMETERS: Symbol = Symbol("de_parameters");
 pub const SER_PARAMETERS: Symbol = Symbol("ser_parameters");
 
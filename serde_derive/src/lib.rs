@@ -76,7 +76,7 @@ mod pretend;
 mod ser;
 mod try;
 
-#[proc_macro_derive(SerializeState, attributes(serde))]
+#[proc_macro_derive(SerializeState, attributes(serde, serde_state))]
 pub fn derive_serialize_state(input: TokenStream) -> TokenStream {
     let mut input = parse_macro_input!(input as DeriveInput);
     ser::expand_derive_serialize(&mut input, true)
@@ -84,7 +84,7 @@ pub fn derive_serialize_state(input: TokenStream) -> TokenStream {
         .into()
 }
 
-#[proc_macro_derive(DeserializeState, attributes(serde))]
+#[proc_macro_derive(DeserializeState, attributes(serde, serde_state))]
 pub fn derive_deserialize_state(input: TokenStream) -> TokenStream {
     let mut input = parse_macro_input!(input as DeriveInput);
     de::expand_derive_deserialize(&mut input, true)
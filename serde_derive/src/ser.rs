@@ -33,7 +33,24 @@ pub fn expand_derive_serialize(
     let body = Stmts(serialize_body(&cont, &params));
     let serde = cont.attrs.serde_path();
 
-    let impl_block = if let Some(remote) = cont.attrs.remote() {
+    let impl_block = if let (Some(remote), true) = (cont.attrs.remote(), seed) {
+        let vis = &input.vis;
+        let used = pretend::pretend_used(&cont);
+        let seed_ty = cont.attrs.serialize_state().unwrap();
+        quote! {
+            impl #impl_generics #ident #ty_generics #where_clause {
+                // Same signature as the functions expected by `serialize_state_with`
+                #[allow(unused_variables)]
+                #vis fn serialize_state<__S>(__self: &#remote #ty_generics, __serializer: __S, __seed: &#seed_ty) -> _serde::__private::Result<__S::Ok, __S::Error>
+                where
+                    __S: _serde::Serializer,
+                {
+                    #used
+                    #body
+                }
+            }
+        }
+    } else if let Some(remote) = cont.attrs.remote() {
         let vis = &input.vis;
         let used = pretend::pretend_used(&cont);
         quote! {
@@ -53,7 +70,8 @@ pub fn expand_derive_serialize(
         quote! {
             #[automatically_derived]
             impl #impl_generics _serde::ser::SerializeState<#seed_ty> for #ident #ty_generics #where_clause {
-
+                // Unit structs and structs without seeded fields never touch the seed.
+                #[allow(unused_variables)]
                 fn serialize_state<__S>(&self, __serializer: __S, __seed: &#seed_ty) -> _serde::__private::Result<__S::Ok, __S::Error>
                     where __S: _serde::Serializer
                 {
@@ -77,7 +95,7 @@ pub fn expand_derive_serialize(
 
     Ok(dummy::wrap_in_const(
         cont.attrs.custom_serde_path(),
-        "SERIALIZE",
+        "SERIALIZE_STATE",
         ident,
         impl_block,
     ))
@@ -164,7 +182,7 @@ fn build_generics(cont: &Container, seeded: bool) -> syn::Generics {
     let generics =
         bound::with_where_predicates_from_variants(cont, &generics, attr::Variant::ser_bound);
 
-    match cont.attrs.ser_bound() {
+    let generics = match cont.attrs.ser_bound() {
         Some(predicates) => bound::with_where_predicates(&generics, predicates),
         None => bound::with_bound(
             cont,
@@ -180,7 +198,9 @@ fn build_generics(cont: &Container, seeded: bool) -> syn::Generics {
                 parse_quote!(_serde::Serialize)
             },
         ),
-    }
+    };
+
+    bound::without_duplicate_predicates(&generics)
 }
 
 // Fields with a `skip_serializing` or `serialize_with` attribute, or which
@@ -229,16 +249,21 @@ fn serialize_transparent(cont: &Container, params: &Parameters) -> Fragment {
     let transparent_field = fields.iter().find(|f| f.attrs.transparent()).unwrap();
     let member = &transparent_field.member;
 
-    let path = match transparent_field.attrs.serialize_with() {
-        Some(path) => quote!(#path),
-        None => {
-            let span = transparent_field.original.span();
-            quote_spanned!(span=> _serde::Serialize::serialize)
-        }
+    let span = transparent_field.original.span();
+    let serialize = if transparent_field.attrs.serialize_state() {
+        let func = quote_spanned!(span=> _serde::ser::SerializeState::serialize_state);
+        quote!(#func(&#self_var.#member, __serializer, __seed))
+    } else if let Some(path) = transparent_field.attrs.serialize_state_with() {
+        quote!(#path(&#self_var.#member, __serializer, __seed))
+    } else if let Some(path) = transparent_field.attrs.serialize_with() {
+        quote!(#path(&#self_var.#member, __serializer))
+    } else {
+        let func = quote_spanned!(span=> _serde::Serialize::serialize);
+        quote!(#func(&#self_var.#member, __serializer))
     };
 
     quote_block! {
-        #path(&#self_var.#member, __serializer)
+        #serialize
     }
 }
 
@@ -333,7 +358,7 @@ fn serialize_tuple_struct(
 fn serialize_struct(params: &Parameters, fields: &[Field], cattrs: &attr::Container) -> Fragment {
     assert!(fields.len() as u64 <= u64::from(u32::max_value()));
 
-    if cattrs.has_flatten() {
+    if cattrs.has_flatten() || cattrs.serialize_state_as_map() {
         serialize_struct_as_map(params, fields, cattrs)
     } else {
         serialize_struct_as_struct(params, fields, cattrs)
@@ -1156,9 +1181,16 @@ fn serialize_struct_visitor(
     struct_trait: &StructTrait,
     seed_ty: Option<&syn::Type>,
 ) -> Vec<TokenStream> {
-    fields
+    // The sort is stable, so fields without a `serialize_state_order` keep
+    // their declaration order
+    let mut fields: Vec<&Field> = fields
         .iter()
         .filter(|&field| !field.attrs.skip_serializing())
+        .collect();
+    fields.sort_by_key(|field| field.attrs.serialize_state_order());
+
+    fields
+        .into_iter()
         .map(|field| {
             let member = &field.member;
 
@@ -1406,14 +1438,30 @@ fn effective_style(variant: &Variant) -> Style {
     }
 }
 
+// The generics of the type plus any extra parameters from
+// `#[serde(ser_parameters = "...")]`.
+fn with_ser_parameters(params: &Parameters) -> syn::Generics {
+    let mut generics = params.generics.clone();
+    if let Some(ref ser_parameters) = params.ser_parameters {
+        // Lifetimes must be declared before any other parameter.
+        for param in ser_parameters {
+            match param {
+                syn::GenericParam::Lifetime(_) => {
+                    let index = generics.lifetimes().count();
+                    generics.params.insert(index, param.clone());
+                }
+                _ => generics.params.push(param.clone()),
+            }
+        }
+    }
+    generics
+}
+
 struct SerImplGenerics<'a>(&'a Parameters);
 
 impl<'a> ToTokens for SerImplGenerics<'a> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
-        let mut generics = self.0.generics.clone();
-        if let Some(ref params) = self.0.ser_parameters {
-            generics.params.extend(params.iter().cloned());
-        }
+        let generics = with_ser_parameters(self.0);
         let (impl_generics, _, _) = generics.split_for_impl();
         impl_generics.to_tokens(tokens);
     }
@@ -1435,10 +1483,7 @@ struct SerLifetimeImplGenerics<'a>(&'a Parameters);
 
 impl<'a> ToTokens for SerLifetimeImplGenerics<'a> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
-        let mut generics = bound::with_lifetime_bound(&self.0.generics, "'__a");
-        if let Some(ref params) = self.0.ser_parameters {
-            generics.params.extend(params.iter().cloned());
-        }
+        let generics = bound::with_lifetime_bound(&with_ser_parameters(self.0), "'__a");
         let (impl_generics, _, _) = generics.split_for_impl();
         impl_generics.to_tokens(tokens);
     }
@@ -1448,7 +1493,7 @@ struct SerTypeGenerics<'a>(&'a Parameters);
 
 impl<'a> ToTokens for SerTypeGenerics<'a> {
     fn to_tokens(&self, tokens: &mut TokenStream) {
-        let generics = bound::with_lifetime_bound(&self.0.generics, "'__a");
+        let generics = bound::with_lifetime_bound(&with_ser_parameters(self.0), "'__a");
         let (_, ty_generics, _) = generics.split_for_impl();
         ty_generics.to_tokens(tokens);
     }
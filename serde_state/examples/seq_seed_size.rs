@@ -0,0 +1,73 @@
+//! Instantiates `SeqSeedEx` for 20 element types, each collected into a `Vec` and a `VecDeque`,
+//! to compare how much code seeded sequences generate.
+//!
+//! ```text
+//! cargo build --example seq_seed_size [--release]
+//! nm -C target/<profile>/examples/seq_seed_size | grep -c SeqSeedEx
+//! size -A target/<profile>/examples/seq_seed_size | grep '^\.text'
+//! ```
+//!
+//! Results with rustc 1.95 (`SeqSeedEx` symbols / `.text` bytes):
+//!
+//! | `visit_seq`                                   | opt-level 0  | opt-level 3 |
+//! |-----------------------------------------------|--------------|-------------|
+//! | one generic loop (current)                    | 120 / 467203 | 0 / 290835  |
+//! | element loop behind `&mut dyn FnMut(U)`       | 160 / 475523 | 0 / 291171  |
+//! | non-generic `expecting` and capacity helpers  | 120 / 464595 | 0 / 292195  |
+//!
+//! Everything left in the loop depends on the collection and the element type, so neither
+//! factoring shrinks optimized builds.
+
+extern crate serde;
+extern crate serde_state;
+
+use std::collections::VecDeque;
+
+use serde::de::value::{Error, SeqDeserializer};
+use serde::de::{Deserialize, DeserializeSeed, Deserializer};
+use serde_state::de::{DeserializeState, SeqSeedEx};
+
+struct Count(usize);
+
+macro_rules! elements {
+    ($($name:ident)*) => {
+        $(
+            struct $name(i32);
+
+            impl<'de> DeserializeState<'de, Count> for $name {
+                fn deserialize_state<D>(seed: &mut Count, deserializer: D) -> Result<Self, D::Error>
+                where
+                    D: Deserializer<'de>,
+                {
+                    seed.0 += 1;
+                    i32::deserialize(deserializer).map($name)
+                }
+            }
+        )*
+
+        fn run(input: Vec<i32>) -> usize {
+            let mut total = 0;
+            $(
+                let mut seed = Count(0);
+                let vec: Vec<$name> = SeqSeedEx::new(&mut seed, Vec::with_capacity)
+                    .deserialize(SeqDeserializer::<_, Error>::new(input.clone().into_iter()))
+                    .unwrap();
+                let deque: VecDeque<$name> = SeqSeedEx::new(&mut seed, VecDeque::with_capacity)
+                    .deserialize(SeqDeserializer::<_, Error>::new(input.clone().into_iter()))
+                    .unwrap();
+                total += vec.iter().map(|x| x.0 as usize).sum::<usize>() + deque.len() + seed.0;
+            )*
+            total
+        }
+    };
+}
+
+elements! {
+    E01 E02 E03 E04 E05 E06 E07 E08 E09 E10
+    E11 E12 E13 E14 E15 E16 E17 E18 E19 E20
+}
+
+fn main() {
+    let n = std::env::args().count() as i32;
+    println!("{}", run(vec![n, 2 * n, 3 * n]));
+}
@@ -10,14 +10,57 @@
 
 use lib::*;
 
+#[cfg(any(feature = "std", feature = "alloc"))]
+use private::de::{Content, ContentRefDeserializer};
+
 ////////////////////////////////////////////////////////////////////////////////
 
+#[macro_use]
+mod stateful;
+#[cfg(feature = "std")]
+mod registry;
 mod seed_impls;
 
-pub use self::seed_impls::{OptionSeed, SeqSeed, SeqSeedEx};
+#[cfg(feature = "std")]
+pub use self::registry::{
+    deserialize_registered, Constructor, RegisteredDeserializer, Registry, RegistryError,
+};
+pub use self::seed_impls::{MapSeedEx, MapValueSeedEx, OptionSeed, SeqSeed, SeqSeedEx};
+pub use self::stateful::{SeedAccess, StatefulDeserializer};
 
 pub use serde::de::*;
 
+/// The input of an untagged enum, buffered in memory so that each variant can be tried against it.
+///
+/// This is how untagged enums buffer their input by default. A function named by
+/// `#[serde(untagged_buffer = "...")]` is called with the seed and the deserializer, and may
+/// return a `Buffer` or any other type `B` for which `&B` implements `IntoDeserializer`, such as
+/// a representation which only knows about the shapes the variants can take.
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub struct Buffer<'de>(Content<'de>);
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'de> Deserialize<'de> for Buffer<'de> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        Content::deserialize(deserializer).map(Buffer)
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a, 'de, E> IntoDeserializer<'de, E> for &'a Buffer<'de>
+where
+    E: Error,
+{
+    type Deserializer = ContentRefDeserializer<'a, 'de, E>;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        ContentRefDeserializer::new(&self.0)
+    }
+}
+
 /// `DeserializeState` is a trait which specifies how to deserialize a type which requires extra
 /// state to deserialize
 pub trait DeserializeState<'de, Seed: ?Sized>: Sized {
@@ -25,6 +68,74 @@ pub trait DeserializeState<'de, Seed: ?Sized>: Sized {
     fn deserialize_state<D>(seed: &mut Seed, deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>;
+
+    /// Deserializes into an existing `place` using `seed` and the `deserializer`.
+    ///
+    /// The default implementation deserializes a new value with `deserialize_state` and assigns it
+    /// to `place`. Implementations may instead reuse the storage already owned by `place`, such as
+    /// the allocation of a `Vec`. If an error is returned, `place` is left in a valid but
+    /// unspecified state.
+    fn deserialize_state_in_place<D>(
+        seed: &mut Seed,
+        deserializer: D,
+        place: &mut Self,
+    ) -> Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        *place = try!(Self::deserialize_state(seed, deserializer));
+        Ok(())
+    }
+}
+
+/// Seeds which can fail with errors of their own while they are used for deserialization.
+///
+/// A seed resolving references, for instance, can report an unknown id as a typed error and leave
+/// it to `state_error` to turn that into the error of whichever deserializer is in use.
+pub trait StateContext {
+    /// The error produced by the seed
+    type Error: Display;
+}
+
+/// Converts an error produced by the seed `S` into the error type of a deserializer, through
+/// `Error::custom`.
+pub fn state_error<S, E>(error: S::Error) -> E
+where
+    S: ?Sized + StateContext,
+    E: Error,
+{
+    E::custom(error)
+}
+
+/// Conversion from a shadow type which may consult the seed, used by containers with
+/// `#[serde(state_from = "Shadow")]`.
+///
+/// The shadow is deserialized with `DeserializeState` using the same seed, after which
+/// `from_state` builds the container. Returning an error rejects the input, with the error
+/// reported through `de::Error::custom`.
+pub trait FromState<T, Seed: ?Sized>: Sized {
+    /// The error produced when `value` is rejected
+    type Error: Display;
+
+    /// Converts `value` into `Self`
+    fn from_state(value: T, seed: &mut Seed) -> Result<Self, Self::Error>;
+}
+
+/// Seeds which can roll back the changes made by a failed deserialization attempt.
+///
+/// Untagged enums try each variant in turn and fields with `try_from` can fail after the seed has
+/// been used. Containers marked with `#[serde(deserialize_state_checkpoint)]` take a checkpoint of
+/// the seed before such attempts and restore it when they fail, so the seed only records what was
+/// actually deserialized. Containers without the attribute do not require this trait.
+pub trait Checkpoint {
+    /// The saved state of the seed
+    type Checkpoint;
+
+    /// Saves the current state of the seed
+    fn checkpoint(&self) -> Self::Checkpoint;
+
+    /// Rolls the seed back to an earlier `checkpoint`
+    fn restore(&mut self, checkpoint: Self::Checkpoint);
 }
 
 /// Wrapper type which implements `DeserializeSeed` for `DeserializeState` instances
@@ -86,3 +197,550 @@ where
         T::deserialize_state(self.seed, deserializer)
     }
 }
+
+/// Object safe counterpart of `DeserializeState` for a fixed deserializer type `D`.
+///
+/// `DeserializeState` can not be used as a trait object since its `deserialize_state` method is
+/// generic over the deserializer. Fixing the deserializer as a type parameter of the trait removes
+/// that generic, so registries of seeded types can be stored as
+/// `Box<dyn DeserializeStateSeed<'de, S, D, Value = Box<dyn Plugin>>>` and dispatched at runtime.
+/// To stay independent of the data format, use an erased deserializer for `D`, such as
+/// `&mut dyn erased_serde::Deserializer<'de>`, which itself implements `Deserializer`.
+///
+/// Any `Fn(&mut S, D) -> Result<T, D::Error>` implements this trait, so both
+/// `T::deserialize_state` and closures converting the value to a trait object can be boxed.
+pub trait DeserializeStateSeed<'de, S: ?Sized, D>
+where
+    D: Deserializer<'de>,
+{
+    /// The type produced by this seed
+    type Value;
+
+    /// Deserializes a value using `seed` and the `deserializer`
+    fn deserialize_state_seed(&self, seed: &mut S, deserializer: D) -> Result<Self::Value, D::Error>;
+}
+
+impl<'de, S: ?Sized, D, F, T> DeserializeStateSeed<'de, S, D> for F
+where
+    D: Deserializer<'de>,
+    F: Fn(&mut S, D) -> Result<T, D::Error>,
+{
+    type Value = T;
+
+    fn deserialize_state_seed(&self, seed: &mut S, deserializer: D) -> Result<T, D::Error> {
+        self(seed, deserializer)
+    }
+}
+
+/// Returns a `DeserializeSeed` which deserializes a `T` with `seed`.
+///
+/// This is a shorthand for `Seed::new(seed)` which lets the value type be inferred, which is
+/// convenient when driving `SeqAccess` or `MapAccess` by hand.
+///
+/// ```
+/// extern crate serde_json;
+/// extern crate serde_state as serde;
+///
+/// use std::collections::BTreeMap;
+/// use std::fmt;
+/// use serde::de::{deserialize_state_seed, Deserializer, DeserializeState, MapAccess, Visitor};
+///
+/// struct Counter(usize);
+///
+/// struct Counted(u32);
+///
+/// impl<'de> DeserializeState<'de, Counter> for Counted {
+///     fn deserialize_state<D>(seed: &mut Counter, deserializer: D) -> Result<Self, D::Error>
+///     where
+///         D: Deserializer<'de>,
+///     {
+///         seed.0 += 1;
+///         DeserializeState::deserialize_state(seed, deserializer).map(Counted)
+///     }
+/// }
+///
+/// struct CountedMap<'a>(&'a mut Counter);
+///
+/// impl<'de, 'a> Visitor<'de> for CountedMap<'a> {
+///     type Value = BTreeMap<String, u32>;
+///
+///     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+///         formatter.write_str("a map")
+///     }
+///
+///     fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+///     where
+///         A: MapAccess<'de>,
+///     {
+///         let mut values = BTreeMap::new();
+///         while let Some(key) = map.next_key()? {
+///             let Counted(value) = map.next_value_seed(deserialize_state_seed(&mut *self.0))?;
+///             values.insert(key, value);
+///         }
+///         Ok(values)
+///     }
+/// }
+///
+/// fn main() {
+///     let mut counter = Counter(0);
+///     let mut deserializer = serde_json::Deserializer::from_str(r#"{ "a": 1, "b": 2 }"#);
+///     let map = deserializer.deserialize_map(CountedMap(&mut counter)).unwrap();
+///     assert_eq!(map["b"], 2);
+///     assert_eq!(counter.0, 2);
+/// }
+/// ```
+pub fn deserialize_state_seed<'de, 's, S, T>(seed: &'s mut S) -> Seed<&'s mut S, T>
+where
+    S: ?Sized,
+    T: DeserializeState<'de, S>,
+{
+    Seed::new(seed)
+}
+
+/// Deserializes a `T` with `seed` from any value which can be turned into a deserializer, such as
+/// a value which was already buffered in memory.
+///
+/// ```
+/// extern crate serde_state as serde;
+///
+/// use serde::de::{from_deserializer_seed, value, Deserializer, DeserializeState};
+///
+/// struct Offset(i32);
+///
+/// struct Shifted(i32);
+///
+/// impl<'de> DeserializeState<'de, Offset> for Shifted {
+///     fn deserialize_state<D>(seed: &mut Offset, deserializer: D) -> Result<Self, D::Error>
+///     where
+///         D: Deserializer<'de>,
+///     {
+///         let value: i32 = DeserializeState::deserialize_state(seed, deserializer)?;
+///         Ok(Shifted(value + seed.0))
+///     }
+/// }
+///
+/// fn main() {
+///     let values: Result<Vec<Shifted>, value::Error> =
+///         from_deserializer_seed(&mut Offset(10), vec![1, 2]);
+///     let values: Vec<i32> = values.unwrap().into_iter().map(|Shifted(n)| n).collect();
+///     assert_eq!(values, [11, 12]);
+/// }
+/// ```
+pub fn from_deserializer_seed<'de, S, T, I, E>(seed: &mut S, value: I) -> Result<T, E>
+where
+    S: ?Sized,
+    T: DeserializeState<'de, S>,
+    I: IntoDeserializer<'de, E>,
+    E: Error,
+{
+    T::deserialize_state(seed, value.into_deserializer())
+}
+
+/// Seeds which can describe their state in error messages.
+///
+/// Errors built with `error_with_state` append this description to the message, which turns an
+/// opaque failure such as a dangling reference into one that shows what the seed did contain.
+pub trait DebugState {
+    /// Writes a description of the current state of the seed
+    fn debug_state(&self, formatter: &mut fmt::Formatter) -> fmt::Result;
+}
+
+/// Maps describe themselves by their keys, in ascending order.
+#[cfg(feature = "std")]
+impl<K, V, H> DebugState for HashMap<K, V, H>
+where
+    K: Debug + Ord,
+    H: BuildHasher,
+{
+    fn debug_state(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let mut keys: Vec<&K> = self.keys().collect();
+        keys.sort();
+        write!(formatter, "known keys: {:?}", keys)
+    }
+}
+
+/// Maps describe themselves by their keys, in ascending order.
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<K, V> DebugState for BTreeMap<K, V>
+where
+    K: Debug + Ord,
+{
+    fn debug_state(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        let keys: Vec<&K> = self.keys().collect();
+        write!(formatter, "known keys: {:?}", keys)
+    }
+}
+
+/// Builds an `Error::custom` from `msg` followed by the description of `seed`.
+///
+/// The description is only added when debug assertions are enabled, so release builds neither
+/// pay for formatting the seed nor expose its contents in error messages.
+pub fn error_with_state<E, S, T>(seed: &S, msg: T) -> E
+where
+    E: Error,
+    S: ?Sized + DebugState,
+    T: Display,
+{
+    if cfg!(debug_assertions) {
+        E::custom(format_args!("{} ({})", msg, StateDescription(seed)))
+    } else {
+        E::custom(msg)
+    }
+}
+
+struct StateDescription<'a, S: ?Sized + 'a>(&'a S);
+
+impl<'a, S: ?Sized + DebugState> Display for StateDescription<'a, S> {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        self.0.debug_state(formatter)
+    }
+}
+
+/// Checks that `handle` is one of the `count` handles a seed has handed out, numbered from `1`.
+///
+/// Meant for seeds which resolve keys or indices to `NonZeroU32` handles. `0`, handles past
+/// `count` and values which do not fit in a `u32` are rejected with `Error::custom`.
+pub fn nonzero_handle<E>(handle: u64, count: usize) -> Result<NonZeroU32, E>
+where
+    E: Error,
+{
+    match u32::try_from(handle).ok().and_then(NonZeroU32::new) {
+        Some(valid) if handle <= count as u64 => Ok(valid),
+        _ => Err(E::custom(format_args!("invalid handle {}", handle))),
+    }
+}
+
+/// Deserializes a `Duration` and adds it to the epoch held by `seed`.
+///
+/// The inverse of `ser::serialize_relative_time`, meant for
+/// `#[serde(deserialize_state_with = "...")]`.
+#[cfg(feature = "std")]
+pub fn deserialize_relative_time<'de, S, D>(
+    seed: &mut S,
+    deserializer: D,
+) -> Result<SystemTime, D::Error>
+where
+    S: ?Sized + Borrow<SystemTime>,
+    D: Deserializer<'de>,
+{
+    let elapsed = try!(Duration::deserialize(deserializer));
+    let epoch: &SystemTime = Borrow::borrow(&*seed);
+    match epoch.checked_add(elapsed) {
+        Some(time) => Ok(time),
+        None => Err(Error::custom("overflow deserializing SystemTime relative to the seed")),
+    }
+}
+
+/// Deserializes a `Cow<str>` which borrows from the input whenever the deserializer allows it,
+/// ignoring `seed`.
+///
+/// The `DeserializeState` impl for `Cow` always allocates. Use this function with
+/// `#[serde(borrow, deserialize_state_with = "...")]` on fields which should stay borrowed.
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub fn borrow_cow_str<'de: 'a, 'a, S, D>(
+    _seed: &mut S,
+    deserializer: D,
+) -> Result<Cow<'a, str>, D::Error>
+where
+    S: ?Sized,
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_str(CowStrVisitor)
+}
+
+/// Deserializes a `Cow<[u8]>` which borrows from the input whenever the deserializer allows it,
+/// ignoring `seed`.
+///
+/// The bytes counterpart of `borrow_cow_str`.
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub fn borrow_cow_bytes<'de: 'a, 'a, S, D>(
+    _seed: &mut S,
+    deserializer: D,
+) -> Result<Cow<'a, [u8]>, D::Error>
+where
+    S: ?Sized,
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_bytes(CowBytesVisitor)
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+struct CowStrVisitor;
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a> Visitor<'a> for CowStrVisitor {
+    type Value = Cow<'a, str>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(Cow::Owned(v.to_owned()))
+    }
+
+    fn visit_borrowed_str<E>(self, v: &'a str) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(Cow::Borrowed(v))
+    }
+
+    fn visit_string<E>(self, v: String) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(Cow::Owned(v))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        match str::from_utf8(v) {
+            Ok(s) => Ok(Cow::Owned(s.to_owned())),
+            Err(_) => Err(Error::invalid_value(Unexpected::Bytes(v), &self)),
+        }
+    }
+
+    fn visit_borrowed_bytes<E>(self, v: &'a [u8]) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        match str::from_utf8(v) {
+            Ok(s) => Ok(Cow::Borrowed(s)),
+            Err(_) => Err(Error::invalid_value(Unexpected::Bytes(v), &self)),
+        }
+    }
+}
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+struct CowBytesVisitor;
+
+#[cfg(any(feature = "std", feature = "alloc"))]
+impl<'a> Visitor<'a> for CowBytesVisitor {
+    type Value = Cow<'a, [u8]>;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a byte array")
+    }
+
+    fn visit_str<E>(self, v: &str) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(Cow::Owned(v.as_bytes().to_vec()))
+    }
+
+    fn visit_borrowed_str<E>(self, v: &'a str) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(Cow::Borrowed(v.as_bytes()))
+    }
+
+    fn visit_bytes<E>(self, v: &[u8]) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(Cow::Owned(v.to_vec()))
+    }
+
+    fn visit_borrowed_bytes<E>(self, v: &'a [u8]) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(Cow::Borrowed(v))
+    }
+
+    fn visit_byte_buf<E>(self, v: Vec<u8>) -> Result<Self::Value, E>
+    where
+        E: Error,
+    {
+        Ok(Cow::Owned(v))
+    }
+}
+
+/// Deserializes each remaining element of `seq` with `seed` and passes it to `f`.
+///
+/// Unlike `SeqSeedEx` nothing is collected, so sequences of any length can be processed in
+/// bounded memory.
+///
+/// ```
+/// extern crate serde_json;
+/// extern crate serde_state as serde;
+///
+/// use std::fmt;
+/// use serde::de::{for_each_seed, Deserializer, DeserializeState, SeqAccess, Visitor};
+///
+/// struct Scale(u64);
+///
+/// struct Scaled(u64);
+///
+/// impl<'de> DeserializeState<'de, Scale> for Scaled {
+///     fn deserialize_state<D>(seed: &mut Scale, deserializer: D) -> Result<Self, D::Error>
+///     where
+///         D: Deserializer<'de>,
+///     {
+///         let value: u64 = DeserializeState::deserialize_state(seed, deserializer)?;
+///         Ok(Scaled(value * seed.0))
+///     }
+/// }
+///
+/// struct Sum<'a>(&'a mut Scale);
+///
+/// impl<'de, 'a> Visitor<'de> for Sum<'a> {
+///     type Value = u64;
+///
+///     fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+///         formatter.write_str("a sequence of integers")
+///     }
+///
+///     fn visit_seq<A>(self, seq: A) -> Result<u64, A::Error>
+///     where
+///         A: SeqAccess<'de>,
+///     {
+///         let mut sum = 0;
+///         for_each_seed(self.0, seq, |Scaled(value)| sum += value)?;
+///         Ok(sum)
+///     }
+/// }
+///
+/// fn main() {
+///     let mut deserializer = serde_json::Deserializer::from_str("[1, 2, 3]");
+///     let sum = deserializer.deserialize_seq(Sum(&mut Scale(10))).unwrap();
+///     assert_eq!(sum, 60);
+/// }
+/// ```
+pub fn for_each_seed<'de, A, S, T, F>(seed: &mut S, mut seq: A, mut f: F) -> Result<(), A::Error>
+where
+    A: SeqAccess<'de>,
+    S: ?Sized,
+    T: DeserializeState<'de, S>,
+    F: FnMut(T),
+{
+    while let Some(value) = try!(seq.next_element_seed(Seed::new(&mut *seed))) {
+        f(value);
+    }
+    Ok(())
+}
+
+/// Deserializes the remaining elements of `seq` with `seed` and collects them into a `Vec`.
+///
+/// This is the loop behind the seeded `Vec` impl, for visitors which deserialize a sequence as
+/// part of something larger. The capacity reserved up front is the length claimed by
+/// `seq.size_hint()`, capped at a conservative limit, so a bogus length in the input can not make
+/// it allocate an arbitrary amount of memory. The `Vec` grows as usual past that limit.
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub fn collect_seq_seed<'de, T, S, A>(seed: &mut S, seq: &mut A) -> Result<Vec<T>, A::Error>
+where
+    T: DeserializeState<'de, S>,
+    S: ?Sized,
+    A: SeqAccess<'de>,
+{
+    SeqSeedEx::new(seed, Vec::with_capacity).visit_seq(seq)
+}
+
+/// Deserializes the remaining entries of `map` with `seed` and collects them into a `Vec` in the
+/// order they appear in the input.
+///
+/// Both keys and values are deserialized with `seed`. Capacity is reserved the same way as in
+/// `collect_seq_seed`.
+#[cfg(any(feature = "std", feature = "alloc"))]
+pub fn collect_map_seed<'de, K, V, S, A>(
+    seed: &mut S,
+    map: &mut A,
+) -> Result<Vec<(K, V)>, A::Error>
+where
+    K: DeserializeState<'de, S>,
+    V: DeserializeState<'de, S>,
+    S: ?Sized,
+    A: MapAccess<'de>,
+{
+    MapSeedEx::new(seed, Vec::with_capacity).visit_map(map)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Seed wrapper which limits how deeply values may nest while they are deserialized.
+///
+/// Recursive types which thread a seed through their fields (such as reference graphs) should
+/// enter each level through `RecursionLimit::nest`, which returns an error instead of letting
+/// maliciously deep input overflow the stack.
+#[derive(Clone, Debug)]
+pub struct RecursionLimit<S> {
+    seed: S,
+    depth: usize,
+    limit: usize,
+}
+
+impl<S> RecursionLimit<S> {
+    /// Wraps `seed`, allowing at most `limit` nested calls to `nest`
+    pub fn new(seed: S, limit: usize) -> RecursionLimit<S> {
+        RecursionLimit {
+            seed: seed,
+            depth: 0,
+            limit: limit,
+        }
+    }
+
+    /// Returns the current nesting depth
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Returns the maximum nesting depth
+    pub fn limit(&self) -> usize {
+        self.limit
+    }
+
+    /// Unwraps the inner seed
+    pub fn into_inner(self) -> S {
+        self.seed
+    }
+
+    /// Runs `f` one level deeper than the current depth, failing with `E::custom` if that would
+    /// exceed the limit
+    pub fn nest<F, R, E>(&mut self, f: F) -> Result<R, E>
+    where
+        F: FnOnce(&mut Self) -> Result<R, E>,
+        E: Error,
+    {
+        if self.depth >= self.limit {
+            return Err(E::custom(format_args!(
+                "recursion limit of {} exceeded",
+                self.limit
+            )));
+        }
+        self.depth += 1;
+        let result = f(self);
+        self.depth -= 1;
+        result
+    }
+}
+
+impl<S> ops::Deref for RecursionLimit<S> {
+    type Target = S;
+
+    fn deref(&self) -> &S {
+        &self.seed
+    }
+}
+
+impl<S> ops::DerefMut for RecursionLimit<S> {
+    fn deref_mut(&mut self) -> &mut S {
+        &mut self.seed
+    }
+}
+
+impl<S, U> AsMut<U> for RecursionLimit<S>
+where
+    S: AsMut<U>,
+{
+    fn as_mut(&mut self) -> &mut U {
+        self.seed.as_mut()
+    }
+}
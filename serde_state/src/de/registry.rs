@@ -0,0 +1,406 @@
+// Copyright 2017 Serde Developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use lib::*;
+
+use de::{value, Deserializer, Error, Expected, IgnoredAny, MapAccess, Unexpected, Visitor};
+use private::de::{BufferedContent, ContentDeserializer};
+
+/// Deserializer handed to the constructors of a `Registry`, replaying the contents of one tagged
+/// value.
+pub struct RegisteredDeserializer<'de> {
+    deserializer: ContentDeserializer<'de, RegistryError>,
+}
+
+/// Error raised while a constructor of a `Registry` deserializes its value.
+///
+/// `deserialize_registered` turns it into an error of its own deserializer by calling the same
+/// `Error` method that created it, so the caller gets the error it would have gotten had the
+/// value been deserialized directly.
+#[derive(Debug)]
+pub struct RegistryError {
+    kind: ErrorKind,
+}
+
+#[derive(Debug)]
+enum ErrorKind {
+    Custom(String),
+    InvalidType(OwnedUnexpected, String),
+    InvalidValue(OwnedUnexpected, String),
+    InvalidLength(usize, String),
+    UnknownVariant(String, &'static [&'static str]),
+    UnknownField(String, &'static [&'static str]),
+    MissingField(&'static str),
+    DuplicateField(&'static str),
+}
+
+// `Unexpected` without the borrow
+#[derive(Debug)]
+enum OwnedUnexpected {
+    Bool(bool),
+    Unsigned(u64),
+    Signed(i64),
+    Float(f64),
+    Char(char),
+    Str(String),
+    Bytes(Vec<u8>),
+    Unit,
+    Option,
+    NewtypeStruct,
+    Seq,
+    Map,
+    Enum,
+    UnitVariant,
+    NewtypeVariant,
+    TupleVariant,
+    StructVariant,
+    Other(String),
+}
+
+impl OwnedUnexpected {
+    fn new(unexpected: Unexpected) -> Self {
+        match unexpected {
+            Unexpected::Bool(v) => OwnedUnexpected::Bool(v),
+            Unexpected::Unsigned(v) => OwnedUnexpected::Unsigned(v),
+            Unexpected::Signed(v) => OwnedUnexpected::Signed(v),
+            Unexpected::Float(v) => OwnedUnexpected::Float(v),
+            Unexpected::Char(v) => OwnedUnexpected::Char(v),
+            Unexpected::Str(v) => OwnedUnexpected::Str(v.to_owned()),
+            Unexpected::Bytes(v) => OwnedUnexpected::Bytes(v.to_owned()),
+            Unexpected::Unit => OwnedUnexpected::Unit,
+            Unexpected::Option => OwnedUnexpected::Option,
+            Unexpected::NewtypeStruct => OwnedUnexpected::NewtypeStruct,
+            Unexpected::Seq => OwnedUnexpected::Seq,
+            Unexpected::Map => OwnedUnexpected::Map,
+            Unexpected::Enum => OwnedUnexpected::Enum,
+            Unexpected::UnitVariant => OwnedUnexpected::UnitVariant,
+            Unexpected::NewtypeVariant => OwnedUnexpected::NewtypeVariant,
+            Unexpected::TupleVariant => OwnedUnexpected::TupleVariant,
+            Unexpected::StructVariant => OwnedUnexpected::StructVariant,
+            Unexpected::Other(v) => OwnedUnexpected::Other(v.to_owned()),
+        }
+    }
+
+    fn as_unexpected(&self) -> Unexpected {
+        match *self {
+            OwnedUnexpected::Bool(v) => Unexpected::Bool(v),
+            OwnedUnexpected::Unsigned(v) => Unexpected::Unsigned(v),
+            OwnedUnexpected::Signed(v) => Unexpected::Signed(v),
+            OwnedUnexpected::Float(v) => Unexpected::Float(v),
+            OwnedUnexpected::Char(v) => Unexpected::Char(v),
+            OwnedUnexpected::Str(ref v) => Unexpected::Str(v),
+            OwnedUnexpected::Bytes(ref v) => Unexpected::Bytes(v),
+            OwnedUnexpected::Unit => Unexpected::Unit,
+            OwnedUnexpected::Option => Unexpected::Option,
+            OwnedUnexpected::NewtypeStruct => Unexpected::NewtypeStruct,
+            OwnedUnexpected::Seq => Unexpected::Seq,
+            OwnedUnexpected::Map => Unexpected::Map,
+            OwnedUnexpected::Enum => Unexpected::Enum,
+            OwnedUnexpected::UnitVariant => Unexpected::UnitVariant,
+            OwnedUnexpected::NewtypeVariant => Unexpected::NewtypeVariant,
+            OwnedUnexpected::TupleVariant => Unexpected::TupleVariant,
+            OwnedUnexpected::StructVariant => Unexpected::StructVariant,
+            OwnedUnexpected::Other(ref v) => Unexpected::Other(v),
+        }
+    }
+}
+
+impl RegistryError {
+    /// Raises this error again with the error type `E`
+    pub fn into_error<E>(self) -> E
+    where
+        E: Error,
+    {
+        self.to_error()
+    }
+
+    fn to_error<E>(&self) -> E
+    where
+        E: Error,
+    {
+        match self.kind {
+            ErrorKind::Custom(ref msg) => E::custom(msg),
+            ErrorKind::InvalidType(ref unexpected, ref expected) => {
+                E::invalid_type(unexpected.as_unexpected(), &&**expected)
+            }
+            ErrorKind::InvalidValue(ref unexpected, ref expected) => {
+                E::invalid_value(unexpected.as_unexpected(), &&**expected)
+            }
+            ErrorKind::InvalidLength(len, ref expected) => E::invalid_length(len, &&**expected),
+            ErrorKind::UnknownVariant(ref variant, expected) => {
+                E::unknown_variant(variant, expected)
+            }
+            ErrorKind::UnknownField(ref field, expected) => E::unknown_field(field, expected),
+            ErrorKind::MissingField(field) => E::missing_field(field),
+            ErrorKind::DuplicateField(field) => E::duplicate_field(field),
+        }
+    }
+}
+
+impl Error for RegistryError {
+    fn custom<T>(msg: T) -> Self
+    where
+        T: Display,
+    {
+        RegistryError {
+            kind: ErrorKind::Custom(msg.to_string()),
+        }
+    }
+
+    fn invalid_type(unexpected: Unexpected, expected: &dyn Expected) -> Self {
+        RegistryError {
+            kind: ErrorKind::InvalidType(OwnedUnexpected::new(unexpected), expected.to_string()),
+        }
+    }
+
+    fn invalid_value(unexpected: Unexpected, expected: &dyn Expected) -> Self {
+        RegistryError {
+            kind: ErrorKind::InvalidValue(OwnedUnexpected::new(unexpected), expected.to_string()),
+        }
+    }
+
+    fn invalid_length(len: usize, expected: &dyn Expected) -> Self {
+        RegistryError {
+            kind: ErrorKind::InvalidLength(len, expected.to_string()),
+        }
+    }
+
+    fn unknown_variant(variant: &str, expected: &'static [&'static str]) -> Self {
+        RegistryError {
+            kind: ErrorKind::UnknownVariant(variant.to_owned(), expected),
+        }
+    }
+
+    fn unknown_field(field: &str, expected: &'static [&'static str]) -> Self {
+        RegistryError {
+            kind: ErrorKind::UnknownField(field.to_owned(), expected),
+        }
+    }
+
+    fn missing_field(field: &'static str) -> Self {
+        RegistryError {
+            kind: ErrorKind::MissingField(field),
+        }
+    }
+
+    fn duplicate_field(field: &'static str) -> Self {
+        RegistryError {
+            kind: ErrorKind::DuplicateField(field),
+        }
+    }
+}
+
+impl Display for RegistryError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        Display::fmt(&self.to_error::<value::Error>(), formatter)
+    }
+}
+
+impl error::Error for RegistryError {}
+
+/// Constructor of one of the types in a `Registry`, which is handed the seed along with the
+/// contents of the tagged value.
+pub type Constructor<S, T> =
+    for<'de> fn(&mut S, RegisteredDeserializer<'de>) -> Result<Box<T>, RegistryError>;
+
+/// Registry of the types which may be deserialized into a `Box<T>`, usually a `Box<dyn Trait>`,
+/// keyed by the tag they are stored under.
+///
+/// A boxed value is a map with a single entry which maps the tag of its type to its contents,
+/// the same shape as an externally tagged enum. Use `deserialize_registered` with
+/// `#[serde(deserialize_state_with = "...")]` on fields whose seed `S` borrows as a registry. The
+/// constructors get the seed as well, so registered types may contain registered values of their
+/// own.
+pub struct Registry<S: ?Sized, T: ?Sized> {
+    constructors: HashMap<String, Constructor<S, T>>,
+}
+
+impl<S: ?Sized, T: ?Sized> Registry<S, T> {
+    /// Constructs an empty registry
+    pub fn new() -> Self {
+        Registry {
+            constructors: HashMap::new(),
+        }
+    }
+
+    /// Registers `constructor` for values tagged with `tag`, replacing any earlier constructor for
+    /// the same tag
+    pub fn register(&mut self, tag: &str, constructor: Constructor<S, T>) {
+        self.constructors.insert(tag.to_owned(), constructor);
+    }
+}
+
+impl<S: ?Sized, T: ?Sized> Default for Registry<S, T> {
+    fn default() -> Self {
+        Registry::new()
+    }
+}
+
+/// Deserializes a `Box<T>` by looking up the constructor for its tag in the registry held by
+/// `seed`.
+pub fn deserialize_registered<'de, S, T, D>(
+    seed: &mut S,
+    deserializer: D,
+) -> Result<Box<T>, D::Error>
+where
+    S: ?Sized + Borrow<Registry<S, T>>,
+    T: ?Sized,
+    D: Deserializer<'de>,
+{
+    let (tag, content) = try!(deserializer.deserialize_map(TaggedVisitor));
+    let constructor = {
+        let registry: &Registry<S, T> = Borrow::borrow(&*seed);
+        match registry.constructors.get(&tag) {
+            Some(constructor) => *constructor,
+            None => return Err(D::Error::custom(format_args!("unknown type tag `{}`", tag))),
+        }
+    };
+    let deserializer = RegisteredDeserializer {
+        deserializer: content.into_deserializer(),
+    };
+    constructor(seed, deserializer).map_err(RegistryError::into_error)
+}
+
+struct TaggedVisitor;
+
+impl<'de> Visitor<'de> for TaggedVisitor {
+    type Value = (String, BufferedContent<'de>);
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map with a single registered type tag")
+    }
+
+    fn visit_map<A>(self, mut access: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let tag: String = match try!(access.next_key()) {
+            Some(tag) => tag,
+            None => return Err(A::Error::invalid_length(0, &self)),
+        };
+        let content = try!(access.next_value());
+
+        if try!(access.next_key::<IgnoredAny>()).is_some() {
+            return Err(A::Error::invalid_length(2, &self));
+        }
+        Ok((tag, content))
+    }
+}
+
+impl<'de> Deserializer<'de> for RegisteredDeserializer<'de> {
+    type Error = RegistryError;
+
+    forward_deserialize! {
+        deserialize_any,
+        deserialize_bool,
+        deserialize_i8,
+        deserialize_i16,
+        deserialize_i32,
+        deserialize_i64,
+        deserialize_u8,
+        deserialize_u16,
+        deserialize_u32,
+        deserialize_u64,
+        deserialize_f32,
+        deserialize_f64,
+        deserialize_char,
+        deserialize_str,
+        deserialize_string,
+        deserialize_bytes,
+        deserialize_byte_buf,
+        deserialize_option,
+        deserialize_unit,
+        deserialize_seq,
+        deserialize_map,
+        deserialize_identifier,
+        deserialize_ignored_any
+    }
+
+    serde_if_integer128! {
+        forward_deserialize! {
+            deserialize_i128,
+            deserialize_u128
+        }
+    }
+
+    #[inline]
+    fn deserialize_unit_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserializer.deserialize_unit_struct(name, visitor)
+    }
+
+    #[inline]
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserializer.deserialize_newtype_struct(name, visitor)
+    }
+
+    #[inline]
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserializer.deserialize_tuple(len, visitor)
+    }
+
+    #[inline]
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserializer.deserialize_tuple_struct(name, len, visitor)
+    }
+
+    #[inline]
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserializer.deserialize_struct(name, fields, visitor)
+    }
+
+    #[inline]
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserializer.deserialize_enum(name, variants, visitor)
+    }
+
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        self.deserializer.is_human_readable()
+    }
+}
@@ -9,13 +9,10 @@
 use lib::*;
 
 use de::{
-    Deserialize, DeserializeSeed, DeserializeState, Deserializer, EnumAccess, Error, Seed,
-    SeqAccess, Unexpected, VariantAccess, Visitor,
+    Deserialize, DeserializeSeed, DeserializeState, Deserializer, EnumAccess, Error, MapAccess,
+    Seed, SeqAccess, Unexpected, VariantAccess, Visitor,
 };
 
-#[cfg(any(feature = "std", feature = "alloc"))]
-use de::MapAccess;
-
 use private::de::size_hint;
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -50,7 +47,15 @@ deserialize_impl! {
     f32,
     f64,
     (),
-    bool
+    bool,
+    char
+}
+
+serde_if_integer128! {
+    deserialize_impl! {
+        i128,
+        u128
+    }
 }
 
 #[cfg(any(feature = "std", feature = "alloc"))]
@@ -58,6 +63,46 @@ deserialize_impl! {
     String
 }
 
+#[cfg(feature = "std")]
+deserialize_impl! {
+    Duration,
+    SystemTime
+}
+
+#[cfg(feature = "std")]
+deserialize_impl! {
+    net::IpAddr,
+    net::Ipv4Addr,
+    net::Ipv6Addr,
+    net::SocketAddr,
+    net::SocketAddrV4,
+    net::SocketAddrV6
+}
+
+#[cfg(feature = "std")]
+deserialize_impl! {
+    PathBuf
+}
+
+#[cfg(all(feature = "std", any(unix, windows)))]
+deserialize_impl! {
+    OsString
+}
+
+#[cfg(feature = "unstable")]
+deserialize_impl! {
+    NonZeroU8,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroUsize,
+    NonZeroI8,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64,
+    NonZeroIsize
+}
+
 macro_rules! forwarded_impl {
     (( $($id: ident),* ), $ty: ty, $func: expr) => {
         impl<'de, S $(, $id)*> DeserializeState<'de, S> for $ty
@@ -190,6 +235,7 @@ where
 pub struct SeqSeedEx<'seed, S, F, T: ?Sized + 'seed, U> {
     seed: &'seed mut T,
     with_capacity: F,
+    capacity_limit: usize,
     _marker: PhantomData<(S, U)>,
 }
 
@@ -201,14 +247,42 @@ where
 {
     /// Constructs a new instance of `SeqSeedEx`
     pub fn new(seed: &'seed mut T, with_capacity: F) -> SeqSeedEx<'seed, S, F, T, U> {
+        SeqSeedEx::with_capacity_limit(seed, with_capacity, size_hint::CAUTIOUS_LIMIT)
+    }
+
+    /// Constructs a new instance of `SeqSeedEx` which never passes a capacity larger than
+    /// `capacity_limit` to `with_capacity`, regardless of the length the input claims to have.
+    /// Any elements beyond the limit are added by growing the collection as usual.
+    pub fn with_capacity_limit(
+        seed: &'seed mut T,
+        with_capacity: F,
+        capacity_limit: usize,
+    ) -> SeqSeedEx<'seed, S, F, T, U> {
         SeqSeedEx {
             seed: seed,
             with_capacity: with_capacity,
+            capacity_limit: capacity_limit,
             _marker: PhantomData,
         }
     }
 }
 
+impl<'seed, 'de, S, T: ?Sized, U> SeqSeedEx<'seed, S, fn(usize) -> S, T, U>
+where
+    U: DeserializeState<'de, T>,
+    S: Default + Extend<U>,
+{
+    /// Constructs a new instance of `SeqSeedEx` which starts from `S::default()`, for
+    /// collections such as `LinkedList` which cannot preallocate
+    pub fn new_default(seed: &'seed mut T) -> SeqSeedEx<'seed, S, fn(usize) -> S, T, U> {
+        SeqSeedEx::new(seed, default_ignoring_capacity::<S> as fn(usize) -> S)
+    }
+}
+
+fn default_ignoring_capacity<S: Default>(_capacity: usize) -> S {
+    S::default()
+}
+
 impl<'de, 'seed, S, F, T: ?Sized, U> Visitor<'de> for SeqSeedEx<'seed, S, F, T, U>
 where
     U: DeserializeState<'de, T>,
@@ -226,7 +300,8 @@ where
     where
         A: SeqAccess<'de>,
     {
-        let mut values = (self.with_capacity)(size_hint::cautious(access.size_hint()));
+        let capacity = cmp::min(access.size_hint().unwrap_or(0), self.capacity_limit);
+        let mut values = (self.with_capacity)(capacity);
 
         while let Some(value) = try!(access.next_element_seed(Seed::new(&mut *&mut *self.seed))) {
             values.extend(Some(value));
@@ -252,6 +327,234 @@ where
     }
 }
 
+enum MapSeeds<'seed, T: ?Sized + 'seed, U: ?Sized + 'seed, C: ?Sized + 'seed> {
+    Projected(&'seed mut C, fn(&mut C) -> &mut T, fn(&mut C) -> &mut U),
+    Split(&'seed mut T, &'seed mut U),
+}
+
+impl<'seed, T: ?Sized, U: ?Sized, C: ?Sized> MapSeeds<'seed, T, U, C> {
+    fn key(&mut self) -> &mut T {
+        match *self {
+            MapSeeds::Projected(ref mut seed, project, _) => project(&mut **seed),
+            MapSeeds::Split(ref mut key_seed, _) => &mut **key_seed,
+        }
+    }
+
+    fn value(&mut self) -> &mut U {
+        match *self {
+            MapSeeds::Projected(ref mut seed, _, project) => project(&mut **seed),
+            MapSeeds::Split(_, ref mut value_seed) => &mut **value_seed,
+        }
+    }
+}
+
+fn same_seed<T: ?Sized>(seed: &mut T) -> &mut T {
+    seed
+}
+
+/// `MapSeedEx` implements `DeserializeSeed` for maps whose keys and values implement
+/// `DeserializeState`.
+///
+/// The keys are deserialized with a `T` seed and the values with a `U` seed, which is the same
+/// seed unless the map is constructed with `with_key_value` or `with_projections`.
+///
+/// Entries are passed to `Extend::extend` one at a time in the order they appear in the input, so
+/// ordered containers such as `Vec<(K, V)>` or `IndexMap` (created with `IndexMap::with_capacity`)
+/// preserve that order and a duplicated key is inserted after, and thus overrides, the earlier one.
+pub struct MapSeedEx<'seed, S, F, T: ?Sized + 'seed, K, V, U: ?Sized + 'seed = T, C: ?Sized + 'seed = T>
+{
+    seeds: MapSeeds<'seed, T, U, C>,
+    with_capacity: F,
+    _marker: PhantomData<(S, K, V)>,
+}
+
+impl<'seed, 'de, S, F, T: ?Sized, K, V> MapSeedEx<'seed, S, F, T, K, V>
+where
+    K: DeserializeState<'de, T>,
+    V: DeserializeState<'de, T>,
+    F: FnOnce(usize) -> S,
+    S: Extend<(K, V)>,
+{
+    /// Constructs a new instance of `MapSeedEx`
+    pub fn new(seed: &'seed mut T, with_capacity: F) -> MapSeedEx<'seed, S, F, T, K, V> {
+        MapSeedEx {
+            seeds: MapSeeds::Projected(seed, same_seed, same_seed),
+            with_capacity: with_capacity,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'seed, 'de, S, F, T: ?Sized, K, V, U: ?Sized> MapSeedEx<'seed, S, F, T, K, V, U>
+where
+    K: DeserializeState<'de, T>,
+    V: DeserializeState<'de, U>,
+    F: FnOnce(usize) -> S,
+    S: Extend<(K, V)>,
+{
+    /// Constructs a new instance of `MapSeedEx` which deserializes keys with `key_seed` and
+    /// values with `value_seed`
+    pub fn with_key_value(
+        key_seed: &'seed mut T,
+        value_seed: &'seed mut U,
+        with_capacity: F,
+    ) -> MapSeedEx<'seed, S, F, T, K, V, U> {
+        MapSeedEx {
+            seeds: MapSeeds::Split(key_seed, value_seed),
+            with_capacity: with_capacity,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'seed, 'de, S, F, T: ?Sized, K, V, U: ?Sized, C: ?Sized> MapSeedEx<'seed, S, F, T, K, V, U, C>
+where
+    K: DeserializeState<'de, T>,
+    V: DeserializeState<'de, U>,
+    F: FnOnce(usize) -> S,
+    S: Extend<(K, V)>,
+{
+    /// Constructs a new instance of `MapSeedEx` which deserializes keys with the part of `seed`
+    /// returned by `key_seed` and values with the part returned by `value_seed`.
+    ///
+    /// The projections are applied anew for every key and value, so both may return the same part
+    /// of `seed`, such as an interner which both keys and values look their symbols up in.
+    pub fn with_projections(
+        seed: &'seed mut C,
+        key_seed: fn(&mut C) -> &mut T,
+        value_seed: fn(&mut C) -> &mut U,
+        with_capacity: F,
+    ) -> MapSeedEx<'seed, S, F, T, K, V, U, C> {
+        MapSeedEx {
+            seeds: MapSeeds::Projected(seed, key_seed, value_seed),
+            with_capacity: with_capacity,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'de, 'seed, S, F, T: ?Sized, K, V, U: ?Sized, C: ?Sized> Visitor<'de>
+    for MapSeedEx<'seed, S, F, T, K, V, U, C>
+where
+    K: DeserializeState<'de, T>,
+    V: DeserializeState<'de, U>,
+    F: FnOnce(usize) -> S,
+    S: Extend<(K, V)>,
+{
+    type Value = S;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map")
+    }
+
+    #[inline]
+    fn visit_map<A>(mut self, mut access: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut values = (self.with_capacity)(size_hint::cautious(access.size_hint()));
+
+        while let Some(key) = try!(access.next_key_seed(Seed::new(self.seeds.key()))) {
+            let value = try!(access.next_value_seed(Seed::new(self.seeds.value())));
+            values.extend(Some((key, value)));
+        }
+
+        Ok(values)
+    }
+}
+
+impl<'de, 'seed, S, F, T: ?Sized, K, V, U: ?Sized, C: ?Sized> DeserializeSeed<'de>
+    for MapSeedEx<'seed, S, F, T, K, V, U, C>
+where
+    K: DeserializeState<'de, T>,
+    V: DeserializeState<'de, U>,
+    F: FnOnce(usize) -> S,
+    S: Extend<(K, V)>,
+{
+    type Value = S;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
+/// `MapValueSeedEx` implements `DeserializeSeed` for maps whose keys implement `Deserialize` and
+/// whose values implement `DeserializeState`.
+///
+/// Each entry is read with `next_key` followed by `next_value_seed` and inserted directly into
+/// the map, so nothing is buffered along the way.
+pub struct MapValueSeedEx<'seed, S, F, T: ?Sized + 'seed, K, V> {
+    seed: &'seed mut T,
+    with_capacity: F,
+    _marker: PhantomData<(S, K, V)>,
+}
+
+impl<'seed, 'de, S, F, T: ?Sized, K, V> MapValueSeedEx<'seed, S, F, T, K, V>
+where
+    K: Deserialize<'de>,
+    V: DeserializeState<'de, T>,
+    F: FnOnce(usize) -> S,
+    S: Extend<(K, V)>,
+{
+    /// Constructs a new instance of `MapValueSeedEx`
+    pub fn new(seed: &'seed mut T, with_capacity: F) -> MapValueSeedEx<'seed, S, F, T, K, V> {
+        MapValueSeedEx {
+            seed: seed,
+            with_capacity: with_capacity,
+            _marker: PhantomData,
+        }
+    }
+}
+
+impl<'de, 'seed, S, F, T: ?Sized, K, V> Visitor<'de> for MapValueSeedEx<'seed, S, F, T, K, V>
+where
+    K: Deserialize<'de>,
+    V: DeserializeState<'de, T>,
+    F: FnOnce(usize) -> S,
+    S: Extend<(K, V)>,
+{
+    type Value = S;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a map")
+    }
+
+    #[inline]
+    fn visit_map<A>(self, mut access: A) -> Result<Self::Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut values = (self.with_capacity)(size_hint::cautious(access.size_hint()));
+
+        while let Some(key) = try!(access.next_key()) {
+            let value = try!(access.next_value_seed(Seed::new(&mut *self.seed)));
+            values.extend(Some((key, value)));
+        }
+
+        Ok(values)
+    }
+}
+
+impl<'de, 'seed, S, F, T: ?Sized, K, V> DeserializeSeed<'de> for MapValueSeedEx<'seed, S, F, T, K, V>
+where
+    K: Deserialize<'de>,
+    V: DeserializeState<'de, T>,
+    F: FnOnce(usize) -> S,
+    S: Extend<(K, V)>,
+{
+    type Value = S;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_map(self)
+    }
+}
+
 /// `DeserializeSeed` instances for optional values
 pub struct OptionSeed<S>(pub S);
 
@@ -364,7 +667,61 @@ seq_impl!(
     HashSet::insert);
 
 #[cfg(any(feature = "std", feature = "alloc"))]
-seq_impl!(Vec<T>, seq, Vec::new(), Vec::with_capacity, Vec::push);
+impl<'de, S: ?Sized, T> DeserializeState<'de, S> for Vec<T>
+where
+    T: DeserializeState<'de, S>,
+{
+    fn deserialize_state<D>(seed: &mut S, deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let visitor = SeqSeedEx::new(seed, Vec::with_capacity);
+        deserializer.deserialize_seq(visitor)
+    }
+
+    fn deserialize_state_in_place<D>(
+        seed: &mut S,
+        deserializer: D,
+        place: &mut Self,
+    ) -> Result<(), D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct VecInPlaceVisitor<'a, 'seed, S: ?Sized + 'seed, T: 'a> {
+            seed: &'seed mut S,
+            place: &'a mut Vec<T>,
+        }
+
+        impl<'a, 'seed, 'de, S: ?Sized, T> Visitor<'de> for VecInPlaceVisitor<'a, 'seed, S, T>
+        where
+            T: DeserializeState<'de, S>,
+        {
+            type Value = ();
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a sequence")
+            }
+
+            fn visit_seq<A>(self, mut access: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                // Keep the existing allocation, only growing it if the hint asks for more
+                self.place.clear();
+                self.place.reserve(size_hint::cautious(access.size_hint()));
+                while let Some(value) = try!(access.next_element_seed(Seed::new(&mut *self.seed))) {
+                    self.place.push(value);
+                }
+                Ok(())
+            }
+        }
+
+        deserializer.deserialize_seq(VecInPlaceVisitor {
+            seed: seed,
+            place: place,
+        })
+    }
+}
 
 #[cfg(any(feature = "std", feature = "alloc"))]
 seq_impl!(
@@ -578,50 +935,17 @@ macro_rules! map_impl {
         $ctor:expr,
         $with_capacity:expr
     ) => {
-        impl<'de, S2, K, V $(, $typaram)*> DeserializeState<'de, S2> for $ty<K, V $(, $typaram)*>
+        impl<'de, Seed: ?Sized, K, V $(, $typaram)*> DeserializeState<'de, Seed> for $ty<K, V $(, $typaram)*>
         where
-            K: DeserializeState<'de, S2> $(+ $kbound1 $(+ $kbound2)*)*,
-            V: DeserializeState<'de, S2>,
+            K: DeserializeState<'de, Seed> $(+ $kbound1 $(+ $kbound2)*)*,
+            V: DeserializeState<'de, Seed>,
             $($typaram: $bound1 $(+ $bound2)*),*
         {
-            fn deserialize_state<D>(seed: &mut S2, deserializer: D) -> Result<Self, D::Error>
+            fn deserialize_state<D>(seed: &mut Seed, deserializer: D) -> Result<Self, D::Error>
             where
                 D: Deserializer<'de>,
             {
-                struct MapVisitor<'seed, S2: 'seed, K, V $(, $typaram)*> {
-                    seed: &'seed mut S2,
-                    marker: PhantomData<$ty<K, V $(, $typaram)*>>,
-                }
-
-                impl<'de, 'seed, S2, K, V $(, $typaram)*> Visitor<'de> for MapVisitor<'seed, S2, K, V $(, $typaram)*>
-                where
-                    K: DeserializeState<'de, S2> $(+ $kbound1 $(+ $kbound2)*)*,
-                    V: DeserializeState<'de, S2>,
-                    $($typaram: $bound1 $(+ $bound2)*),*
-                {
-                    type Value = $ty<K, V $(, $typaram)*>;
-
-                    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                        formatter.write_str("a map")
-                    }
-
-                    #[inline]
-                    fn visit_map<A>(self, mut $access: A) -> Result<Self::Value, A::Error>
-                    where
-                        A: MapAccess<'de>,
-                    {
-                        let mut values = $with_capacity;
-
-                        while let Some(key) = try!($access.next_key_seed(Seed::new(&mut *self.seed))) {
-                            let value = try!($access.next_value_seed(Seed::new(&mut *self.seed)));
-                            values.insert(key, value);
-                        }
-
-                        Ok(values)
-                    }
-                }
-
-                let visitor = MapVisitor { seed: seed, marker: PhantomData };
+                let visitor = MapSeedEx::new(seed, $with_capacity);
                 deserializer.deserialize_map(visitor)
             }
         }
@@ -633,14 +957,14 @@ map_impl!(
     BTreeMap<K: Ord, V>,
     map,
     BTreeMap::new(),
-    BTreeMap::new());
+    |_| BTreeMap::new());
 
 #[cfg(feature = "std")]
 map_impl!(
     HashMap<K: Eq + Hash, V, S: BuildHasher + Default>,
     map,
     HashMap::with_hasher(S::default()),
-    HashMap::with_capacity_and_hasher(size_hint::cautious(map.size_hint()), S::default()));
+    |size| HashMap::with_capacity_and_hasher(size, S::default()));
 
 ////////////////////////////////////////////////////////////////////////////////
 
@@ -650,6 +974,9 @@ forwarded_impl!((T), Box<T>, Box::new);
 #[cfg(any(feature = "std", feature = "alloc"))]
 forwarded_impl!((T), Box<[T]>, Vec::into_boxed_slice);
 
+#[cfg(any(feature = "std", feature = "alloc"))]
+forwarded_impl!((), Box<str>, String::into_boxed_str);
+
 #[cfg(all(feature = "rc", any(feature = "std", feature = "alloc")))]
 forwarded_impl!((T), Arc<T>, Arc::new);
 
@@ -683,6 +1010,8 @@ forwarded_impl!((T), Mutex<T>, Mutex::new);
 #[cfg(feature = "std")]
 forwarded_impl!((T), RwLock<T>, RwLock::new);
 
+forwarded_impl!((T), Wrapping<T>, Wrapping);
+
 ////////////////////////////////////////////////////////////////////////////////
 
 // Similar to:
@@ -694,7 +1023,7 @@ forwarded_impl!((T), RwLock<T>, RwLock::new);
 //         end: u32,
 //     }
 #[cfg(feature = "std")]
-impl<'de, S, Idx> DeserializeState<'de, S> for ops::Range<Idx>
+impl<'de, S: ?Sized, Idx> DeserializeState<'de, S> for ops::Range<Idx>
 where
     Idx: DeserializeState<'de, S>,
 {
@@ -702,140 +1031,172 @@ where
     where
         D: Deserializer<'de>,
     {
-        // If this were outside of the serde crate, it would just use:
-        //
-        //    #[derive(Deserialize)]
-        //    #[serde(field_identifier, rename_all = "lowercase")]
-        enum Field {
-            Start,
-            End,
-        }
+        let (start, end) = try!(deserializer.deserialize_struct(
+            "Range",
+            range::FIELDS,
+            range::RangeVisitor {
+                expecting: "struct Range",
+                seed: seed,
+                phantom: PhantomData,
+            },
+        ));
+        Ok(start..end)
+    }
+}
 
-        impl<'de> Deserialize<'de> for Field {
-            fn deserialize<D>(deserializer: D) -> Result<Field, D::Error>
-            where
-                D: Deserializer<'de>,
-            {
-                struct FieldVisitor;
+#[cfg(feature = "std")]
+impl<'de, S: ?Sized, Idx> DeserializeState<'de, S> for ops::RangeInclusive<Idx>
+where
+    Idx: DeserializeState<'de, S>,
+{
+    fn deserialize_state<D>(seed: &mut S, deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let (start, end) = try!(deserializer.deserialize_struct(
+            "RangeInclusive",
+            range::FIELDS,
+            range::RangeVisitor {
+                expecting: "struct RangeInclusive",
+                seed: seed,
+                phantom: PhantomData,
+            },
+        ));
+        Ok(ops::RangeInclusive::new(start, end))
+    }
+}
 
-                impl<'de> Visitor<'de> for FieldVisitor {
-                    type Value = Field;
+#[cfg(feature = "std")]
+mod range {
+    use lib::*;
 
-                    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                        formatter.write_str("`start` or `end`")
-                    }
+    use de::{Deserialize, DeserializeState, Deserializer, Error, MapAccess, Seed, SeqAccess, Visitor};
 
-                    fn visit_str<E>(self, value: &str) -> Result<Field, E>
-                    where
-                        E: Error,
-                    {
-                        match value {
-                            "start" => Ok(Field::Start),
-                            "end" => Ok(Field::End),
-                            _ => Err(Error::unknown_field(value, FIELDS)),
-                        }
+    pub const FIELDS: &'static [&'static str] = &["start", "end"];
+
+    // If this were outside of the serde crate, it would just use:
+    //
+    //    #[derive(Deserialize)]
+    //    #[serde(field_identifier, rename_all = "lowercase")]
+    enum Field {
+        Start,
+        End,
+    }
+
+    impl<'de> Deserialize<'de> for Field {
+        fn deserialize<D>(deserializer: D) -> Result<Field, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            struct FieldVisitor;
+
+            impl<'de> Visitor<'de> for FieldVisitor {
+                type Value = Field;
+
+                fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                    formatter.write_str("`start` or `end`")
+                }
+
+                fn visit_str<E>(self, value: &str) -> Result<Field, E>
+                where
+                    E: Error,
+                {
+                    match value {
+                        "start" => Ok(Field::Start),
+                        "end" => Ok(Field::End),
+                        _ => Err(Error::unknown_field(value, FIELDS)),
                     }
+                }
 
-                    fn visit_bytes<E>(self, value: &[u8]) -> Result<Field, E>
-                    where
-                        E: Error,
-                    {
-                        match value {
-                            b"start" => Ok(Field::Start),
-                            b"end" => Ok(Field::End),
-                            _ => {
-                                let value = String::from_utf8_lossy(value);
-                                Err(Error::unknown_field(&value, FIELDS))
-                            }
+                fn visit_bytes<E>(self, value: &[u8]) -> Result<Field, E>
+                where
+                    E: Error,
+                {
+                    match value {
+                        b"start" => Ok(Field::Start),
+                        b"end" => Ok(Field::End),
+                        _ => {
+                            let value = String::from_utf8_lossy(value);
+                            Err(Error::unknown_field(&value, FIELDS))
                         }
                     }
                 }
-
-                deserializer.deserialize_identifier(FieldVisitor)
             }
+
+            deserializer.deserialize_identifier(FieldVisitor)
         }
+    }
 
-        struct RangeVisitor<'seed, S: 'seed, Idx> {
-            seed: &'seed mut S,
-            phantom: PhantomData<Idx>,
+    pub struct RangeVisitor<'seed, S: ?Sized + 'seed, Idx> {
+        pub expecting: &'static str,
+        pub seed: &'seed mut S,
+        pub phantom: PhantomData<Idx>,
+    }
+
+    impl<'de, 'seed, S: ?Sized, Idx> Visitor<'de> for RangeVisitor<'seed, S, Idx>
+    where
+        Idx: DeserializeState<'de, S>,
+    {
+        type Value = (Idx, Idx);
+
+        fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+            formatter.write_str(self.expecting)
         }
 
-        impl<'de, 'seed, S, Idx> Visitor<'de> for RangeVisitor<'seed, S, Idx>
+        fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
         where
-            Idx: DeserializeState<'de, S>,
+            A: SeqAccess<'de>,
         {
-            type Value = ops::Range<Idx>;
-
-            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
-                formatter.write_str("struct Range")
-            }
-
-            fn visit_seq<A>(self, mut seq: A) -> Result<ops::Range<Idx>, A::Error>
-            where
-                A: SeqAccess<'de>,
-            {
-                let start: Idx = match try!(seq.next_element_seed(Seed::new(&mut *self.seed))) {
-                    Some(value) => value,
-                    None => {
-                        return Err(Error::invalid_length(0, &self));
-                    }
-                };
-                let end: Idx = match try!(seq.next_element_seed(Seed::new(&mut *self.seed))) {
-                    Some(value) => value,
-                    None => {
-                        return Err(Error::invalid_length(1, &self));
-                    }
-                };
-                Ok(start..end)
-            }
+            let start: Idx = match try!(seq.next_element_seed(Seed::new(&mut *self.seed))) {
+                Some(value) => value,
+                None => {
+                    return Err(Error::invalid_length(0, &self));
+                }
+            };
+            let end: Idx = match try!(seq.next_element_seed(Seed::new(&mut *self.seed))) {
+                Some(value) => value,
+                None => {
+                    return Err(Error::invalid_length(1, &self));
+                }
+            };
+            Ok((start, end))
+        }
 
-            fn visit_map<A>(self, mut map: A) -> Result<ops::Range<Idx>, A::Error>
-            where
-                A: MapAccess<'de>,
-            {
-                let mut start: Option<Idx> = None;
-                let mut end: Option<Idx> = None;
-                while let Some(key) = try!(map.next_key()) {
-                    match key {
-                        Field::Start => {
-                            if start.is_some() {
-                                return Err(<A::Error as Error>::duplicate_field("start"));
-                            }
-                            start = Some(try!(map.next_value_seed(Seed::new(&mut *self.seed))));
+        fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+        where
+            A: MapAccess<'de>,
+        {
+            let mut start: Option<Idx> = None;
+            let mut end: Option<Idx> = None;
+            while let Some(key) = try!(map.next_key()) {
+                match key {
+                    Field::Start => {
+                        if start.is_some() {
+                            return Err(<A::Error as Error>::duplicate_field("start"));
                         }
-                        Field::End => {
-                            if end.is_some() {
-                                return Err(<A::Error as Error>::duplicate_field("end"));
-                            }
-                            end = Some(try!(map.next_value_seed(Seed::new(&mut *self.seed))));
+                        start = Some(try!(map.next_value_seed(Seed::new(&mut *self.seed))));
+                    }
+                    Field::End => {
+                        if end.is_some() {
+                            return Err(<A::Error as Error>::duplicate_field("end"));
                         }
+                        end = Some(try!(map.next_value_seed(Seed::new(&mut *self.seed))));
                     }
                 }
-                let start = match start {
-                    Some(start) => start,
-                    None => return Err(<A::Error as Error>::missing_field("start")),
-                };
-                let end = match end {
-                    Some(end) => end,
-                    None => return Err(<A::Error as Error>::missing_field("end")),
-                };
-                Ok(start..end)
             }
+            let start = match start {
+                Some(start) => start,
+                None => return Err(<A::Error as Error>::missing_field("start")),
+            };
+            let end = match end {
+                Some(end) => end,
+                None => return Err(<A::Error as Error>::missing_field("end")),
+            };
+            Ok((start, end))
         }
-
-        const FIELDS: &'static [&'static str] = &["start", "end"];
-        deserializer.deserialize_struct(
-            "Range",
-            FIELDS,
-            RangeVisitor {
-                seed,
-                phantom: PhantomData,
-            },
-        )
     }
 }
 
-
 ////////////////////////////////////////////////////////////////////////////////
 
 impl<'de, S, T, E> DeserializeState<'de, S> for Result<T, E>
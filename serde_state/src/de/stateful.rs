@@ -0,0 +1,184 @@
+// Copyright 2017 Serde Developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+use de::{Deserializer, Visitor};
+
+/// Deserializers which carry a seed alongside them.
+///
+/// A `Deserialize` impl which is generic over `D: Deserializer<'de> + SeedAccess<S>` can retrieve
+/// the seed before handing the deserializer on, which lets types that can not implement
+/// `DeserializeState` still make use of the seed.
+pub trait SeedAccess<S: ?Sized> {
+    /// Returns the seed carried by this deserializer
+    fn seed(&mut self) -> &mut S;
+}
+
+/// `StatefulDeserializer` wraps a `Deserializer` together with a seed and forwards every method to
+/// the wrapped deserializer.
+///
+/// The seed is only reachable from the value handed the `StatefulDeserializer` itself. Nested
+/// values are deserialized through the `SeqAccess`, `MapAccess` and `EnumAccess` of the wrapped
+/// deserializer, so they do not see the seed; use `DeserializeState` to thread it further down.
+/// The seed is borrowed mutably for `'s`, so it can not be used elsewhere until the
+/// `StatefulDeserializer` has been consumed or dropped. No unsafe code is involved.
+#[derive(Debug)]
+pub struct StatefulDeserializer<'s, D, S: ?Sized + 's> {
+    deserializer: D,
+    seed: &'s mut S,
+}
+
+impl<'s, D, S: ?Sized> StatefulDeserializer<'s, D, S> {
+    /// Constructs a new instance of `StatefulDeserializer`
+    pub fn new(deserializer: D, seed: &'s mut S) -> StatefulDeserializer<'s, D, S> {
+        StatefulDeserializer {
+            deserializer: deserializer,
+            seed: seed,
+        }
+    }
+
+    /// Returns the wrapped deserializer and the seed
+    pub fn into_parts(self) -> (D, &'s mut S) {
+        (self.deserializer, self.seed)
+    }
+}
+
+impl<'s, D, S: ?Sized> SeedAccess<S> for StatefulDeserializer<'s, D, S> {
+    fn seed(&mut self) -> &mut S {
+        self.seed
+    }
+}
+
+macro_rules! forward_deserialize {
+    ($($method: ident),*) => {
+        $(
+        #[inline]
+        fn $method<V>(self, visitor: V) -> Result<V::Value, Self::Error>
+        where
+            V: Visitor<'de>,
+        {
+            self.deserializer.$method(visitor)
+        }
+        )*
+    }
+}
+
+impl<'de, 's, D, S: ?Sized> Deserializer<'de> for StatefulDeserializer<'s, D, S>
+where
+    D: Deserializer<'de>,
+{
+    type Error = D::Error;
+
+    forward_deserialize! {
+        deserialize_any,
+        deserialize_bool,
+        deserialize_i8,
+        deserialize_i16,
+        deserialize_i32,
+        deserialize_i64,
+        deserialize_u8,
+        deserialize_u16,
+        deserialize_u32,
+        deserialize_u64,
+        deserialize_f32,
+        deserialize_f64,
+        deserialize_char,
+        deserialize_str,
+        deserialize_string,
+        deserialize_bytes,
+        deserialize_byte_buf,
+        deserialize_option,
+        deserialize_unit,
+        deserialize_seq,
+        deserialize_map,
+        deserialize_identifier,
+        deserialize_ignored_any
+    }
+
+    serde_if_integer128! {
+        forward_deserialize! {
+            deserialize_i128,
+            deserialize_u128
+        }
+    }
+
+    #[inline]
+    fn deserialize_unit_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserializer.deserialize_unit_struct(name, visitor)
+    }
+
+    #[inline]
+    fn deserialize_newtype_struct<V>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserializer.deserialize_newtype_struct(name, visitor)
+    }
+
+    #[inline]
+    fn deserialize_tuple<V>(self, len: usize, visitor: V) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserializer.deserialize_tuple(len, visitor)
+    }
+
+    #[inline]
+    fn deserialize_tuple_struct<V>(
+        self,
+        name: &'static str,
+        len: usize,
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserializer.deserialize_tuple_struct(name, len, visitor)
+    }
+
+    #[inline]
+    fn deserialize_struct<V>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserializer.deserialize_struct(name, fields, visitor)
+    }
+
+    #[inline]
+    fn deserialize_enum<V>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Self::Error>
+    where
+        V: Visitor<'de>,
+    {
+        self.deserializer.deserialize_enum(name, variants, visitor)
+    }
+
+    #[inline]
+    fn is_human_readable(&self) -> bool {
+        self.deserializer.is_human_readable()
+    }
+}
@@ -0,0 +1,261 @@
+// Copyright 2017 Serde Developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Seeded counterparts of the `serde_json` entry points, and seeded deserialization of
+//! `serde_json::Value`.
+//!
+//! Each function constructs a `serde_json::Deserializer`, deserializes a value with
+//! `DeserializeState` and checks that only trailing whitespace remains, exactly like
+//! `serde_json::from_str` and friends.
+//!
+//! ```
+//! extern crate serde_state as serde;
+//!
+//! use serde::de::{Deserializer, DeserializeState};
+//!
+//! struct Offset(u64);
+//!
+//! struct Shifted(u64);
+//!
+//! impl<'de> DeserializeState<'de, Offset> for Shifted {
+//!     fn deserialize_state<D>(seed: &mut Offset, deserializer: D) -> Result<Self, D::Error>
+//!     where
+//!         D: Deserializer<'de>,
+//!     {
+//!         let value: u64 = DeserializeState::deserialize_state(seed, deserializer)?;
+//!         Ok(Shifted(value + seed.0))
+//!     }
+//! }
+//!
+//! fn main() {
+//!     let mut offset = Offset(10);
+//!     let values: Vec<Shifted> = serde::json::from_str_seed(&mut offset, "[1, 2]").unwrap();
+//!     assert_eq!(values.iter().map(|s| s.0).collect::<Vec<_>>(), [11, 12]);
+//! }
+//! ```
+
+use serde_json::{self, Deserializer, Map, Number, Value};
+use std::fmt;
+use std::io;
+
+use de::{DeserializeSeed, DeserializeState, Error, MapAccess, Seed, SeqAccess, Visitor};
+
+/// Deserializes an instance of `T` from a string of JSON text using `seed`.
+pub fn from_str_seed<'a, S, T>(seed: &mut S, s: &'a str) -> serde_json::Result<T>
+where
+    S: ?Sized,
+    T: DeserializeState<'a, S>,
+{
+    from_json_seed(seed, Deserializer::from_str(s))
+}
+
+/// Deserializes an instance of `T` from bytes of JSON text using `seed`.
+pub fn from_slice_seed<'a, S, T>(seed: &mut S, v: &'a [u8]) -> serde_json::Result<T>
+where
+    S: ?Sized,
+    T: DeserializeState<'a, S>,
+{
+    from_json_seed(seed, Deserializer::from_slice(v))
+}
+
+/// Deserializes an instance of `T` from an IO stream of JSON using `seed`.
+///
+/// As with `serde_json::from_reader`, nothing can be borrowed from the input so `T` must be
+/// deserializable for any lifetime.
+pub fn from_reader_seed<R, S, T>(seed: &mut S, rdr: R) -> serde_json::Result<T>
+where
+    R: io::Read,
+    S: ?Sized,
+    T: for<'de> DeserializeState<'de, S>,
+{
+    from_json_seed(seed, Deserializer::from_reader(rdr))
+}
+
+fn from_json_seed<'de, R, S, T>(seed: &mut S, mut de: Deserializer<R>) -> serde_json::Result<T>
+where
+    R: serde_json::de::Read<'de>,
+    S: ?Sized,
+    T: DeserializeState<'de, S>,
+{
+    let value = try!(T::deserialize_state(seed, &mut de));
+    try!(de.end());
+    Ok(value)
+}
+
+/// Seeds which produce the strings of a `serde_json::Value`.
+///
+/// `Value` implements `DeserializeState` for any such seed, so dynamic documents can still make
+/// use of the seed. Every string in the document, object keys included, is handed to the seed,
+/// which can for example deduplicate or validate them.
+///
+/// ```
+/// extern crate serde_json;
+/// extern crate serde_state as serde;
+///
+/// use std::collections::BTreeSet;
+///
+/// use serde::de::DeserializeState;
+/// use serde::json::StringSeed;
+/// use serde_json::Value;
+///
+/// #[derive(Default)]
+/// struct Strings(BTreeSet<String>);
+///
+/// impl StringSeed for Strings {
+///     fn string(&mut self, value: &str) -> String {
+///         self.0.insert(value.to_owned());
+///         value.to_owned()
+///     }
+/// }
+///
+/// fn main() {
+///     let mut strings = Strings::default();
+///     let mut deserializer =
+///         serde_json::Deserializer::from_str(r#"{"name": "a", "tags": ["a", "b"], "n": 1}"#);
+///     let value = Value::deserialize_state(&mut strings, &mut deserializer).unwrap();
+///
+///     assert_eq!(value["tags"][1], "b");
+///     let found: Vec<_> = strings.0.iter().map(|s| &s[..]).collect();
+///     assert_eq!(found, ["a", "b", "n", "name", "tags"]);
+/// }
+/// ```
+pub trait StringSeed {
+    /// Returns the string to store in the `Value` for `value`
+    fn string(&mut self, value: &str) -> String;
+}
+
+impl<'de, S> DeserializeState<'de, S> for Value
+where
+    S: ?Sized + StringSeed,
+{
+    fn deserialize_state<D>(seed: &mut S, deserializer: D) -> Result<Value, D::Error>
+    where
+        D: ::de::Deserializer<'de>,
+    {
+        deserializer.deserialize_any(ValueVisitor { seed: seed })
+    }
+}
+
+struct ValueVisitor<'a, S: ?Sized + 'a> {
+    seed: &'a mut S,
+}
+
+impl<'de, 'a, S> Visitor<'de> for ValueVisitor<'a, S>
+where
+    S: ?Sized + StringSeed,
+{
+    type Value = Value;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("any valid JSON value")
+    }
+
+    #[inline]
+    fn visit_bool<E>(self, value: bool) -> Result<Value, E> {
+        Ok(Value::Bool(value))
+    }
+
+    #[inline]
+    fn visit_i64<E>(self, value: i64) -> Result<Value, E> {
+        Ok(Value::Number(value.into()))
+    }
+
+    #[inline]
+    fn visit_u64<E>(self, value: u64) -> Result<Value, E> {
+        Ok(Value::Number(value.into()))
+    }
+
+    #[inline]
+    fn visit_f64<E>(self, value: f64) -> Result<Value, E> {
+        Ok(Number::from_f64(value).map_or(Value::Null, Value::Number))
+    }
+
+    #[inline]
+    fn visit_str<E>(self, value: &str) -> Result<Value, E>
+    where
+        E: Error,
+    {
+        Ok(Value::String(self.seed.string(value)))
+    }
+
+    #[inline]
+    fn visit_none<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    #[inline]
+    fn visit_some<D>(self, deserializer: D) -> Result<Value, D::Error>
+    where
+        D: ::de::Deserializer<'de>,
+    {
+        Value::deserialize_state(self.seed, deserializer)
+    }
+
+    #[inline]
+    fn visit_unit<E>(self) -> Result<Value, E> {
+        Ok(Value::Null)
+    }
+
+    fn visit_seq<A>(self, mut access: A) -> Result<Value, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let mut values = Vec::new();
+        while let Some(value) = try!(access.next_element_seed(Seed::new(&mut *self.seed))) {
+            values.push(value);
+        }
+        Ok(Value::Array(values))
+    }
+
+    fn visit_map<A>(self, mut access: A) -> Result<Value, A::Error>
+    where
+        A: MapAccess<'de>,
+    {
+        let mut values = Map::new();
+        while let Some(key) = try!(access.next_key_seed(KeySeed(&mut *self.seed))) {
+            let value = try!(access.next_value_seed(Seed::new(&mut *self.seed)));
+            values.insert(key, value);
+        }
+        Ok(Value::Object(values))
+    }
+}
+
+// Object keys are strings as well, so they go through the seed too
+struct KeySeed<'a, S: ?Sized + 'a>(&'a mut S);
+
+impl<'de, 'a, S> DeserializeSeed<'de> for KeySeed<'a, S>
+where
+    S: ?Sized + StringSeed,
+{
+    type Value = String;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<String, D::Error>
+    where
+        D: ::de::Deserializer<'de>,
+    {
+        deserializer.deserialize_str(self)
+    }
+}
+
+impl<'de, 'a, S> Visitor<'de> for KeySeed<'a, S>
+where
+    S: ?Sized + StringSeed,
+{
+    type Value = String;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a string key")
+    }
+
+    fn visit_str<E>(self, value: &str) -> Result<String, E>
+    where
+        E: Error,
+    {
+        Ok(self.0.string(value))
+    }
+}
@@ -81,6 +81,32 @@
 //!     // does the serialization or deserialization
 //!     #[serde(serialize_state_with = "serialize_inner")]
 //!     value4: Inner,
+//!
+//!     // The `state_with` attribute specifies a module containing both a `serialize_state` and a
+//!     // `deserialize_state` function
+//!     #[serde(state_with = "inner_state")]
+//!     value5: Inner,
+//! }
+//!
+//! mod inner_state {
+//!     use std::borrow::BorrowMut;
+//!     use std::cell::Cell;
+//!     use serde::ser::Serializer;
+//!     use serde::de::{Deserializer, DeserializeState};
+//!     use Inner;
+//!
+//!     pub fn serialize_state<S>(self_: &Inner, serializer: S, seed: &Cell<i32>) -> Result<S::Ok, S::Error>
+//!         where S: Serializer
+//!     {
+//!         super::serialize_inner(self_, serializer, seed)
+//!     }
+//!
+//!     pub fn deserialize_state<'de, S, D>(seed: &mut S, deserializer: D) -> Result<Inner, D::Error>
+//!         where S: BorrowMut<i32>,
+//!               D: Deserializer<'de>
+//!     {
+//!         Inner::deserialize_state(seed, deserializer)
+//!     }
 //! }
 //!
 //! fn serialize_inner<S>(self_: &Inner, serializer: S, seed: &Cell<i32>) -> Result<S::Ok, S::Error>
@@ -96,6 +122,7 @@
 //!         value2: Inner,
 //!         value3: Inner,
 //!         value4: Inner,
+//!         value5: Inner,
 //!     };
 //!
 //!     let mut buffer = Vec::new();
@@ -103,17 +130,27 @@
 //!         let mut serializer = serde_json::Serializer::pretty(&mut buffer);
 //!         let seed = Cell::new(0);
 //!         s.serialize_state(&mut serializer, &seed).unwrap();
-//!         assert_eq!(seed.get(), 12);
+//!         assert_eq!(seed.get(), 22);
 //!     }
 //!     {
 //!         let mut deserializer = serde_json::Deserializer::from_slice(&buffer);
 //!         let mut seed = 0;
 //!         Struct::deserialize_state(&mut seed, &mut deserializer).unwrap();
-//!         assert_eq!(seed, 2);
+//!         assert_eq!(seed, 3);
 //!     }
 //! }
 //!
 //! ```
+//!
+//! The state derives also read attributes written as `#[serde_state(...)]`, which `serde_derive`
+//! ignores. A type can therefore derive both `Deserialize` and `DeserializeState` as long as the
+//! attributes only the state derives understand, such as `deserialize_state`, use that form.
+//!
+//! `#[serde(serialize_state_order = N)]` serializes a field after every field with a lower order
+//! (the default is 0), so that a field such as a checksum can be computed from the state the other
+//! fields left behind. Self-describing formats read the fields back by name, but sequence formats
+//! such as bincode drop the names and read the values back in declaration order, so a reordered
+//! field ends up in the wrong place or fails to deserialize.
 
 ////////////////////////////////////////////////////////////////////////////////
 
@@ -145,6 +182,9 @@ extern crate core;
 #[macro_use]
 extern crate serde;
 
+#[cfg(all(feature = "serde_json", feature = "std"))]
+extern crate serde_json;
+
 /// A facade around all the types we need from the `std`, `core`, `alloc`, and
 /// `collections` crates. This avoids elaborate import wrangling having to
 /// happen in every module.
@@ -163,25 +203,26 @@ mod lib {
 
     pub use self::core::cell::{Cell, RefCell};
     pub use self::core::clone::{self, Clone};
-    pub use self::core::convert::{self, From, Into};
+    pub use self::core::convert::{self, From, Into, TryFrom};
     pub use self::core::default::{self, Default};
     pub use self::core::fmt::{self, Debug, Display};
     pub use self::core::marker::{self, PhantomData};
+    pub use self::core::num::{NonZeroU32, Wrapping};
     pub use self::core::option::{self, Option};
     pub use self::core::result::{self, Result};
 
     #[cfg(all(feature = "alloc", not(feature = "std")))]
-    pub use collections::borrow::{Cow, ToOwned};
+    pub use alloc::borrow::{Borrow, Cow, ToOwned};
     #[cfg(feature = "std")]
-    pub use std::borrow::{Cow, ToOwned};
+    pub use std::borrow::{Borrow, Cow, ToOwned};
 
     #[cfg(all(feature = "alloc", not(feature = "std")))]
-    pub use collections::string::{String, ToString};
+    pub use alloc::string::{String, ToString};
     #[cfg(feature = "std")]
     pub use std::string::String;
 
     #[cfg(all(feature = "alloc", not(feature = "std")))]
-    pub use collections::vec::Vec;
+    pub use alloc::vec::Vec;
     #[cfg(feature = "std")]
     pub use std::vec::Vec;
 
@@ -196,12 +237,12 @@ mod lib {
     pub use std::rc::Rc;
 
     #[cfg(all(feature = "rc", feature = "alloc", not(feature = "std")))]
-    pub use alloc::arc::Arc;
+    pub use alloc::sync::Arc;
     #[cfg(all(feature = "rc", feature = "std"))]
     pub use std::sync::Arc;
 
     #[cfg(all(feature = "alloc", not(feature = "std")))]
-    pub use collections::{BTreeMap, BTreeSet, BinaryHeap, LinkedList, VecDeque};
+    pub use alloc::collections::{BTreeMap, BTreeSet, BinaryHeap, LinkedList, VecDeque};
     #[cfg(feature = "std")]
     pub use std::collections::{BTreeMap, BTreeSet, BinaryHeap, LinkedList, VecDeque};
 
@@ -221,7 +262,12 @@ mod lib {
     #[cfg(feature = "std")]
     pub use std::sync::{Mutex, RwLock};
     #[cfg(feature = "std")]
-    pub use std::time::Duration;
+    pub use std::time::{Duration, SystemTime};
+
+    #[cfg(feature = "unstable")]
+    pub use self::core::num::{NonZeroI16, NonZeroI32, NonZeroI64, NonZeroI8, NonZeroIsize};
+    #[cfg(feature = "unstable")]
+    pub use self::core::num::{NonZeroU16, NonZeroU64, NonZeroU8, NonZeroUsize};
 }
 
 ////////////////////////////////////////////////////////////////////////////////
@@ -229,6 +275,12 @@ mod lib {
 pub mod de;
 pub mod ser;
 
+#[cfg(all(feature = "rc", any(feature = "std", feature = "alloc")))]
+pub mod shared;
+
+#[cfg(all(feature = "serde_json", feature = "std"))]
+pub mod json;
+
 #[doc(hidden)]
 pub mod private;
 
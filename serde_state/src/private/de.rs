@@ -8,7 +8,7 @@
 
 use lib::*;
 
-use de::{DeserializeSeed, Deserializer, IntoDeserializer, Error, Visitor};
+use de::{DeserializeSeed, DeserializeState, Deserializer, IntoDeserializer, Error, Visitor};
 
 use serde::Deserialize;
 
@@ -16,7 +16,7 @@ use serde::Deserialize;
 use de::Unexpected;
 
 #[cfg(any(feature = "std", feature = "alloc"))]
-pub use self::content::{Content, ContentRefDeserializer, ContentDeserializer,
+pub use self::content::{BufferedContent, Content, ContentRefDeserializer, ContentDeserializer,
                         TaggedContentVisitor, TagOrContentField, TagOrContentFieldVisitor,
                         TagContentOtherField, TagContentOtherFieldVisitor,
                         InternallyTaggedUnitVisitor, UntaggedUnitVisitor};
@@ -41,6 +41,36 @@ where
     }
 }
 
+/// A DeserializeSeed helper for implementing deserialize_state_in_place Visitors.
+///
+/// Wraps a seed and a mutable reference and calls deserialize_state_in_place on it.
+pub struct InPlaceStateSeed<'a, 'seed, S: ?Sized + 'seed, T: 'a> {
+    seed: &'seed mut S,
+    place: &'a mut T,
+}
+
+impl<'a, 'seed, S: ?Sized, T> InPlaceStateSeed<'a, 'seed, S, T> {
+    pub fn new(seed: &'seed mut S, place: &'a mut T) -> Self {
+        InPlaceStateSeed {
+            seed: seed,
+            place: place,
+        }
+    }
+}
+
+impl<'a, 'seed, 'de, S: ?Sized, T> DeserializeSeed<'de> for InPlaceStateSeed<'a, 'seed, S, T>
+where
+    T: DeserializeState<'de, S>,
+{
+    type Value = ();
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        T::deserialize_state_in_place(self.seed, deserializer, self.place)
+    }
+}
+
 /// If the missing field is of type `Option<T>` then treat is as `None`,
 /// otherwise it is an error.
 pub fn missing_field<'de, V, E>(seed: V, field: &'static str) -> Result<V::Value, E>
@@ -81,6 +111,15 @@ where
     seed.deserialize(deserializer)
 }
 
+/// Prefixes the message of an error raised while deserializing a seeded field
+/// with the name of that field.
+pub fn field_error<E>(err: E, field: &'static str) -> E
+where
+    E: Error,
+{
+    E::custom(format_args!("field `{}`: {}", field, err))
+}
+
 #[cfg(any(feature = "std", feature = "alloc"))]
 pub fn borrow_cow_str<'de: 'a, 'a, D>(deserializer: D) -> Result<Cow<'a, str>, D::Error>
 where
@@ -220,8 +259,11 @@ pub mod size_hint {
         helper(iter.size_hint())
     }
 
+    /// The largest capacity preallocated from a size hint given by the input
+    pub const CAUTIOUS_LIMIT: usize = 4096;
+
     pub fn cautious(hint: Option<usize>) -> usize {
-        cmp::min(hint.unwrap_or(0), 4096)
+        cmp::min(hint.unwrap_or(0), CAUTIOUS_LIMIT)
     }
 
     fn helper(bounds: (usize, Option<usize>)) -> Option<usize> {
@@ -991,6 +1033,7 @@ mod content {
     /// Not public API
     pub struct ContentDeserializer<'de, E> {
         content: Content<'de>,
+        human_readable: bool,
         err: PhantomData<E>,
     }
 
@@ -1006,6 +1049,8 @@ mod content {
         where
             V: Visitor<'de>,
         {
+            let human_readable = self.human_readable;
+            let new = |content| ContentDeserializer::with_human_readable(content, human_readable);
             match self.content {
                 Content::Bool(v) => visitor.visit_bool(v),
                 Content::U8(v) => visitor.visit_u8(v),
@@ -1025,20 +1070,17 @@ mod content {
                 Content::Bytes(v) => visitor.visit_borrowed_bytes(v),
                 Content::Unit => visitor.visit_unit(),
                 Content::None => visitor.visit_none(),
-                Content::Some(v) => visitor.visit_some(ContentDeserializer::new(*v)),
-                Content::Newtype(v) => visitor.visit_newtype_struct(ContentDeserializer::new(*v)),
+                Content::Some(v) => visitor.visit_some(new(*v)),
+                Content::Newtype(v) => visitor.visit_newtype_struct(new(*v)),
                 Content::Seq(v) => {
-                    let seq = v.into_iter().map(ContentDeserializer::new);
+                    let seq = v.into_iter().map(new);
                     let mut seq_visitor = de::value::SeqDeserializer::new(seq);
                     let value = try!(visitor.visit_seq(&mut seq_visitor));
                     try!(seq_visitor.end());
                     Ok(value)
                 }
                 Content::Map(v) => {
-                    let map = v.into_iter().map(|(k, v)| {
-                                                    (ContentDeserializer::new(k),
-                                                    ContentDeserializer::new(v))
-                                                });
+                    let map = v.into_iter().map(|(k, v)| (new(k), new(v)));
                     let mut map_visitor = de::value::MapDeserializer::new(map);
                     let value = try!(visitor.visit_map(&mut map_visitor));
                     try!(map_visitor.end());
@@ -1053,7 +1095,9 @@ mod content {
         {
             match self.content {
                 Content::None => visitor.visit_none(),
-                Content::Some(v) => visitor.visit_some(ContentDeserializer::new(*v)),
+                Content::Some(v) => {
+                    visitor.visit_some(ContentDeserializer::with_human_readable(*v, self.human_readable))
+                }
                 Content::Unit => visitor.visit_unit(),
                 _ => visitor.visit_some(self),
             }
@@ -1114,11 +1158,16 @@ mod content {
                 EnumDeserializer {
                     variant: variant,
                     value: value,
+                    human_readable: self.human_readable,
                     err: PhantomData,
                 },
             )
         }
 
+        fn is_human_readable(&self) -> bool {
+            self.human_readable
+        }
+
         forward_to_deserialize_any! {
             bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes
             byte_buf unit unit_struct seq tuple tuple_struct map struct
@@ -1129,19 +1178,58 @@ mod content {
     impl<'de, E> ContentDeserializer<'de, E> {
         /// private API, don't use
         pub fn new(content: Content<'de>) -> Self {
+            ContentDeserializer::with_human_readable(content, true)
+        }
+
+        /// private API, don't use
+        pub fn with_human_readable(content: Content<'de>, human_readable: bool) -> Self {
             ContentDeserializer {
                 content: content,
+                human_readable: human_readable,
                 err: PhantomData,
             }
         }
     }
 
+    /// Not public API.
+    ///
+    /// Content buffered by a derived `DeserializeState` impl, which remembers
+    /// whether the deserializer it came from is human readable so that the
+    /// content is replayed the same way.
+    pub struct BufferedContent<'de> {
+        content: Content<'de>,
+        human_readable: bool,
+    }
+
+    impl<'de> Deserialize<'de> for BufferedContent<'de> {
+        fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+        where
+            D: Deserializer<'de>,
+        {
+            let human_readable = deserializer.is_human_readable();
+            Content::deserialize(deserializer).map(|content| {
+                BufferedContent {
+                    content: content,
+                    human_readable: human_readable,
+                }
+            })
+        }
+    }
+
+    impl<'de> BufferedContent<'de> {
+        /// private API, don't use
+        pub fn into_deserializer<E>(self) -> ContentDeserializer<'de, E> {
+            ContentDeserializer::with_human_readable(self.content, self.human_readable)
+        }
+    }
+
     struct EnumDeserializer<'de, E>
     where
         E: de::Error,
     {
         variant: Content<'de>,
         value: Option<Content<'de>>,
+        human_readable: bool,
         err: PhantomData<E>,
     }
 
@@ -1158,9 +1246,10 @@ mod content {
         {
             let visitor = VariantDeserializer {
                 value: self.value,
+                human_readable: self.human_readable,
                 err: PhantomData,
             };
-            seed.deserialize(ContentDeserializer::new(self.variant))
+            seed.deserialize(ContentDeserializer::with_human_readable(self.variant, self.human_readable))
                 .map(|v| (v, visitor))
         }
     }
@@ -1170,6 +1259,7 @@ mod content {
         E: de::Error,
     {
         value: Option<Content<'de>>,
+        human_readable: bool,
         err: PhantomData<E>,
     }
 
@@ -1181,7 +1271,9 @@ mod content {
 
         fn unit_variant(self) -> Result<(), E> {
             match self.value {
-                Some(value) => de::Deserialize::deserialize(ContentDeserializer::new(value)),
+                Some(value) => {
+                    de::Deserialize::deserialize(ContentDeserializer::with_human_readable(value, self.human_readable))
+                }
                 None => Ok(()),
             }
         }
@@ -1191,7 +1283,9 @@ mod content {
             T: de::DeserializeSeed<'de>,
         {
             match self.value {
-                Some(value) => seed.deserialize(ContentDeserializer::new(value)),
+                Some(value) => {
+                    seed.deserialize(ContentDeserializer::with_human_readable(value, self.human_readable))
+                }
                 None => {
                     Err(de::Error::invalid_type(de::Unexpected::UnitVariant, &"newtype variant"),)
                 }
@@ -1204,7 +1298,7 @@ mod content {
         {
             match self.value {
                 Some(Content::Seq(v)) => {
-                    de::Deserializer::deserialize_any(SeqDeserializer::new(v), visitor)
+                    de::Deserializer::deserialize_any(SeqDeserializer::new(v, self.human_readable), visitor)
                 }
                 Some(other) => Err(de::Error::invalid_type(other.unexpected(), &"tuple variant"),),
                 None => Err(de::Error::invalid_type(de::Unexpected::UnitVariant, &"tuple variant"),),
@@ -1221,7 +1315,7 @@ mod content {
         {
             match self.value {
                 Some(Content::Map(v)) => {
-                    de::Deserializer::deserialize_any(MapDeserializer::new(v), visitor)
+                    de::Deserializer::deserialize_any(MapDeserializer::new(v, self.human_readable), visitor)
                 }
                 Some(other) => Err(de::Error::invalid_type(other.unexpected(), &"struct variant"),),
                 _ => Err(de::Error::invalid_type(de::Unexpected::UnitVariant, &"struct variant"),),
@@ -1234,6 +1328,7 @@ mod content {
         E: de::Error,
     {
         iter: <Vec<Content<'de>> as IntoIterator>::IntoIter,
+        human_readable: bool,
         err: PhantomData<E>,
     }
 
@@ -1241,9 +1336,10 @@ mod content {
     where
         E: de::Error,
     {
-        fn new(vec: Vec<Content<'de>>) -> Self {
+        fn new(vec: Vec<Content<'de>>, human_readable: bool) -> Self {
             SeqDeserializer {
                 iter: vec.into_iter(),
+                human_readable: human_readable,
                 err: PhantomData,
             }
         }
@@ -1274,6 +1370,10 @@ mod content {
             }
         }
 
+        fn is_human_readable(&self) -> bool {
+            self.human_readable
+        }
+
         forward_to_deserialize_any! {
             bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes
             byte_buf option unit unit_struct newtype_struct seq tuple
@@ -1293,7 +1393,7 @@ mod content {
         {
             match self.iter.next() {
                 Some(value) => {
-                    seed.deserialize(ContentDeserializer::new(value))
+                    seed.deserialize(ContentDeserializer::with_human_readable(value, self.human_readable))
                         .map(Some)
                 }
                 None => Ok(None),
@@ -1311,6 +1411,7 @@ mod content {
     {
         iter: <Vec<(Content<'de>, Content<'de>)> as IntoIterator>::IntoIter,
         value: Option<Content<'de>>,
+        human_readable: bool,
         err: PhantomData<E>,
     }
 
@@ -1318,10 +1419,11 @@ mod content {
     where
         E: de::Error,
     {
-        fn new(map: Vec<(Content<'de>, Content<'de>)>) -> Self {
+        fn new(map: Vec<(Content<'de>, Content<'de>)>, human_readable: bool) -> Self {
             MapDeserializer {
                 iter: map.into_iter(),
                 value: None,
+                human_readable: human_readable,
                 err: PhantomData,
             }
         }
@@ -1340,7 +1442,8 @@ mod content {
             match self.iter.next() {
                 Some((key, value)) => {
                     self.value = Some(value);
-                    seed.deserialize(ContentDeserializer::new(key)).map(Some)
+                    seed.deserialize(ContentDeserializer::with_human_readable(key, self.human_readable))
+                        .map(Some)
                 }
                 None => Ok(None),
             }
@@ -1351,7 +1454,9 @@ mod content {
             T: de::DeserializeSeed<'de>,
         {
             match self.value.take() {
-                Some(value) => seed.deserialize(ContentDeserializer::new(value)),
+                Some(value) => {
+                    seed.deserialize(ContentDeserializer::with_human_readable(value, self.human_readable))
+                }
                 None => Err(de::Error::custom("value is missing")),
             }
         }
@@ -1375,6 +1480,10 @@ mod content {
             visitor.visit_map(self)
         }
 
+        fn is_human_readable(&self) -> bool {
+            self.human_readable
+        }
+
         forward_to_deserialize_any! {
             bool i8 i16 i32 i64 u8 u16 u32 u64 f32 f64 char str string bytes
             byte_buf option unit unit_struct newtype_struct seq tuple
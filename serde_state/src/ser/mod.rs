@@ -8,8 +8,10 @@
 
 //! Generic data structure serialization framework.
 
+use lib::*;
+
 mod seed_impls;
-pub use self::seed_impls::{Seeded, Unseeded};
+pub use self::seed_impls::{MapKeySeed, Seeded, Unseeded};
 
 pub use serde::ser::*;
 /// Stateful variant of serdeäs `Serialize` trait
@@ -19,3 +21,56 @@ pub trait SerializeState<State: ?Sized> {
     where
         S: Serializer;
 }
+
+/// Serializes `time` as the `Duration` which has elapsed since the epoch held by `seed`.
+///
+/// Meant for `#[serde(serialize_state_with = "...")]` and paired with
+/// `de::deserialize_relative_time`. Times earlier than the epoch can not be represented and
+/// produce an error.
+///
+/// ```
+/// extern crate serde_json;
+/// extern crate serde_state as serde;
+/// #[macro_use]
+/// extern crate serde_derive_state;
+///
+/// use std::time::{Duration, SystemTime, UNIX_EPOCH};
+/// use serde::de::DeserializeState;
+/// use serde::ser::Seeded;
+///
+/// #[derive(SerializeState, DeserializeState, Debug, PartialEq)]
+/// #[serde(serialize_state = "SystemTime", deserialize_state = "SystemTime")]
+/// struct Event {
+///     #[serde(serialize_state_with = "serde::ser::serialize_relative_time")]
+///     #[serde(deserialize_state_with = "serde::de::deserialize_relative_time")]
+///     at: SystemTime,
+/// }
+///
+/// fn main() {
+///     let mut epoch = UNIX_EPOCH + Duration::from_secs(1_000_000);
+///     let event = Event { at: epoch + Duration::from_secs(5) };
+///
+///     let json = serde_json::to_string(&Seeded::new(&epoch, &event)).unwrap();
+///     assert_eq!(json, r#"{"at":{"secs":5,"nanos":0}}"#);
+///
+///     let mut deserializer = serde_json::Deserializer::from_str(&json);
+///     let event_again = Event::deserialize_state(&mut epoch, &mut deserializer).unwrap();
+///     assert_eq!(event_again, event);
+/// }
+/// ```
+#[cfg(feature = "std")]
+pub fn serialize_relative_time<S, T>(
+    time: &SystemTime,
+    serializer: S,
+    seed: &T,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    T: ?Sized + Borrow<SystemTime>,
+{
+    let epoch: &SystemTime = Borrow::borrow(seed);
+    match time.duration_since(*epoch) {
+        Ok(elapsed) => elapsed.serialize(serializer),
+        Err(_) => Err(Error::custom("time is earlier than the epoch of the seed")),
+    }
+}
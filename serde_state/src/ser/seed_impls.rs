@@ -8,7 +8,7 @@
 
 use lib::*;
 
-use ser::{Serialize, SerializeTuple, Serializer, SerializeState};
+use ser::{Serialize, SerializeMap, SerializeTuple, Serializer, SerializeState};
 
 #[cfg(feature = "std")]
 use ser::Error;
@@ -45,7 +45,16 @@ serialize_impl! {
     f32,
     f64,
     (),
-    bool
+    bool,
+    char,
+    str
+}
+
+serde_if_integer128! {
+    serialize_impl! {
+        i128,
+        u128
+    }
 }
 
 #[cfg(any(feature = "std", feature = "alloc"))]
@@ -53,6 +62,48 @@ serialize_impl!{
     String
 }
 
+#[cfg(feature = "std")]
+serialize_impl! {
+    Duration,
+    SystemTime
+}
+
+#[cfg(feature = "std")]
+serialize_impl! {
+    net::IpAddr,
+    net::Ipv4Addr,
+    net::Ipv6Addr,
+    net::SocketAddr,
+    net::SocketAddrV4,
+    net::SocketAddrV6
+}
+
+#[cfg(feature = "std")]
+serialize_impl! {
+    Path,
+    PathBuf
+}
+
+#[cfg(all(feature = "std", any(unix, windows)))]
+serialize_impl! {
+    OsStr,
+    OsString
+}
+
+#[cfg(feature = "unstable")]
+serialize_impl! {
+    NonZeroU8,
+    NonZeroU16,
+    NonZeroU32,
+    NonZeroU64,
+    NonZeroUsize,
+    NonZeroI8,
+    NonZeroI16,
+    NonZeroI32,
+    NonZeroI64,
+    NonZeroIsize
+}
+
 impl<T, Seed: ?Sized> SerializeState<Seed> for Option<T>
 where
     T: SerializeState<Seed>,
@@ -69,6 +120,16 @@ where
     }
 }
 
+impl<T: ?Sized, Seed: ?Sized> SerializeState<Seed> for PhantomData<T> {
+    #[inline]
+    fn serialize_state<S>(&self, serializer: S, _seed: &Seed) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.serialize(serializer)
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 
 macro_rules! array_impls {
@@ -117,7 +178,7 @@ where
 #[cfg(any(feature = "std", feature = "alloc"))]
 macro_rules! seq_impl {
     ($ty:ident < T $(: $tbound1:ident $(+ $tbound2:ident)*)* $(, $typaram:ident : $bound:ident)* >) => {
-        impl<T, Seed $(, $typaram)*> SerializeState<Seed> for $ty<T $(, $typaram)*>
+        impl<T, Seed: ?Sized $(, $typaram)*> SerializeState<Seed> for $ty<T $(, $typaram)*>
         where
             T: SerializeState<Seed> $(+ $tbound1 $(+ $tbound2)*)*,
             $($typaram: $bound,)*
@@ -170,6 +231,23 @@ where
     }
 }
 
+#[cfg(feature = "std")]
+impl<Idx, Seed> SerializeState<Seed> for ops::RangeInclusive<Idx>
+where
+    Idx: SerializeState<Seed>,
+{
+    fn serialize_state<S>(&self, serializer: S, seed: &Seed) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        use super::SerializeStruct;
+        let mut state = try!(serializer.serialize_struct("RangeInclusive", 2));
+        try!(state.serialize_field("start", &Seeded::new(seed, self.start())));
+        try!(state.serialize_field("end", &Seeded::new(seed, self.end())));
+        state.end()
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 
 macro_rules! tuple_impls {
@@ -220,9 +298,9 @@ tuple_impls! {
 #[cfg(any(feature = "std", feature = "alloc"))]
 macro_rules! map_impl {
     ($ty:ident < K $(: $kbound1:ident $(+ $kbound2:ident)*)*, V $(, $typaram:ident : $bound:ident)* >) => {
-        impl<K, V, Seed $(, $typaram)*> SerializeState<Seed> for $ty<K, V $(, $typaram)*>
+        impl<K, V, Seed: ?Sized $(, $typaram)*> SerializeState<Seed> for $ty<K, V $(, $typaram)*>
         where
-            K: SerializeState<Seed> $(+ $kbound1 $(+ $kbound2)*)*,
+            K: Serialize $(+ $kbound1 $(+ $kbound2)*)*,
             V: SerializeState<Seed>,
             $($typaram: $bound,)*
         {
@@ -231,7 +309,12 @@ macro_rules! map_impl {
             where
                 S: Serializer,
             {
-                serializer.collect_map(self.into_iter().map(|(k, v)| (Seeded::new(seed, k), Seeded::new(seed, v))))
+                let mut map = try!(serializer.serialize_map(Some(self.len())));
+                for (k, v) in self {
+                    try!(map.serialize_key(k));
+                    try!(map.serialize_value(&Seeded::new(seed, v)));
+                }
+                map.end()
             }
         }
     }
@@ -335,9 +418,22 @@ where
     }
 }
 
+impl<T, Seed: ?Sized> SerializeState<Seed> for Wrapping<T>
+where
+    T: SerializeState<Seed>,
+{
+    #[inline]
+    fn serialize_state<S>(&self, serializer: S, seed: &Seed) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        self.0.serialize_state(serializer, seed)
+    }
+}
+
 ////////////////////////////////////////////////////////////////////////////////
 
-impl<T, E, Seed> SerializeState<Seed> for Result<T, E>
+impl<T, E, Seed: ?Sized> SerializeState<Seed> for Result<T, E>
 where
     T: SerializeState<Seed>,
     E: SerializeState<Seed>,
@@ -403,3 +499,25 @@ where
         self.0.serialize(serializer)
     }
 }
+
+/// Serializes a map whose keys need the seed but whose values are plain `Serialize` types.
+///
+/// Maps implement `SerializeState` themselves for plain `Serialize` keys and seeded values.
+/// `MapKeySeed` covers the opposite case, for instance interned symbols which are resolved to
+/// strings through the seed before being emitted.
+pub struct MapKeySeed<'a, M: ?Sized + 'a>(pub &'a M);
+
+impl<'a, M: ?Sized, K: 'a, V: 'a, Seed: ?Sized> SerializeState<Seed> for MapKeySeed<'a, M>
+where
+    &'a M: IntoIterator<Item = (&'a K, &'a V)>,
+    K: SerializeState<Seed>,
+    V: Serialize,
+{
+    #[inline]
+    fn serialize_state<S>(&self, serializer: S, seed: &Seed) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serializer.collect_map(self.0.into_iter().map(|(k, v)| (Seeded::new(seed, k), v)))
+    }
+}
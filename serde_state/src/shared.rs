@@ -0,0 +1,410 @@
+// Copyright 2017 Serde Developers
+//
+// Licensed under the Apache License, Version 2.0 <LICENSE-APACHE or
+// http://www.apache.org/licenses/LICENSE-2.0> or the MIT license
+// <LICENSE-MIT or http://opensource.org/licenses/MIT>, at your
+// option. This file may not be copied, modified, or distributed
+// except according to those terms.
+
+//! Helpers for (de)serializing graphs of `Rc` and `Arc` pointers without duplicating the values
+//! which are shared.
+//!
+//! A shared pointer is represented as an enum named `Shared` with three variants:
+//!
+//! * `Plain(value)` - the pointer is not shared, so no id is needed
+//! * `Marked(id, value)` - the first occurrence of a shared pointer
+//! * `Reference(id)` - a later occurrence of a pointer which was already `Marked`
+//!
+//! `serialize_shared` and `deserialize_shared` read and write that representation and can be used
+//! directly with `#[serde(serialize_state_with = "...")]` and
+//! `#[serde(deserialize_state_with = "...")]`.
+
+use lib::*;
+
+use de::{
+    self, Deserialize, DeserializeSeed, DeserializeState, Deserializer, EnumAccess, OptionSeed,
+    SeqAccess, StateContext, Unexpected, VariantAccess, Visitor,
+};
+use ser::{self, Serialize, SerializeState, SerializeTupleVariant, Seeded, Serializer};
+
+/// Identifier assigned to each shared value
+pub type Id = u32;
+
+const VARIANTS: &'static [&'static str] = &["Plain", "Marked", "Reference"];
+
+/// Pointer types whose pointee may be shared between multiple (de)serialized values
+pub trait SharedPointer: Clone + ops::Deref {
+    /// Allocates a new pointer to `value`
+    fn new(value: Self::Target) -> Self
+    where
+        Self::Target: Sized;
+
+    /// Returns true if other pointers to the same value exist
+    fn is_shared(&self) -> bool;
+}
+
+impl<T: ?Sized> SharedPointer for Rc<T> {
+    fn new(value: T) -> Self
+    where
+        T: Sized,
+    {
+        Rc::new(value)
+    }
+
+    fn is_shared(&self) -> bool {
+        Rc::strong_count(self) > 1
+    }
+}
+
+impl<T: ?Sized> SharedPointer for Arc<T> {
+    fn new(value: T) -> Self
+    where
+        T: Sized,
+    {
+        Arc::new(value)
+    }
+
+    fn is_shared(&self) -> bool {
+        Arc::strong_count(self) > 1
+    }
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Deserialization seed which maps the ids of `Marked` values to the pointers created for them
+#[derive(Clone, Debug)]
+pub struct SharedSeed<P> {
+    map: BTreeMap<Id, P>,
+}
+
+impl<P> Default for SharedSeed<P> {
+    fn default() -> Self {
+        SharedSeed::new()
+    }
+}
+
+impl<P> SharedSeed<P> {
+    /// Creates an empty `SharedSeed`
+    pub fn new() -> SharedSeed<P> {
+        SharedSeed {
+            map: BTreeMap::new(),
+        }
+    }
+
+    /// Associates `value` with `id`, returning the value previously associated with it
+    pub fn insert(&mut self, id: Id, value: P) -> Option<P> {
+        self.map.insert(id, value)
+    }
+
+    /// Returns the value associated with `id`
+    pub fn get(&self, id: Id) -> Option<&P> {
+        self.map.get(&id)
+    }
+}
+
+impl<P> StateContext for SharedSeed<P> {
+    type Error = SharedError;
+}
+
+/// Errors for ids which do not match up with the values marked so far
+#[derive(Clone, Debug, PartialEq)]
+pub enum SharedError {
+    /// A `Reference` to an id which no value has been `Marked` with
+    MissingId(Id),
+    /// A `Marked` value with an id which an earlier value was already marked with
+    DuplicateId(Id),
+}
+
+impl Display for SharedError {
+    fn fmt(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            SharedError::MissingId(id) => write!(formatter, "missing shared value with id {}", id),
+            SharedError::DuplicateId(id) => {
+                write!(formatter, "duplicate shared value with id {}", id)
+            }
+        }
+    }
+}
+
+impl<P> AsMut<SharedSeed<P>> for SharedSeed<P> {
+    fn as_mut(&mut self) -> &mut SharedSeed<P> {
+        self
+    }
+}
+
+enum Variant {
+    Plain,
+    Marked,
+    Reference,
+}
+
+impl<'de> Deserialize<'de> for Variant {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct VariantVisitor;
+
+        impl<'de> Visitor<'de> for VariantVisitor {
+            type Value = Variant;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("`Plain`, `Marked` or `Reference`")
+            }
+
+            fn visit_u64<E>(self, value: u64) -> Result<Variant, E>
+            where
+                E: de::Error,
+            {
+                match value {
+                    0 => Ok(Variant::Plain),
+                    1 => Ok(Variant::Marked),
+                    2 => Ok(Variant::Reference),
+                    _ => Err(E::invalid_value(Unexpected::Unsigned(value), &self)),
+                }
+            }
+
+            fn visit_str<E>(self, value: &str) -> Result<Variant, E>
+            where
+                E: de::Error,
+            {
+                match value {
+                    "Plain" => Ok(Variant::Plain),
+                    "Marked" => Ok(Variant::Marked),
+                    "Reference" => Ok(Variant::Reference),
+                    _ => Err(E::unknown_variant(value, VARIANTS)),
+                }
+            }
+        }
+
+        deserializer.deserialize_identifier(VariantVisitor)
+    }
+}
+
+struct SharedVisitor<'seed, S: 'seed, P> {
+    seed: &'seed mut S,
+    _marker: PhantomData<P>,
+}
+
+impl<'de, 'seed, S, P> Visitor<'de> for SharedVisitor<'seed, S, P>
+where
+    S: AsMut<SharedSeed<P>>,
+    P: SharedPointer,
+    P::Target: DeserializeState<'de, S>,
+{
+    type Value = P;
+
+    fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+        formatter.write_str("a shared value")
+    }
+
+    fn visit_enum<A>(self, data: A) -> Result<P, A::Error>
+    where
+        A: EnumAccess<'de>,
+    {
+        match try!(data.variant()) {
+            (Variant::Plain, variant) => {
+                let value = try!(variant.newtype_variant_seed(de::Seed::<_, P::Target>::new(
+                    &mut *self.seed
+                )));
+                Ok(P::new(value))
+            }
+            (Variant::Marked, variant) => variant.tuple_variant(2, self),
+            (Variant::Reference, variant) => {
+                let id = try!(variant.newtype_variant::<Id>());
+                match self.seed.as_mut().get(id) {
+                    Some(value) => Ok(value.clone()),
+                    None => {
+                        let error = SharedError::MissingId(id);
+                        Err(de::state_error::<SharedSeed<P>, _>(error))
+                    }
+                }
+            }
+        }
+    }
+
+    // The contents of `Marked(id, value)`
+    fn visit_seq<A>(self, mut seq: A) -> Result<P, A::Error>
+    where
+        A: SeqAccess<'de>,
+    {
+        let id: Id = match try!(seq.next_element()) {
+            Some(id) => id,
+            None => return Err(de::Error::invalid_length(0, &self)),
+        };
+        if self.seed.as_mut().get(id).is_some() {
+            return Err(de::state_error::<SharedSeed<P>, _>(
+                SharedError::DuplicateId(id),
+            ));
+        }
+        let value = match try!(seq.next_element_seed(de::Seed::<_, P::Target>::new(
+            &mut *self.seed
+        ))) {
+            Some(value) => P::new(value),
+            None => return Err(de::Error::invalid_length(1, &self)),
+        };
+        self.seed.as_mut().insert(id, value.clone());
+        Ok(value)
+    }
+}
+
+/// Deserializes a shared pointer which was serialized by `serialize_shared`, reusing the pointer
+/// created for a `Marked` value whenever a `Reference` to it is encountered
+pub fn deserialize_shared<'de, S, P, D>(seed: &mut S, deserializer: D) -> Result<P, D::Error>
+where
+    S: AsMut<SharedSeed<P>>,
+    P: SharedPointer,
+    P::Target: DeserializeState<'de, S>,
+    D: Deserializer<'de>,
+{
+    deserializer.deserialize_enum(
+        "Shared",
+        VARIANTS,
+        SharedVisitor {
+            seed: seed,
+            _marker: PhantomData,
+        },
+    )
+}
+
+struct DeserializeShared<'seed, S: 'seed, P> {
+    seed: &'seed mut S,
+    _marker: PhantomData<P>,
+}
+
+impl<'de, 'seed, S, P> DeserializeSeed<'de> for DeserializeShared<'seed, S, P>
+where
+    S: AsMut<SharedSeed<P>>,
+    P: SharedPointer,
+    P::Target: DeserializeState<'de, S>,
+{
+    type Value = P;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<P, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserialize_shared(self.seed, deserializer)
+    }
+}
+
+/// Deserializes an optional shared pointer which was serialized by `serialize_option_shared`
+pub fn deserialize_option_shared<'de, S, P, D>(
+    seed: &mut S,
+    deserializer: D,
+) -> Result<Option<P>, D::Error>
+where
+    S: AsMut<SharedSeed<P>>,
+    P: SharedPointer,
+    P::Target: DeserializeState<'de, S>,
+    D: Deserializer<'de>,
+{
+    OptionSeed(DeserializeShared {
+        seed: seed,
+        _marker: PhantomData,
+    })
+    .deserialize(deserializer)
+}
+
+////////////////////////////////////////////////////////////////////////////////
+
+/// Serialization seed which assigns ids to shared pointers the first time they are serialized
+#[derive(Debug, Default)]
+pub struct SerSharedSeed {
+    ids: RefCell<BTreeMap<usize, Id>>,
+}
+
+impl SerSharedSeed {
+    /// Creates an empty `SerSharedSeed`
+    pub fn new() -> SerSharedSeed {
+        SerSharedSeed::default()
+    }
+
+    /// Returns the id of the value at `ptr` and whether that value has been seen before
+    fn lookup_or_insert<T: ?Sized, E>(&self, ptr: *const T) -> Result<(Id, bool), E>
+    where
+        E: ser::Error,
+    {
+        let mut ids = self.ids.borrow_mut();
+        let key = ptr as *const () as usize;
+        if let Some(id) = ids.get(&key) {
+            return Ok((*id, true));
+        }
+        let id = match Id::try_from(ids.len()) {
+            Ok(id) => id,
+            Err(_) => return Err(E::custom("too many shared values to assign each an id")),
+        };
+        ids.insert(key, id);
+        Ok((id, false))
+    }
+}
+
+impl AsRef<SerSharedSeed> for SerSharedSeed {
+    fn as_ref(&self) -> &SerSharedSeed {
+        self
+    }
+}
+
+/// Serializes a shared pointer as `Plain` if it is the only pointer to its value, as `Marked` the
+/// first time a shared value is encountered and as a `Reference` to that id thereafter
+pub fn serialize_shared<S, Seed, P>(value: &P, serializer: S, seed: &Seed) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    Seed: ?Sized + AsRef<SerSharedSeed>,
+    P: SharedPointer,
+    P::Target: SerializeState<Seed>,
+{
+    if !value.is_shared() {
+        let value = Seeded::new(seed, &**value);
+        return serializer.serialize_newtype_variant("Shared", 0, "Plain", &value);
+    }
+    match try!(seed.as_ref().lookup_or_insert(&**value)) {
+        (id, true) => serializer.serialize_newtype_variant("Shared", 2, "Reference", &id),
+        (id, false) => {
+            let mut variant = try!(serializer.serialize_tuple_variant("Shared", 1, "Marked", 2));
+            try!(variant.serialize_field(&id));
+            try!(variant.serialize_field(&Seeded::new(seed, &**value)));
+            variant.end()
+        }
+    }
+}
+
+struct SerializeShared<'a, Seed: ?Sized + 'a, P: 'a> {
+    value: &'a P,
+    seed: &'a Seed,
+}
+
+impl<'a, Seed: ?Sized, P> Serialize for SerializeShared<'a, Seed, P>
+where
+    Seed: AsRef<SerSharedSeed>,
+    P: SharedPointer,
+    P::Target: SerializeState<Seed>,
+{
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        serialize_shared(self.value, serializer, self.seed)
+    }
+}
+
+/// Serializes an optional shared pointer, using `serialize_shared` for the `Some` case
+pub fn serialize_option_shared<S, Seed, P>(
+    value: &Option<P>,
+    serializer: S,
+    seed: &Seed,
+) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+    Seed: ?Sized + AsRef<SerSharedSeed>,
+    P: SharedPointer,
+    P::Target: SerializeState<Seed>,
+{
+    match *value {
+        Some(ref value) => serializer.serialize_some(&SerializeShared {
+            value: value,
+            seed: seed,
+        }),
+        None => serializer.serialize_none(),
+    }
+}
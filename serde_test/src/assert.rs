@@ -206,6 +206,30 @@ where
     }
 }
 
+/// Asserts that the given `tokens` yield `error` when deserializing with `seed`.
+///
+/// The seed is borrowed rather than consumed, so any state it accumulated before the failure can
+/// be inspected afterwards.
+#[cfg_attr(track_caller, track_caller)]
+pub fn assert_de_seed_tokens_error<'de, S, T>(seed: &mut S, tokens: &'de [Token], error: &str)
+where
+    S: ?Sized,
+    T: DeserializeState<'de, S>,
+{
+    let mut de = Deserializer::new(tokens);
+    match T::deserialize_state(seed, &mut de) {
+        Ok(_) => panic!("tokens deserialized successfully"),
+        Err(e) => assert_eq!(e, *error),
+    }
+
+    // There may be one token left if a peek caused the error
+    de.next_token_opt();
+
+    if de.remaining() > 0 {
+        panic!("{} remaining tokens", de.remaining());
+    }
+}
+
 /// Asserts that the given `tokens` yield `error` when deserializing.
 ///
 /// ```edition2018
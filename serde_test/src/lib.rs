@@ -181,7 +181,7 @@ pub use assert::{
     assert_de_tokens, assert_de_tokens_error, assert_ser_tokens, assert_ser_tokens_error,
     assert_tokens,
 
-    assert_de_seed_tokens, assert_ser_seed_tokens,
+    assert_de_seed_tokens, assert_de_seed_tokens_error, assert_ser_seed_tokens,
 };
 pub use token::Token;
 
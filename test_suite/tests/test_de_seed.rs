@@ -6,14 +6,28 @@ extern crate serde_derive_state;
 extern crate serde_state;
 extern crate serde_test;
 
-use std::collections::HashMap;
+use std::borrow::Cow;
+use std::cell::Cell;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashMap, HashSet};
+use std::ffi::OsString;
 use std::marker::PhantomData;
+use std::net::{IpAddr, SocketAddr};
+use std::num::{NonZeroU32, Wrapping};
+use std::path::PathBuf;
 use std::rc::Rc;
+use std::time::Duration;
+
+use fnv::FnvBuildHasher;
 
 use serde::de::{Deserialize, DeserializeSeed, Deserializer, Error};
-use serde_state::de::DeserializeState;
+use serde::ser::{self, Serializer};
+use serde_state::de::{state_error, DeserializeState, RecursionLimit, StateContext};
+use serde_state::ser::{MapKeySeed, Seeded, SerializeState};
+use serde_state::shared::{SerSharedSeed, SharedSeed};
 
-use serde_test::{assert_de_seed_tokens, Token};
+use serde_test::{
+    assert_de_seed_tokens, assert_de_seed_tokens_error, assert_ser_tokens, Configure, Token,
+};
 
 #[derive(Clone, Default)]
 struct Seed(i32);
@@ -84,6 +98,48 @@ fn test_deserialize_state() {
     assert_eq!(seed.0, 2);
 }
 
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed", deserialize_state_all)]
+struct SeedAllStruct {
+    first: Inner,
+    second: Inner,
+    count: u32,
+    #[serde(deserialize)]
+    unseeded: Inner,
+}
+
+#[test]
+fn test_deserialize_state_all() {
+    let value = SeedAllStruct {
+        first: Inner,
+        second: Inner,
+        count: 3,
+        unseeded: Inner,
+    };
+    let mut seed = Seed(0);
+    assert_de_seed_tokens(
+        &mut seed,
+        &value,
+        &[
+            Token::Struct {
+                name: "SeedAllStruct",
+                len: 4,
+            },
+            Token::Str("first"),
+            Token::UnitStruct { name: "Inner" },
+            Token::Str("second"),
+            Token::UnitStruct { name: "Inner" },
+            Token::Str("count"),
+            Token::U32(3),
+            Token::Str("unseeded"),
+            Token::UnitStruct { name: "Inner" },
+            Token::StructEnd,
+        ],
+    );
+
+    assert_eq!(seed.0, 2);
+}
+
 #[derive(DeserializeState, Debug, PartialEq)]
 #[serde(deserialize_state = "Seed")]
 struct Newtype(#[serde(deserialize_state_with = "deserialize_inner")] Inner);
@@ -104,6 +160,27 @@ fn test_newtype_deserialize_state() {
     assert_eq!(seed.0, 1);
 }
 
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed")]
+struct ForwardingNewtype(#[serde(deserialize_state)] Inner);
+
+#[test]
+fn test_newtype_forwards_seed() {
+    let mut seed = Seed::default();
+    assert_de_seed_tokens(
+        &mut seed,
+        &ForwardingNewtype(Inner),
+        &[
+            Token::NewtypeStruct {
+                name: "ForwardingNewtype",
+            },
+            Token::UnitStruct { name: "Inner" },
+        ],
+    );
+
+    assert_eq!(seed.0, 1);
+}
+
 #[derive(Clone)]
 struct ExtraParameterNewtypeSeed<T>(Seed, PhantomData<T>);
 
@@ -136,6 +213,437 @@ fn extra_parameter_test_newtype_deserialize_state() {
     assert_eq!((seed.0).0, 1);
 }
 
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed")]
+struct PhantomField<T> {
+    #[serde(deserialize_state)]
+    inner: Inner,
+    #[serde(deserialize_state)]
+    marker: PhantomData<T>,
+}
+
+#[test]
+fn test_phantom_data_deserialize_state() {
+    let value = PhantomField::<String> {
+        inner: Inner,
+        marker: PhantomData,
+    };
+    let mut seed = Seed::default();
+    assert_de_seed_tokens(
+        &mut seed,
+        &value,
+        &[
+            Token::Struct {
+                name: "PhantomField",
+                len: 2,
+            },
+            Token::Str("inner"),
+            Token::UnitStruct { name: "Inner" },
+            Token::Str("marker"),
+            Token::UnitStruct {
+                name: "PhantomData",
+            },
+            Token::StructEnd,
+        ],
+    );
+
+    assert_eq!(seed.0, 1);
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed")]
+struct Timeout {
+    #[serde(deserialize_state)]
+    inner: Inner,
+    #[serde(deserialize_state)]
+    timeout: Duration,
+}
+
+#[derive(SerializeState, DeserializeState, Debug, PartialEq)]
+#[serde(serialize_state = "Cell<i32>", deserialize_state = "Seed")]
+struct Listen {
+    #[serde(state)]
+    value: Counted,
+    #[serde(state)]
+    addr: SocketAddr,
+    #[serde(state)]
+    ip: IpAddr,
+}
+
+#[test]
+fn test_net_addresses_round_trip() {
+    let value = Listen {
+        value: Counted,
+        addr: "127.0.0.1:8080".parse().unwrap(),
+        ip: "::1".parse().unwrap(),
+    };
+    let tokens = &[
+        Token::Struct {
+            name: "Listen",
+            len: 3,
+        },
+        Token::Str("value"),
+        Token::Unit,
+        Token::Str("addr"),
+        Token::Str("127.0.0.1:8080"),
+        Token::Str("ip"),
+        Token::Str("::1"),
+        Token::StructEnd,
+    ];
+
+    let ser_seed = Cell::new(0);
+    assert_ser_tokens(&Seeded::new(&ser_seed, &value).readable(), tokens);
+    assert_eq!(ser_seed.get(), 1);
+
+    // Address types check `is_human_readable`, which `assert_de_seed_tokens` does not configure
+    let mut de_seed = Seed::default();
+    let mut de = serde_test::Deserializer::new(tokens);
+    let deserialized = serde_state::de::Seed::<_, Listen>::new(&mut de_seed)
+        .readable()
+        .deserialize(&mut de)
+        .unwrap();
+    assert_eq!(deserialized, value);
+    assert_eq!(de_seed.0, 1);
+}
+
+#[derive(SerializeState, DeserializeState, Debug, PartialEq)]
+#[serde(serialize_state = "Cell<i32>", deserialize_state = "Seed")]
+struct Paths {
+    #[serde(state)]
+    value: Counted,
+    #[serde(state)]
+    path: PathBuf,
+    #[serde(state)]
+    name: OsString,
+}
+
+#[cfg(unix)]
+#[test]
+fn test_paths_round_trip() {
+    let value = Paths {
+        value: Counted,
+        path: PathBuf::from("dir/file"),
+        name: OsString::from("ab"),
+    };
+    let tokens = &[
+        Token::Struct {
+            name: "Paths",
+            len: 3,
+        },
+        Token::Str("value"),
+        Token::Unit,
+        Token::Str("path"),
+        Token::Str("dir/file"),
+        Token::Str("name"),
+        Token::NewtypeVariant {
+            name: "OsString",
+            variant: "Unix",
+        },
+        Token::Seq { len: Some(2) },
+        Token::U8(b'a'),
+        Token::U8(b'b'),
+        Token::SeqEnd,
+        Token::StructEnd,
+    ];
+
+    let ser_seed = Cell::new(0);
+    assert_ser_tokens(&Seeded::new(&ser_seed, &value), tokens);
+    assert_eq!(ser_seed.get(), 1);
+
+    let mut de_seed = Seed::default();
+    assert_de_seed_tokens(&mut de_seed, &value, tokens);
+    assert_eq!(de_seed.0, 1);
+}
+
+#[test]
+fn test_duration_deserialize_state() {
+    let value = Timeout {
+        inner: Inner,
+        timeout: Duration::new(1, 2),
+    };
+    let mut seed = Seed::default();
+    assert_de_seed_tokens(
+        &mut seed,
+        &value,
+        &[
+            Token::Struct {
+                name: "Timeout",
+                len: 2,
+            },
+            Token::Str("inner"),
+            Token::UnitStruct { name: "Inner" },
+            Token::Str("timeout"),
+            Token::Struct {
+                name: "Duration",
+                len: 2,
+            },
+            Token::Str("secs"),
+            Token::U64(1),
+            Token::Str("nanos"),
+            Token::U32(2),
+            Token::StructEnd,
+            Token::StructEnd,
+        ],
+    );
+
+    assert_eq!(seed.0, 1);
+}
+
+#[derive(Debug, PartialEq)]
+struct Counted;
+
+impl SerializeState<Cell<i32>> for Counted {
+    fn serialize_state<S>(&self, serializer: S, seed: &Cell<i32>) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        seed.set(seed.get() + 1);
+        serializer.serialize_unit()
+    }
+}
+
+impl<'de> DeserializeState<'de, Seed> for Counted {
+    fn deserialize_state<D>(seed: &mut Seed, deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        seed.0 += 1;
+        <()>::deserialize(deserializer).map(|()| Counted)
+    }
+}
+
+#[derive(SerializeState, DeserializeState, Debug, PartialEq)]
+#[serde(serialize_state = "Cell<i32>", deserialize_state = "Seed")]
+struct SplitSeeds {
+    #[serde(state)]
+    value: Counted,
+    plain: i32,
+}
+
+#[test]
+fn test_separate_ser_and_de_state() {
+    let value = SplitSeeds {
+        value: Counted,
+        plain: 4,
+    };
+    let tokens = &[
+        Token::Struct {
+            name: "SplitSeeds",
+            len: 2,
+        },
+        Token::Str("value"),
+        Token::Unit,
+        Token::Str("plain"),
+        Token::I32(4),
+        Token::StructEnd,
+    ];
+
+    let ser_seed = Cell::new(0);
+    assert_ser_tokens(&Seeded::new(&ser_seed, &value), tokens);
+    assert_eq!(ser_seed.get(), 1);
+
+    let mut de_seed = Seed::default();
+    assert_de_seed_tokens(&mut de_seed, &value, tokens);
+    assert_eq!(de_seed.0, 1);
+}
+
+#[derive(SerializeState, DeserializeState, Debug, PartialEq)]
+#[serde(serialize_state = "Cell<i32>", deserialize_state = "Seed")]
+#[serde(bound(
+    serialize = "T: SerializeState<Cell<i32>>",
+    deserialize = "T: DeserializeState<'de, Seed>"
+))]
+struct SplitBounds<T> {
+    #[serde(state)]
+    value: T,
+}
+
+#[test]
+fn test_separate_ser_and_de_bounds() {
+    let value = SplitBounds { value: Counted };
+    let tokens = &[
+        Token::Struct {
+            name: "SplitBounds",
+            len: 1,
+        },
+        Token::Str("value"),
+        Token::Unit,
+        Token::StructEnd,
+    ];
+
+    let ser_seed = Cell::new(0);
+    assert_ser_tokens(&Seeded::new(&ser_seed, &value), tokens);
+    assert_eq!(ser_seed.get(), 1);
+
+    let mut de_seed = Seed::default();
+    assert_de_seed_tokens(&mut de_seed, &value, tokens);
+    assert_eq!(de_seed.0, 1);
+
+    // `Inner` only implements `DeserializeState`, which the serialize bound must not require
+    assert_de_seed_tokens(
+        &mut de_seed,
+        &SplitBounds { value: Inner },
+        &[
+            Token::Struct {
+                name: "SplitBounds",
+                len: 1,
+            },
+            Token::Str("value"),
+            Token::UnitStruct { name: "Inner" },
+            Token::StructEnd,
+        ],
+    );
+    assert_eq!(de_seed.0, 2);
+}
+
+#[test]
+fn test_result_round_trip() {
+    let tokens = &[
+        Token::NewtypeVariant {
+            name: "Result",
+            variant: "Err",
+        },
+        Token::Unit,
+    ];
+    let value: Result<i32, Counted> = Err(Counted);
+
+    let ser_seed = Cell::new(0);
+    assert_ser_tokens(&Seeded::new(&ser_seed, &value), tokens);
+    assert_eq!(ser_seed.get(), 1);
+
+    let mut de_seed = Seed::default();
+    assert_de_seed_tokens(&mut de_seed, &value, tokens);
+    assert_eq!(de_seed.0, 1);
+}
+
+#[test]
+fn test_wrapping_round_trip() {
+    let tokens = &[Token::Unit];
+    let value = Wrapping(Counted);
+
+    let ser_seed = Cell::new(0);
+    assert_ser_tokens(&Seeded::new(&ser_seed, &value), tokens);
+    assert_eq!(ser_seed.get(), 1);
+
+    let mut de_seed = Seed::default();
+    assert_de_seed_tokens(&mut de_seed, &value, tokens);
+    assert_eq!(de_seed.0, 1);
+}
+
+#[derive(SerializeState, DeserializeState, Debug, PartialEq)]
+#[serde(serialize_state = "Cell<i32>", deserialize_state = "Seed")]
+struct RenamedFields {
+    #[serde(state, rename = "counted")]
+    value: Counted,
+    #[serde(rename = "other")]
+    plain: i32,
+}
+
+#[test]
+fn test_renamed_field_round_trip() {
+    let value = RenamedFields {
+        value: Counted,
+        plain: 4,
+    };
+    let tokens = &[
+        Token::Struct {
+            name: "RenamedFields",
+            len: 2,
+        },
+        Token::Str("counted"),
+        Token::Unit,
+        Token::Str("other"),
+        Token::I32(4),
+        Token::StructEnd,
+    ];
+
+    let ser_seed = Cell::new(0);
+    assert_ser_tokens(&Seeded::new(&ser_seed, &value), tokens);
+    assert_eq!(ser_seed.get(), 1);
+
+    let mut de_seed = Seed::default();
+    assert_de_seed_tokens(&mut de_seed, &value, tokens);
+    assert_eq!(de_seed.0, 1);
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed")]
+struct BorrowedCow<'a> {
+    #[serde(borrow, deserialize_state_with = "serde_state::de::borrow_cow_str")]
+    name: Cow<'a, str>,
+    #[serde(borrow, deserialize_state_with = "serde_state::de::borrow_cow_bytes")]
+    bytes: Cow<'a, [u8]>,
+    #[serde(deserialize_state)]
+    value: Inner,
+}
+
+#[test]
+fn test_borrowed_cow_deserialize_state() {
+    let tokens = &[
+        Token::Struct {
+            name: "BorrowedCow",
+            len: 3,
+        },
+        Token::Str("name"),
+        Token::BorrowedStr("borrowed"),
+        Token::Str("bytes"),
+        Token::BorrowedBytes(b"bytes"),
+        Token::Str("value"),
+        Token::UnitStruct { name: "Inner" },
+        Token::StructEnd,
+    ];
+    let mut seed = Seed::default();
+    let mut de = serde_test::Deserializer::new(tokens);
+    let value = BorrowedCow::deserialize_state(&mut seed, &mut de).unwrap();
+    match value.name {
+        Cow::Borrowed("borrowed") => {}
+        _ => panic!("expected a borrowed string, got {:?}", value.name),
+    }
+    match value.bytes {
+        Cow::Borrowed(b"bytes") => {}
+        _ => panic!("expected borrowed bytes, got {:?}", value.bytes),
+    }
+
+    assert_eq!(seed.0, 1);
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed")]
+struct BorrowedAndSeeded<'a> {
+    name: &'a str,
+    #[serde(borrow)]
+    label: Cow<'a, str>,
+    #[serde(deserialize_state)]
+    value: Inner,
+}
+
+#[test]
+fn test_borrowed_field_next_to_seeded_field() {
+    let tokens = &[
+        Token::Struct {
+            name: "BorrowedAndSeeded",
+            len: 3,
+        },
+        Token::Str("name"),
+        Token::BorrowedStr("name"),
+        Token::Str("label"),
+        Token::BorrowedStr("label"),
+        Token::Str("value"),
+        Token::UnitStruct { name: "Inner" },
+        Token::StructEnd,
+    ];
+    let mut seed = Seed::default();
+    let mut de = serde_test::Deserializer::new(tokens);
+    let value = BorrowedAndSeeded::deserialize_state(&mut seed, &mut de).unwrap();
+    assert_eq!(value.name, "name");
+    match value.label {
+        Cow::Borrowed("label") => {}
+        _ => panic!("expected a borrowed string, got {:?}", value.label),
+    }
+
+    assert_eq!(seed.0, 1);
+}
+
 #[derive(Clone)]
 struct VecSeed<T>(T);
 
@@ -173,68 +681,256 @@ fn test_vec_newtype_deserialize_state() {
     assert_eq!((seed.0).0, 2);
 }
 
-#[derive(Clone)]
-struct GenericTypeSeed<T>(Seed, T);
+#[test]
+fn test_seq_seed_ex_new_default() {
+    use serde_state::de::SeqSeedEx;
+    use std::collections::LinkedList;
 
-impl<T> AsMut<Seed> for GenericTypeSeed<T> {
-    fn as_mut(&mut self) -> &mut Seed {
-        &mut self.0
-    }
+    let tokens = [
+        Token::Seq { len: Some(2) },
+        Token::I32(1),
+        Token::I32(2),
+        Token::SeqEnd,
+    ];
+
+    let mut seed = Seed::default();
+    let mut de = serde_test::Deserializer::new(&tokens);
+    let list = SeqSeedEx::<LinkedList<_>, _, _, Ranked>::new_default(&mut seed)
+        .deserialize(&mut de)
+        .unwrap();
+    assert_eq!(de.remaining(), 0);
+    assert_eq!(list.into_iter().collect::<Vec<_>>(), [Ranked(1), Ranked(2)]);
+
+    let mut de = serde_test::Deserializer::new(&tokens);
+    let set = SeqSeedEx::<HashSet<_>, _, _, Ranked>::new_default(&mut seed)
+        .deserialize(&mut de)
+        .unwrap();
+    assert_eq!(de.remaining(), 0);
+    assert_eq!(set, vec![Ranked(1), Ranked(2)].into_iter().collect::<HashSet<_>>());
+
+    assert_eq!(seed.0, 4);
 }
 
-fn deserialize_nested_seed<'de, T, D>(
-    seed: &mut GenericTypeSeed<T>,
+fn deserialize_btree_map<'de, K, V, D>(
+    seed: &mut Seed,
     deserializer: D,
-) -> Result<T::Value, D::Error>
+) -> Result<BTreeMap<K, V>, D::Error>
 where
     D: Deserializer<'de>,
-    T: DeserializeSeed<'de> + Clone,
+    K: DeserializeState<'de, Seed> + Ord,
+    V: DeserializeState<'de, Seed>,
 {
-    seed.1.clone().deserialize(deserializer)
+    use serde_state::de::MapSeedEx;
+    deserializer.deserialize_map(MapSeedEx::new(seed, |_| BTreeMap::new()))
 }
 
 #[derive(DeserializeState, Debug, PartialEq)]
-#[serde(deserialize_state = "GenericTypeSeed<S>")]
-#[serde(de_parameters = "S")]
-#[serde(bound = "S: Clone + DeserializeSeed<'de, Value = T>")]
-struct GenericType<T> {
-    #[serde(deserialize_state_with = "deserialize_inner")]
-    inner: Inner,
-    #[serde(deserialize_state_with = "deserialize_nested_seed")]
-    t: T,
-}
+#[serde(deserialize_state = "Seed")]
+struct MapNewtype(#[serde(deserialize_state_with = "deserialize_btree_map")] BTreeMap<String, Inner>);
 
 #[test]
-fn test_generic_deserialize_state() {
-    let value = GenericType { inner: Inner, t: 3 };
-    let mut seed = GenericTypeSeed(Seed::default(), PhantomData);
+fn test_map_seed_ex() {
+    let mut map = BTreeMap::new();
+    map.insert("a".to_string(), Inner);
+    map.insert("b".to_string(), Inner);
+    let value = MapNewtype(map);
+    let mut seed = Seed::default();
     assert_de_seed_tokens(
         &mut seed,
         &value,
         &[
-            Token::Struct {
-                name: "GenericType",
-                len: 2,
-            },
-            Token::String("inner"),
+            Token::NewtypeStruct { name: "MapNewtype" },
+            Token::Map { len: Some(2) },
+            Token::Str("a"),
             Token::UnitStruct { name: "Inner" },
-            Token::String("t"),
-            Token::I32(3),
-            Token::StructEnd,
+            Token::Str("b"),
+            Token::UnitStruct { name: "Inner" },
+            Token::MapEnd,
         ],
     );
 
-    assert_eq!((seed.0).0, 1);
+    assert_eq!(seed.0, 2);
 }
 
-#[derive(DeserializeState, Debug, PartialEq)]
-#[serde(deserialize_state = "Seed")]
-enum Enum {
-    Inner(#[serde(deserialize_state_with = "deserialize_inner")] Inner),
-    Inner2(
-        u32,
-        #[serde(deserialize_state_with = "deserialize_inner")] Inner,
-    ),
+struct KeyValueSeed {
+    keys: Seed,
+    values: Seed,
+}
+
+fn deserialize_split_map<'de, D>(
+    seed: &mut KeyValueSeed,
+    deserializer: D,
+) -> Result<BTreeMap<Key, Inner>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    use serde_state::de::MapSeedEx;
+    let KeyValueSeed {
+        ref mut keys,
+        ref mut values,
+    } = *seed;
+    deserializer.deserialize_map(MapSeedEx::with_key_value(keys, values, |_| BTreeMap::new()))
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "KeyValueSeed")]
+struct SplitMap(#[serde(deserialize_state_with = "deserialize_split_map")] BTreeMap<Key, Inner>);
+
+#[test]
+fn test_map_seed_ex_with_key_value() {
+    let mut map = BTreeMap::new();
+    map.insert(Key(1), Inner);
+    map.insert(Key(2), Inner);
+    let value = SplitMap(map);
+    let mut seed = KeyValueSeed {
+        keys: Seed(0),
+        values: Seed(10),
+    };
+    assert_de_seed_tokens(
+        &mut seed,
+        &value,
+        &[
+            Token::NewtypeStruct { name: "SplitMap" },
+            Token::Map { len: Some(2) },
+            Token::NewtypeStruct { name: "Key" },
+            Token::I32(1),
+            Token::UnitStruct { name: "Inner" },
+            Token::NewtypeStruct { name: "Key" },
+            Token::I32(2),
+            Token::UnitStruct { name: "Inner" },
+            Token::MapEnd,
+        ],
+    );
+
+    assert_eq!(seed.keys.0, 2);
+    assert_eq!(seed.values.0, 12);
+}
+
+struct Workspace {
+    interner: Seed,
+    opened: usize,
+}
+
+fn workspace_interner(workspace: &mut Workspace) -> &mut Seed {
+    &mut workspace.interner
+}
+
+fn deserialize_interned_map<'de, D>(
+    seed: &mut Workspace,
+    deserializer: D,
+) -> Result<BTreeMap<Key, Inner>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    use serde_state::de::MapSeedEx;
+    seed.opened += 1;
+    deserializer.deserialize_map(MapSeedEx::with_projections(
+        seed,
+        workspace_interner,
+        workspace_interner,
+        |_| BTreeMap::new(),
+    ))
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Workspace")]
+struct InternedMap(
+    #[serde(deserialize_state_with = "deserialize_interned_map")] BTreeMap<Key, Inner>,
+);
+
+#[test]
+fn test_map_seed_ex_with_projections() {
+    let mut map = BTreeMap::new();
+    map.insert(Key(1), Inner);
+    map.insert(Key(2), Inner);
+    let value = InternedMap(map);
+    let mut seed = Workspace {
+        interner: Seed(0),
+        opened: 0,
+    };
+    assert_de_seed_tokens(
+        &mut seed,
+        &value,
+        &[
+            Token::NewtypeStruct { name: "InternedMap" },
+            Token::Map { len: Some(2) },
+            Token::NewtypeStruct { name: "Key" },
+            Token::I32(1),
+            Token::UnitStruct { name: "Inner" },
+            Token::NewtypeStruct { name: "Key" },
+            Token::I32(2),
+            Token::UnitStruct { name: "Inner" },
+            Token::MapEnd,
+        ],
+    );
+
+    // Keys and values were both deserialized with the one interner
+    assert_eq!(seed.interner.0, 4);
+    assert_eq!(seed.opened, 1);
+}
+
+#[derive(Clone)]
+struct GenericTypeSeed<T>(Seed, T);
+
+impl<T> AsMut<Seed> for GenericTypeSeed<T> {
+    fn as_mut(&mut self) -> &mut Seed {
+        &mut self.0
+    }
+}
+
+fn deserialize_nested_seed<'de, T, D>(
+    seed: &mut GenericTypeSeed<T>,
+    deserializer: D,
+) -> Result<T::Value, D::Error>
+where
+    D: Deserializer<'de>,
+    T: DeserializeSeed<'de> + Clone,
+{
+    seed.1.clone().deserialize(deserializer)
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "GenericTypeSeed<S>")]
+#[serde(de_parameters = "S")]
+#[serde(bound = "S: Clone + DeserializeSeed<'de, Value = T>")]
+struct GenericType<T> {
+    #[serde(deserialize_state_with = "deserialize_inner")]
+    inner: Inner,
+    #[serde(deserialize_state_with = "deserialize_nested_seed")]
+    t: T,
+}
+
+#[test]
+fn test_generic_deserialize_state() {
+    let value = GenericType { inner: Inner, t: 3 };
+    let mut seed = GenericTypeSeed(Seed::default(), PhantomData);
+    assert_de_seed_tokens(
+        &mut seed,
+        &value,
+        &[
+            Token::Struct {
+                name: "GenericType",
+                len: 2,
+            },
+            Token::String("inner"),
+            Token::UnitStruct { name: "Inner" },
+            Token::String("t"),
+            Token::I32(3),
+            Token::StructEnd,
+        ],
+    );
+
+    assert_eq!((seed.0).0, 1);
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed")]
+enum Enum {
+    Inner(#[serde(deserialize_state_with = "deserialize_inner")] Inner),
+    Inner2(
+        u32,
+        #[serde(deserialize_state_with = "deserialize_inner")] Inner,
+    ),
 }
 
 #[test]
@@ -278,6 +974,111 @@ fn test_enum_deserialize_state_2() {
     assert_eq!(seed.0, 1);
 }
 
+#[test]
+fn test_enum_deserialize_state_variant_index() {
+    let mut seed = Seed::default();
+    assert_de_seed_tokens(
+        &mut seed,
+        &Enum::Inner(Inner),
+        &[
+            Token::Enum { name: "Enum" },
+            Token::U32(0),
+            Token::UnitStruct { name: "Inner" },
+        ],
+    );
+    assert_de_seed_tokens(
+        &mut seed,
+        &Enum::Inner2(3, Inner),
+        &[
+            Token::Enum { name: "Enum" },
+            Token::U32(1),
+            Token::Seq { len: Some(2) },
+            Token::U32(3),
+            Token::UnitStruct { name: "Inner" },
+            Token::SeqEnd,
+        ],
+    );
+
+    assert_eq!(seed.0, 2);
+}
+
+#[test]
+fn test_enum_deserialize_state_variant_index_out_of_range() {
+    let mut seed = Seed::default();
+    assert_de_seed_tokens_error::<_, Enum>(
+        &mut seed,
+        &[
+            Token::Enum { name: "Enum" },
+            Token::U32(2),
+            Token::Unit,
+        ],
+        "invalid value: integer `2`, expected variant index 0 <= i < 2",
+    );
+
+    assert_eq!(seed.0, 0);
+}
+
+struct Context<'ctx> {
+    names: &'ctx [&'ctx str],
+    lookups: usize,
+}
+
+fn deserialize_interned<'de, 'ctx, D>(
+    seed: &mut Context<'ctx>,
+    deserializer: D,
+) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let index = usize::deserialize(deserializer)?;
+    seed.lookups += 1;
+    match seed.names.get(index) {
+        Some(name) => Ok(name.to_string()),
+        None => Err(Error::custom(format_args!("missing name {}", index))),
+    }
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Context<'ctx>")]
+#[serde(de_parameters = "'ctx")]
+struct Interned {
+    #[serde(deserialize_state_with = "deserialize_interned")]
+    first: String,
+    #[serde(deserialize_state_with = "deserialize_interned")]
+    second: String,
+}
+
+#[test]
+fn test_lifetime_de_parameters() {
+    let names = vec!["a".to_string(), "b".to_string()];
+    let names: Vec<&str> = names.iter().map(|name| &name[..]).collect();
+    let value = Interned {
+        first: "b".to_string(),
+        second: "a".to_string(),
+    };
+    let mut seed = Context {
+        names: &names,
+        lookups: 0,
+    };
+    assert_de_seed_tokens(
+        &mut seed,
+        &value,
+        &[
+            Token::Struct {
+                name: "Interned",
+                len: 2,
+            },
+            Token::Str("first"),
+            Token::U64(1),
+            Token::Str("second"),
+            Token::U64(0),
+            Token::StructEnd,
+        ],
+    );
+
+    assert_eq!(seed.lookups, 2);
+}
+
 #[derive(DeserializeState, Debug, PartialEq)]
 #[serde(deserialize_state = "NodeMap")]
 struct Node {
@@ -312,7 +1113,7 @@ where
             Variant::Plain { data, left, right } => Ok(Some(Rc::new(Node { data, left, right }))),
             Variant::Reference(id) => match seed.get(&id) {
                 Some(rc) => Ok(Some(Rc::clone(rc))),
-                None => Err(Error::custom(format_args!("missing id {}", id))),
+                None => Err(serde_state::de::error_with_state(seed, MissingId(id))),
             },
         },
     }
@@ -321,16 +1122,53 @@ where
 type Id = u32;
 type IdToShared<T> = HashMap<Id, T>;
 
-type IdToNode = IdToShared<Rc<Node>>;
+#[derive(Default)]
+struct NodeMap(IdToShared<Rc<Node>>);
+
+impl NodeMap {
+    fn new() -> Self {
+        NodeMap::default()
+    }
+}
+
+impl std::ops::Deref for NodeMap {
+    type Target = IdToShared<Rc<Node>>;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl std::ops::DerefMut for NodeMap {
+    fn deref_mut(&mut self) -> &mut Self::Target {
+        &mut self.0
+    }
+}
+
+impl serde_state::de::DebugState for NodeMap {
+    fn debug_state(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        serde_state::de::DebugState::debug_state(&self.0, formatter)
+    }
+}
+
+struct MissingId(Id);
 
-type NodeMap = IdToShared<Rc<Node>>;
+impl std::fmt::Display for MissingId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "missing id {}", self.0)
+    }
+}
+
+impl StateContext for NodeMap {
+    type Error = MissingId;
+}
 
 impl<'de> Deserialize<'de> for Node {
     fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
     where
         D: Deserializer<'de>,
     {
-        let mut map = IdToNode::default();
+        let mut map = NodeMap::default();
         Self::deserialize_state(&mut map, deserializer)
     }
 }
@@ -370,8 +1208,7 @@ fn test_node_deserialize() {
         left: Some(Rc::clone(&b)),
         right: Some(Rc::clone(&b)),
     });
-    let map = HashMap::new();
-    let mut seed = map;
+    let mut seed = NodeMap::new();
     assert_de_seed_tokens(
         &mut seed,
         &*a,
@@ -410,9 +1247,3537 @@ fn test_node_deserialize() {
     );
 }
 
-#[derive(DeserializeState, Debug, PartialEq)]
-#[serde(deserialize_state = "Seed")]
-struct Attr {
-    #[serde(with = "i32")]
-    x: i32,
+#[test]
+fn test_seeded_field_error_context() {
+    let tokens = &[
+        Token::Struct {
+            name: "Node",
+            len: 3,
+        },
+        Token::Str("data"),
+        Token::Char('a'),
+        Token::Str("left"),
+        Token::Some,
+        Token::NewtypeVariant {
+            name: "Node",
+            variant: "Reference",
+        },
+        Token::U32(5),
+    ];
+    let mut seed = NodeMap::new();
+    seed.insert(
+        2,
+        Rc::new(Node {
+            data: 'b',
+            left: None,
+            right: None,
+        }),
+    );
+    seed.insert(
+        1,
+        Rc::new(Node {
+            data: 'c',
+            left: None,
+            right: None,
+        }),
+    );
+    let expected = if cfg!(debug_assertions) {
+        "field `left`: missing id 5 (known keys: [1, 2])"
+    } else {
+        "field `left`: missing id 5"
+    };
+    let mut de = serde_test::Deserializer::new(tokens);
+    match Node::deserialize_state(&mut seed, &mut de) {
+        Ok(_) => panic!("expected the missing reference to fail"),
+        Err(err) => assert_eq!(err.to_string(), expected),
+    }
+}
+
+#[derive(SerializeState, DeserializeState, Debug, PartialEq)]
+#[serde(serialize_state = "NodeMap", deserialize_state = "NodeMap")]
+#[serde(rename = "Node")]
+enum RenamedNode {
+    #[serde(rename = "ref")]
+    Reference(
+        #[serde(
+            serialize_state_with = "serialize_node_ref",
+            deserialize_state_with = "deserialize_node_ref"
+        )]
+        Rc<Node>,
+    ),
+    #[serde(rename = "leaf")]
+    Leaf { data: char },
+}
+
+fn serialize_node_ref<S>(node: &Rc<Node>, serializer: S, map: &NodeMap) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    match map.iter().find(|&(_, n)| Rc::ptr_eq(n, node)) {
+        Some((id, _)) => serializer.serialize_u32(*id),
+        None => Err(ser::Error::custom("node is not in the map")),
+    }
+}
+
+fn deserialize_node_ref<'de, D>(map: &mut NodeMap, deserializer: D) -> Result<Rc<Node>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let id = Id::deserialize(deserializer)?;
+    match map.get(&id) {
+        Some(rc) => Ok(Rc::clone(rc)),
+        None => Err(state_error::<NodeMap, _>(MissingId(id))),
+    }
+}
+
+#[test]
+fn test_renamed_variant_round_trip() {
+    let node = Rc::new(Node {
+        data: 'a',
+        left: None,
+        right: None,
+    });
+    let mut map = NodeMap::new();
+    map.insert(7, Rc::clone(&node));
+
+    let value = RenamedNode::Reference(node);
+    let tokens = &[
+        Token::NewtypeVariant {
+            name: "Node",
+            variant: "ref",
+        },
+        Token::U32(7),
+    ];
+    assert_ser_tokens(&Seeded::new(&map, &value), tokens);
+    assert_de_seed_tokens(&mut map, &value, tokens);
+
+    assert_de_seed_tokens_error::<_, RenamedNode>(
+        &mut map,
+        &[
+            Token::NewtypeVariant {
+                name: "Node",
+                variant: "ref",
+            },
+            Token::U32(8),
+        ],
+        "missing id 8",
+    );
+
+    let value = RenamedNode::Leaf { data: 'b' };
+    let tokens = &[
+        Token::StructVariant {
+            name: "Node",
+            variant: "leaf",
+            len: 1,
+        },
+        Token::Str("data"),
+        Token::Char('b'),
+        Token::StructVariantEnd,
+    ];
+    assert_ser_tokens(&Seeded::new(&map, &value), tokens);
+    assert_de_seed_tokens(&mut map, &value, tokens);
+}
+
+#[derive(SerializeState, DeserializeState, Debug, PartialEq)]
+#[serde(serialize_state = "SerSharedSeed")]
+#[serde(deserialize_state = "SharedSeed<Rc<i32>>")]
+struct SharedPair {
+    #[serde(
+        serialize_state_with = "serde_state::shared::serialize_shared",
+        deserialize_state_with = "serde_state::shared::deserialize_shared"
+    )]
+    first: Rc<i32>,
+    #[serde(
+        serialize_state_with = "serde_state::shared::serialize_shared",
+        deserialize_state_with = "serde_state::shared::deserialize_shared"
+    )]
+    second: Rc<i32>,
+    #[serde(
+        serialize_state_with = "serde_state::shared::serialize_shared",
+        deserialize_state_with = "serde_state::shared::deserialize_shared"
+    )]
+    third: Rc<i32>,
+}
+
+#[test]
+fn test_shared_round_trip() {
+    let shared = Rc::new(1);
+    let value = SharedPair {
+        first: Rc::clone(&shared),
+        second: shared,
+        third: Rc::new(2),
+    };
+    let tokens = &[
+        Token::Struct {
+            name: "SharedPair",
+            len: 3,
+        },
+        Token::Str("first"),
+        Token::TupleVariant {
+            name: "Shared",
+            variant: "Marked",
+            len: 2,
+        },
+        Token::U32(0),
+        Token::I32(1),
+        Token::TupleVariantEnd,
+        Token::Str("second"),
+        Token::NewtypeVariant {
+            name: "Shared",
+            variant: "Reference",
+        },
+        Token::U32(0),
+        Token::Str("third"),
+        Token::NewtypeVariant {
+            name: "Shared",
+            variant: "Plain",
+        },
+        Token::I32(2),
+        Token::StructEnd,
+    ];
+
+    assert_ser_tokens(&Seeded::new(&SerSharedSeed::new(), &value), tokens);
+    assert_de_seed_tokens(&mut SharedSeed::new(), &value, tokens);
+
+    let mut de = serde_test::Deserializer::new(tokens);
+    let pair = SharedPair::deserialize_state(&mut SharedSeed::new(), &mut de).unwrap();
+    assert!(Rc::ptr_eq(&pair.first, &pair.second));
+    assert!(!Rc::ptr_eq(&pair.first, &pair.third));
+}
+
+#[derive(SerializeState, DeserializeState, Debug, PartialEq)]
+#[serde(serialize_state = "SerSharedSeed")]
+#[serde(deserialize_state = "SharedSeed<Rc<SharedNode>>")]
+struct SharedNode {
+    data: char,
+    #[serde(
+        serialize_state_with = "serde_state::shared::serialize_option_shared",
+        deserialize_state_with = "serde_state::shared::deserialize_option_shared"
+    )]
+    left: Option<Rc<SharedNode>>,
+    #[serde(
+        serialize_state_with = "serde_state::shared::serialize_option_shared",
+        deserialize_state_with = "serde_state::shared::deserialize_option_shared"
+    )]
+    right: Option<Rc<SharedNode>>,
+}
+
+#[test]
+fn test_shared_node_graph() {
+    let b = Rc::new(SharedNode {
+        data: 'b',
+        left: None,
+        right: None,
+    });
+    let a = SharedNode {
+        data: 'a',
+        left: Some(Rc::clone(&b)),
+        right: Some(b),
+    };
+    let tokens = &[
+        Token::Struct {
+            name: "SharedNode",
+            len: 3,
+        },
+        Token::Str("data"),
+        Token::Char('a'),
+        Token::Str("left"),
+        Token::Some,
+        Token::TupleVariant {
+            name: "Shared",
+            variant: "Marked",
+            len: 2,
+        },
+        Token::U32(0),
+        Token::Struct {
+            name: "SharedNode",
+            len: 3,
+        },
+        Token::Str("data"),
+        Token::Char('b'),
+        Token::Str("left"),
+        Token::None,
+        Token::Str("right"),
+        Token::None,
+        Token::StructEnd,
+        Token::TupleVariantEnd,
+        Token::Str("right"),
+        Token::Some,
+        Token::NewtypeVariant {
+            name: "Shared",
+            variant: "Reference",
+        },
+        Token::U32(0),
+        Token::StructEnd,
+    ];
+
+    assert_ser_tokens(&Seeded::new(&SerSharedSeed::new(), &a), tokens);
+    assert_de_seed_tokens(&mut SharedSeed::new(), &a, tokens);
+
+    let mut de = serde_test::Deserializer::new(tokens);
+    let node = SharedNode::deserialize_state(&mut SharedSeed::new(), &mut de).unwrap();
+    assert!(Rc::ptr_eq(
+        node.left.as_ref().unwrap(),
+        node.right.as_ref().unwrap()
+    ));
+}
+
+#[test]
+fn test_shared_node_missing_reference() {
+    let mut seed = SharedSeed::new();
+    assert_de_seed_tokens_error::<_, SharedNode>(
+        &mut seed,
+        &[
+            Token::Struct {
+                name: "SharedNode",
+                len: 3,
+            },
+            Token::Str("data"),
+            Token::Char('a'),
+            Token::Str("left"),
+            Token::Some,
+            Token::TupleVariant {
+                name: "Shared",
+                variant: "Marked",
+                len: 2,
+            },
+            Token::U32(0),
+            Token::Struct {
+                name: "SharedNode",
+                len: 3,
+            },
+            Token::Str("data"),
+            Token::Char('b'),
+            Token::Str("left"),
+            Token::None,
+            Token::Str("right"),
+            Token::None,
+            Token::StructEnd,
+            Token::TupleVariantEnd,
+            Token::Str("right"),
+            Token::Some,
+            Token::NewtypeVariant {
+                name: "Shared",
+                variant: "Reference",
+            },
+            Token::U32(1),
+            Token::StructEnd,
+        ],
+        "field `right`: missing shared value with id 1",
+    );
+
+    // The value marked before the failure was still registered
+    assert_eq!(seed.get(0).map(|node| node.data), Some('b'));
+    assert!(seed.get(1).is_none());
+}
+
+#[test]
+fn test_shared_duplicate_id() {
+    let mut seed = SharedSeed::new();
+    assert_de_seed_tokens_error::<_, SharedPair>(
+        &mut seed,
+        &[
+            Token::Struct {
+                name: "SharedPair",
+                len: 3,
+            },
+            Token::Str("first"),
+            Token::TupleVariant {
+                name: "Shared",
+                variant: "Marked",
+                len: 2,
+            },
+            Token::U32(0),
+            Token::I32(1),
+            Token::TupleVariantEnd,
+            Token::Str("second"),
+            Token::TupleVariant {
+                name: "Shared",
+                variant: "Marked",
+                len: 2,
+            },
+            Token::U32(0),
+        ],
+        "field `second`: duplicate shared value with id 0",
+    );
+
+    // The first value marked with the id is kept
+    assert_eq!(seed.get(0).map(|value| **value), Some(1));
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "RecursionLimit<Seed>")]
+struct Nested {
+    #[serde(deserialize_state_with = "deserialize_nested")]
+    next: Option<Box<Nested>>,
+}
+
+fn deserialize_nested<'de, D>(
+    seed: &mut RecursionLimit<Seed>,
+    deserializer: D,
+) -> Result<Option<Box<Nested>>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    seed.0 += 1;
+    seed.nest(|seed| Option::<Box<Nested>>::deserialize_state(seed, deserializer))
+}
+
+fn nested_tokens(depth: usize) -> Vec<Token> {
+    let mut tokens = Vec::new();
+    for _ in 0..depth {
+        tokens.push(Token::Struct {
+            name: "Nested",
+            len: 1,
+        });
+        tokens.push(Token::Str("next"));
+        tokens.push(Token::Some);
+    }
+    tokens.push(Token::Struct {
+        name: "Nested",
+        len: 1,
+    });
+    tokens.push(Token::Str("next"));
+    tokens.push(Token::None);
+    for _ in 0..depth + 1 {
+        tokens.push(Token::StructEnd);
+    }
+    tokens
+}
+
+#[test]
+fn test_recursion_limit() {
+    let value = Nested {
+        next: Some(Box::new(Nested { next: None })),
+    };
+    let tokens = nested_tokens(1);
+    let mut seed = RecursionLimit::new(Seed::default(), 2);
+    assert_de_seed_tokens(&mut seed, &value, &tokens);
+
+    assert_eq!(seed.depth(), 0);
+    assert_eq!(seed.0, 2);
+}
+
+#[test]
+fn test_recursion_limit_exceeded() {
+    let tokens = nested_tokens(3);
+    let mut seed = RecursionLimit::new(Seed::default(), 2);
+    let mut de = serde_test::Deserializer::new(&tokens);
+    match Nested::deserialize_state(&mut seed, &mut de) {
+        Ok(_) => panic!("expected the recursion limit to be hit"),
+        Err(err) => assert_eq!(err.to_string(), "recursion limit of 2 exceeded"),
+    }
+
+    assert_eq!(seed.depth(), 0);
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed")]
+struct Attr {
+    #[serde(with = "i32")]
+    x: i32,
+}
+
+#[derive(Debug, PartialEq)]
+struct Even(i32);
+
+impl std::convert::TryFrom<i32> for Even {
+    type Error = String;
+
+    fn try_from(value: i32) -> Result<Self, Self::Error> {
+        if value % 2 == 0 {
+            Ok(Even(value))
+        } else {
+            Err(format!("{} is odd", value))
+        }
+    }
+}
+
+fn deserialize_counted_i32<'de, D>(seed: &mut Seed, deserializer: D) -> Result<i32, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    seed.0 += 1;
+    i32::deserialize(deserializer)
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed")]
+struct TryFromField {
+    #[serde(deserialize_state_with = "deserialize_counted_i32", try_from = "i32")]
+    value: Even,
+}
+
+#[test]
+fn test_deserialize_state_with_try_from() {
+    let mut seed = Seed::default();
+    assert_de_seed_tokens(
+        &mut seed,
+        &TryFromField { value: Even(2) },
+        &[
+            Token::Struct {
+                name: "TryFromField",
+                len: 1,
+            },
+            Token::Str("value"),
+            Token::I32(2),
+            Token::StructEnd,
+        ],
+    );
+
+    assert_eq!(seed.0, 1);
+}
+
+#[test]
+fn test_deserialize_state_with_try_from_error() {
+    let tokens = &[
+        Token::Struct {
+            name: "TryFromField",
+            len: 1,
+        },
+        Token::Str("value"),
+        Token::I32(3),
+    ];
+    let mut seed = Seed::default();
+    let mut de = serde_test::Deserializer::new(tokens);
+    match TryFromField::deserialize_state(&mut seed, &mut de) {
+        Ok(_) => panic!("expected the conversion to fail"),
+        Err(err) => assert_eq!(err.to_string(), "field `value`: 3 is odd"),
+    }
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed", expose_fields)]
+struct ExposedFields {
+    #[serde(deserialize_state)]
+    inner: Inner,
+    #[serde(rename = "other")]
+    plain: i32,
+    #[serde(skip_deserializing)]
+    skipped: i32,
+}
+
+#[test]
+fn test_expose_fields() {
+    assert_eq!(ExposedFields::FIELDS, &["inner", "other"]);
+}
+
+#[derive(Deserialize, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct Key(i32);
+
+impl<'de> DeserializeState<'de, Seed> for Key {
+    fn deserialize_state<D>(seed: &mut Seed, deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        seed.0 += 1;
+        Key::deserialize(deserializer)
+    }
+}
+
+#[test]
+fn test_hash_collections_custom_hasher() {
+    let mut seed = Seed::default();
+    let mut map: HashMap<Key, Key, FnvBuildHasher> = HashMap::default();
+    map.insert(Key(1), Key(2));
+    assert_de_seed_tokens(
+        &mut seed,
+        &map,
+        &[
+            Token::Map { len: Some(1) },
+            Token::NewtypeStruct { name: "Key" },
+            Token::I32(1),
+            Token::NewtypeStruct { name: "Key" },
+            Token::I32(2),
+            Token::MapEnd,
+        ],
+    );
+    assert_eq!(seed.0, 2);
+
+    let mut set: HashSet<Key, FnvBuildHasher> = HashSet::default();
+    set.insert(Key(3));
+    assert_de_seed_tokens(
+        &mut seed,
+        &set,
+        &[
+            Token::Seq { len: Some(1) },
+            Token::NewtypeStruct { name: "Key" },
+            Token::I32(3),
+            Token::SeqEnd,
+        ],
+    );
+    assert_eq!(seed.0, 3);
+}
+
+fn duplicate_key_tokens() -> Vec<Token> {
+    vec![
+        Token::Seq { len: Some(3) },
+        Token::NewtypeStruct { name: "Key" },
+        Token::I32(1),
+        Token::NewtypeStruct { name: "Key" },
+        Token::I32(2),
+        Token::NewtypeStruct { name: "Key" },
+        Token::I32(1),
+        Token::SeqEnd,
+    ]
+}
+
+#[test]
+fn test_btree_set_seed() {
+    let value: BTreeSet<Key> = vec![Key(1), Key(2)].into_iter().collect();
+    let mut seed = Seed::default();
+    assert_de_seed_tokens(&mut seed, &value, &duplicate_key_tokens());
+
+    assert_eq!(seed.0, 3);
+}
+
+#[test]
+fn test_hash_set_seed() {
+    let value: HashSet<Key> = vec![Key(1), Key(2)].into_iter().collect();
+    let mut seed = Seed::default();
+    assert_de_seed_tokens(&mut seed, &value, &duplicate_key_tokens());
+
+    assert_eq!(seed.0, 3);
+}
+
+#[test]
+fn test_seq_seed_capacity_limit() {
+    use serde_state::de::SeqSeedEx;
+
+    let tokens = &[
+        Token::Seq {
+            len: Some(1_000_000),
+        },
+        Token::UnitStruct { name: "Inner" },
+        Token::UnitStruct { name: "Inner" },
+        Token::SeqEnd,
+    ];
+    let requested = Cell::new(0);
+    let mut seed = Seed::default();
+    let mut de = serde_test::Deserializer::new(tokens);
+    let values: Vec<Inner> = SeqSeedEx::with_capacity_limit(
+        &mut seed,
+        |capacity| {
+            requested.set(capacity);
+            Vec::with_capacity(capacity)
+        },
+        16,
+    )
+    .deserialize(&mut de)
+    .unwrap();
+
+    assert_eq!(values, vec![Inner, Inner]);
+    assert_eq!(requested.get(), 16);
+    assert_eq!(seed.0, 2);
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed", transparent)]
+struct TransparentInner {
+    #[serde(deserialize_state)]
+    inner: Inner,
+}
+
+#[test]
+fn test_transparent_deserialize_state() {
+    let mut seed = Seed::default();
+    assert_de_seed_tokens(
+        &mut seed,
+        &TransparentInner { inner: Inner },
+        &[Token::UnitStruct { name: "Inner" }],
+    );
+
+    assert_eq!(seed.0, 1);
+}
+
+mod counted_inner {
+    use super::{Inner, Seed};
+    use serde::de::{Deserialize, Deserializer};
+    use serde::ser::Serializer;
+    use std::cell::Cell;
+
+    pub fn serialize_state<S>(_: &Inner, serializer: S, seed: &Cell<i32>) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        seed.set(seed.get() + 10);
+        serializer.serialize_unit_struct("Inner")
+    }
+
+    pub fn deserialize_state<'de, D>(seed: &mut Seed, deserializer: D) -> Result<Inner, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        seed.0 += 10;
+        Inner::deserialize(deserializer)
+    }
+}
+
+#[derive(SerializeState, DeserializeState, Debug, PartialEq)]
+#[serde(serialize_state = "Cell<i32>", deserialize_state = "Seed")]
+struct StateWithModule {
+    #[serde(state_with = "counted_inner")]
+    inner: Inner,
+}
+
+#[test]
+fn test_state_with_module() {
+    let value = StateWithModule { inner: Inner };
+    let tokens = &[
+        Token::Struct {
+            name: "StateWithModule",
+            len: 1,
+        },
+        Token::Str("inner"),
+        Token::UnitStruct { name: "Inner" },
+        Token::StructEnd,
+    ];
+
+    let ser_seed = Cell::new(0);
+    assert_ser_tokens(&Seeded::new(&ser_seed, &value), tokens);
+    assert_eq!(ser_seed.get(), 10);
+
+    let mut de_seed = Seed::default();
+    assert_de_seed_tokens(&mut de_seed, &value, tokens);
+    assert_eq!(de_seed.0, 10);
+}
+
+#[derive(Default)]
+struct Registry(Vec<String>);
+
+fn register(seed: &mut Registry, value: &mut Registered) {
+    seed.0.push(value.name.clone());
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Registry", deserialize_state_finish = "register")]
+struct Registered {
+    name: String,
+}
+
+#[test]
+fn test_deserialize_state_finish() {
+    let value = vec![
+        Registered {
+            name: "a".to_string(),
+        },
+        Registered {
+            name: "b".to_string(),
+        },
+    ];
+    let mut seed = Registry::default();
+    assert_de_seed_tokens(
+        &mut seed,
+        &value,
+        &[
+            Token::Seq { len: Some(2) },
+            Token::Struct {
+                name: "Registered",
+                len: 1,
+            },
+            Token::Str("name"),
+            Token::Str("a"),
+            Token::StructEnd,
+            Token::Struct {
+                name: "Registered",
+                len: 1,
+            },
+            Token::Str("name"),
+            Token::Str("b"),
+            Token::StructEnd,
+            Token::SeqEnd,
+        ],
+    );
+
+    assert_eq!(seed.0, vec!["a", "b"]);
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed", deserialize_state_by_value)]
+struct ByValue {
+    #[serde(deserialize_state)]
+    first: Inner,
+    #[serde(deserialize_state)]
+    second: Inner,
+}
+
+#[test]
+fn test_deserialize_state_by_value() {
+    let tokens = &[
+        Token::Struct {
+            name: "ByValue",
+            len: 2,
+        },
+        Token::Str("first"),
+        Token::UnitStruct { name: "Inner" },
+        Token::Str("second"),
+        Token::UnitStruct { name: "Inner" },
+        Token::StructEnd,
+    ];
+    let mut de = serde_test::Deserializer::new(tokens);
+    let (value, seed) = ByValue::deserialize_state_by_value(Seed(1), &mut de).unwrap();
+
+    assert_eq!(
+        value,
+        ByValue {
+            first: Inner,
+            second: Inner,
+        }
+    );
+    assert_eq!(seed.0, 3);
+}
+
+#[test]
+fn test_map_value_seed_ex() {
+    use serde_state::de::MapValueSeedEx;
+
+    let tokens = &[
+        Token::Map { len: Some(2) },
+        Token::Str("a"),
+        Token::UnitStruct { name: "Inner" },
+        Token::Str("b"),
+        Token::UnitStruct { name: "Inner" },
+        Token::MapEnd,
+    ];
+    let mut seed = Seed::default();
+    let mut de = serde_test::Deserializer::new(tokens);
+    let map: HashMap<String, Inner> = MapValueSeedEx::new(&mut seed, HashMap::with_capacity)
+        .deserialize(&mut de)
+        .unwrap();
+
+    assert_eq!(map.len(), 2);
+    assert_eq!(map["a"], Inner);
+    assert_eq!(seed.0, 2);
+}
+
+fn seeded_error<'de, T>(tokens: &'de [Token]) -> String
+where
+    T: DeserializeState<'de, Seed> + std::fmt::Debug,
+{
+    let mut seed = Seed::default();
+    let mut de = serde_test::Deserializer::new(tokens);
+    T::deserialize_state(&mut seed, &mut de).unwrap_err().to_string()
+}
+
+#[test]
+fn test_expecting_names_the_seeded_type() {
+    assert_eq!(
+        seeded_error::<SeedStruct>(&[Token::I32(1)]),
+        "invalid type: integer `1`, expected struct SeedStruct"
+    );
+    assert_eq!(
+        seeded_error::<Newtype>(&[Token::I32(1)]),
+        "invalid type: integer `1`, expected tuple struct Newtype"
+    );
+    assert_eq!(
+        seeded_error::<Enum>(&[Token::I32(1)]),
+        "invalid type: integer `1`, expected enum Enum"
+    );
+}
+
+fn deserialize_array<'de, const N: usize, D>(seed: &mut Seed, deserializer: D) -> Result<[u8; N], D::Error>
+where
+    D: Deserializer<'de>,
+{
+    seed.0 += 1;
+    let bytes = Vec::<u8>::deserialize(deserializer)?;
+    std::convert::TryFrom::try_from(&bytes[..])
+        .map_err(|_| D::Error::invalid_length(bytes.len(), &"an array of the requested length"))
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed")]
+struct Buffer<const N: usize> {
+    #[serde(deserialize_state_with = "deserialize_array")]
+    data: [u8; N],
+}
+
+#[test]
+fn test_const_generic_struct() {
+    let mut seed = Seed::default();
+    assert_de_seed_tokens(
+        &mut seed,
+        &Buffer { data: [1, 2] },
+        &[
+            Token::Struct {
+                name: "Buffer",
+                len: 1,
+            },
+            Token::Str("data"),
+            Token::Seq { len: Some(2) },
+            Token::U8(1),
+            Token::U8(2),
+            Token::SeqEnd,
+            Token::StructEnd,
+        ],
+    );
+
+    assert_eq!(seed.0, 1);
+}
+
+struct Padding<const N: usize>;
+
+fn deserialize_padded<'de, const N: usize, D>(
+    _: &mut Padding<N>,
+    deserializer: D,
+) -> Result<Vec<u8>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let mut bytes = Vec::<u8>::deserialize(deserializer)?;
+    bytes.resize(N, 0);
+    Ok(bytes)
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Padding<N>", de_parameters = "const N: usize")]
+struct Padded(#[serde(deserialize_state_with = "deserialize_padded")] Vec<u8>);
+
+#[test]
+fn test_const_generic_de_parameters() {
+    assert_de_seed_tokens(
+        &mut Padding::<3>,
+        &Padded(vec![1, 0, 0]),
+        &[
+            Token::NewtypeStruct { name: "Padded" },
+            Token::Seq { len: Some(1) },
+            Token::U8(1),
+            Token::SeqEnd,
+        ],
+    );
+}
+
+mod shared_field_names {
+    use super::{Counted, Inner, Seed};
+    use serde_state::de::DeserializeState;
+    use serde_test::Token;
+    use std::cell::Cell;
+
+    #[derive(SerializeState, DeserializeState, Debug, PartialEq)]
+    #[serde(serialize_state = "Cell<i32>", deserialize_state = "Seed")]
+    pub struct First {
+        #[serde(state)]
+        value: Counted,
+    }
+
+    #[derive(DeserializeState, Debug, PartialEq)]
+    #[serde(deserialize_state = "Seed", expose_fields)]
+    pub struct Second {
+        #[serde(deserialize_state)]
+        value: Inner,
+    }
+
+    #[test]
+    fn test_derives_in_the_same_module() {
+        let mut seed = Seed::default();
+        let mut de = serde_test::Deserializer::new(&[
+            Token::Struct {
+                name: "Second",
+                len: 1,
+            },
+            Token::Str("value"),
+            Token::UnitStruct { name: "Inner" },
+            Token::StructEnd,
+        ]);
+        let second = Second::deserialize_state(&mut seed, &mut de).unwrap();
+        assert_eq!(second, Second { value: Inner });
+        assert_eq!(Second::FIELDS, &["value"]);
+        assert_eq!(seed.0, 1);
+
+    }
+
+    // Both the serde derives and the state derives expand to a dummy const on
+    // the same type, so their names must not clash.
+    #[derive(Serialize, Deserialize, SerializeState, DeserializeState, Debug, PartialEq)]
+    #[serde_state(serialize_state = "Cell<i32>", deserialize_state = "Seed")]
+    pub struct Both {
+        value: i32,
+    }
+
+    #[test]
+    fn test_serde_and_state_derives_on_one_type() {
+        let tokens = &[
+            Token::Struct {
+                name: "Both",
+                len: 1,
+            },
+            Token::Str("value"),
+            Token::I32(1),
+            Token::StructEnd,
+        ];
+        serde_test::assert_tokens(&Both { value: 1 }, tokens);
+        serde_test::assert_ser_seed_tokens(&Both { value: 1 }, &Cell::new(0), tokens);
+        serde_test::assert_de_seed_tokens(&mut Seed::default(), &Both { value: 1 }, tokens);
+    }
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed")]
+enum Protocol {
+    Known(#[serde(deserialize_state)] Inner),
+    #[serde(other)]
+    Unknown,
+}
+
+#[test]
+fn test_other_variant() {
+    let mut seed = Seed::default();
+    assert_de_seed_tokens(
+        &mut seed,
+        &Protocol::Known(Inner),
+        &[
+            Token::NewtypeVariant {
+                name: "Protocol",
+                variant: "Known",
+            },
+            Token::UnitStruct { name: "Inner" },
+        ],
+    );
+    assert_eq!(seed.0, 1);
+
+    assert_de_seed_tokens(
+        &mut seed,
+        &Protocol::Unknown,
+        &[Token::UnitVariant {
+            name: "Protocol",
+            variant: "Future",
+        }],
+    );
+    assert_eq!(seed.0, 1);
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed")]
+struct SeededUnit;
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed")]
+struct WithUnits {
+    #[serde(deserialize_state)]
+    unit: (),
+    #[serde(deserialize_state)]
+    unit_struct: SeededUnit,
+}
+
+#[test]
+fn test_unit_ignores_seed() {
+    let mut seed = Seed::default();
+    assert_de_seed_tokens(&mut seed, &(), &[Token::Unit]);
+    assert_de_seed_tokens(
+        &mut seed,
+        &WithUnits {
+            unit: (),
+            unit_struct: SeededUnit,
+        },
+        &[
+            Token::Struct {
+                name: "WithUnits",
+                len: 2,
+            },
+            Token::Str("unit"),
+            Token::Unit,
+            Token::Str("unit_struct"),
+            Token::UnitStruct { name: "SeededUnit" },
+            Token::StructEnd,
+        ],
+    );
+    assert_eq!(seed.0, 0);
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed")]
+struct CatchAll {
+    id: i32,
+    #[serde(flatten, deserialize_state)]
+    extra: HashMap<String, Inner>,
+}
+
+#[test]
+fn test_flatten_seeded_map() {
+    let mut extra = HashMap::new();
+    extra.insert("a".to_string(), Inner);
+    extra.insert("b".to_string(), Inner);
+    let value = CatchAll { id: 1, extra: extra };
+
+    let mut seed = Seed::default();
+    assert_de_seed_tokens(
+        &mut seed,
+        &value,
+        &[
+            Token::Map { len: None },
+            Token::Str("a"),
+            Token::UnitStruct { name: "Inner" },
+            Token::Str("id"),
+            Token::I32(1),
+            Token::Str("b"),
+            Token::UnitStruct { name: "Inner" },
+            Token::MapEnd,
+        ],
+    );
+    assert_eq!(seed.0, 2);
+
+    assert_de_seed_tokens(
+        &mut seed,
+        &value,
+        &[
+            Token::Map { len: None },
+            Token::Str("id"),
+            Token::I32(1),
+            Token::Str("b"),
+            Token::UnitStruct { name: "Inner" },
+            Token::Str("a"),
+            Token::UnitStruct { name: "Inner" },
+            Token::MapEnd,
+        ],
+    );
+    assert_eq!(seed.0, 4);
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "S", de_parameters = "S")]
+#[serde(bound(deserialize = "S: AsMut<Seed> + Send"))]
+struct ThreadDoc {
+    #[serde(deserialize_state_with = "deserialize_inner")]
+    inner: Inner,
+}
+
+const THREAD_DOC_TOKENS: &'static [Token] = &[
+    Token::Struct {
+        name: "ThreadDoc",
+        len: 1,
+    },
+    Token::Str("inner"),
+    Token::UnitStruct { name: "Inner" },
+    Token::StructEnd,
+];
+
+fn deserialize_on_thread<S>(mut seed: S) -> std::thread::JoinHandle<(ThreadDoc, S)>
+where
+    S: AsMut<Seed> + Send + 'static,
+{
+    std::thread::spawn(move || {
+        let mut de = serde_test::Deserializer::new(THREAD_DOC_TOKENS);
+        let doc = ThreadDoc::deserialize_state(&mut seed, &mut de).unwrap();
+        (doc, seed)
+    })
+}
+
+#[test]
+fn test_send_seed_bound() {
+    let first = deserialize_on_thread(Seed(0));
+    let second = deserialize_on_thread(Seed(10));
+
+    let (doc, seed) = first.join().unwrap();
+    assert_eq!(doc, ThreadDoc { inner: Inner });
+    assert_eq!(seed.0, 1);
+
+    let (doc, seed) = second.join().unwrap();
+    assert_eq!(doc, ThreadDoc { inner: Inner });
+    assert_eq!(seed.0, 11);
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed")]
+#[serde(rename_all = "camelCase")]
+enum GraphEntry {
+    #[serde(rename_all = "camelCase")]
+    MarkedNode {
+        node_id: u32,
+        #[serde(deserialize_state)]
+        node_value: Inner,
+    },
+    Reference(u32),
+}
+
+#[test]
+fn test_rename_all_seeded_struct_variant() {
+    let mut seed = Seed::default();
+    assert_de_seed_tokens(
+        &mut seed,
+        &GraphEntry::MarkedNode {
+            node_id: 1,
+            node_value: Inner,
+        },
+        &[
+            Token::StructVariant {
+                name: "GraphEntry",
+                variant: "markedNode",
+                len: 2,
+            },
+            Token::Str("nodeId"),
+            Token::U32(1),
+            Token::Str("nodeValue"),
+            Token::UnitStruct { name: "Inner" },
+            Token::StructVariantEnd,
+        ],
+    );
+
+    assert_eq!(seed.0, 1);
+}
+
+impl Seed {
+    fn decode_inner<'de, D>(&mut self, deserializer: D) -> Result<Inner, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        self.0 += 10;
+        Inner::deserialize(deserializer)
+    }
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed")]
+struct SeedMethod {
+    #[serde(deserialize_state_with = "Seed::decode_inner")]
+    inner: Inner,
+}
+
+#[test]
+fn test_deserialize_state_with_seed_method() {
+    let mut seed = Seed::default();
+    assert_de_seed_tokens(
+        &mut seed,
+        &SeedMethod { inner: Inner },
+        &[
+            Token::Struct {
+                name: "SeedMethod",
+                len: 1,
+            },
+            Token::Str("inner"),
+            Token::UnitStruct { name: "Inner" },
+            Token::StructEnd,
+        ],
+    );
+
+    assert_eq!(seed.0, 10);
+}
+
+struct Minimum(i32);
+
+fn at_least_minimum(seed: &mut Minimum, value: &i32) -> Result<(), String> {
+    if *value >= seed.0 {
+        Ok(())
+    } else {
+        Err(format!("{} is below the minimum of {}", value, seed.0))
+    }
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Minimum")]
+struct ValidatedAttr {
+    #[serde(deserialize_state_validate = "at_least_minimum")]
+    x: i32,
+}
+
+#[test]
+fn test_deserialize_state_validate() {
+    let tokens = &[
+        Token::Struct {
+            name: "ValidatedAttr",
+            len: 1,
+        },
+        Token::Str("x"),
+        Token::I32(-1),
+        Token::StructEnd,
+    ];
+
+    assert_de_seed_tokens(&mut Minimum(-1), &ValidatedAttr { x: -1 }, tokens);
+    assert_de_seed_tokens_error::<_, ValidatedAttr>(
+        &mut Minimum(0),
+        tokens,
+        "field `x`: -1 is below the minimum of 0",
+    );
+}
+
+#[test]
+fn test_from_deserializer_seed() {
+    use serde_state::de::{from_deserializer_seed, value};
+
+    let mut seed = Seed::default();
+    let values: Result<Vec<Inner>, value::Error> = from_deserializer_seed(&mut seed, vec![(), ()]);
+    assert_eq!(values.unwrap(), [Inner, Inner]);
+    assert_eq!(seed.0, 2);
+}
+
+fn deserialize_entries<'de, D>(
+    seed: &mut Seed,
+    deserializer: D,
+) -> Result<Vec<(String, Key)>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    use serde_state::de::MapSeedEx;
+    deserializer.deserialize_map(MapSeedEx::new(seed, Vec::with_capacity))
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed")]
+struct OrderedEntries(#[serde(deserialize_state_with = "deserialize_entries")] Vec<(String, Key)>);
+
+#[test]
+fn test_map_seed_ex_preserves_order() {
+    let mut seed = Seed::default();
+    assert_de_seed_tokens(
+        &mut seed,
+        &OrderedEntries(vec![
+            ("b".to_string(), Key(1)),
+            ("a".to_string(), Key(2)),
+            ("b".to_string(), Key(3)),
+        ]),
+        &[
+            Token::NewtypeStruct {
+                name: "OrderedEntries",
+            },
+            Token::Map { len: Some(3) },
+            Token::Str("b"),
+            Token::NewtypeStruct { name: "Key" },
+            Token::I32(1),
+            Token::Str("a"),
+            Token::NewtypeStruct { name: "Key" },
+            Token::I32(2),
+            Token::Str("b"),
+            Token::NewtypeStruct { name: "Key" },
+            Token::I32(3),
+            Token::MapEnd,
+        ],
+    );
+
+    assert_eq!(seed.0, 3);
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed")]
+#[serde(tag = "type")]
+enum Tagged {
+    Node {
+        #[serde(deserialize_state)]
+        inner: Inner,
+    },
+    Empty,
+}
+
+#[test]
+fn test_internally_tagged_unit_variant() {
+    let mut seed = Seed::default();
+    assert_de_seed_tokens(
+        &mut seed,
+        &Tagged::Node { inner: Inner },
+        &[
+            Token::Struct {
+                name: "Tagged",
+                len: 2,
+            },
+            Token::Str("type"),
+            Token::Str("Node"),
+            Token::Str("inner"),
+            Token::UnitStruct { name: "Inner" },
+            Token::StructEnd,
+        ],
+    );
+    assert_eq!(seed.0, 1);
+
+    assert_de_seed_tokens(
+        &mut seed,
+        &Tagged::Empty,
+        &[
+            Token::Struct {
+                name: "Tagged",
+                len: 1,
+            },
+            Token::Str("type"),
+            Token::Str("Empty"),
+            Token::StructEnd,
+        ],
+    );
+    assert_eq!(seed.0, 1);
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed")]
+struct FlattenTagged {
+    id: i32,
+    #[serde(flatten, deserialize_state)]
+    tagged: Tagged,
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed")]
+struct FlattenExternal {
+    id: i32,
+    #[serde(flatten, deserialize_state)]
+    external: Enum,
+}
+
+#[test]
+fn test_flatten_seeded_enum() {
+    let mut seed = Seed::default();
+    assert_de_seed_tokens(
+        &mut seed,
+        &FlattenTagged {
+            id: 1,
+            tagged: Tagged::Node { inner: Inner },
+        },
+        &[
+            Token::Map { len: None },
+            Token::Str("inner"),
+            Token::UnitStruct { name: "Inner" },
+            Token::Str("id"),
+            Token::I32(1),
+            Token::Str("type"),
+            Token::Str("Node"),
+            Token::MapEnd,
+        ],
+    );
+    assert_eq!(seed.0, 1);
+
+    assert_de_seed_tokens(
+        &mut seed,
+        &FlattenExternal {
+            id: 2,
+            external: Enum::Inner(Inner),
+        },
+        &[
+            Token::Map { len: None },
+            Token::Str("id"),
+            Token::I32(2),
+            Token::Str("Inner"),
+            Token::UnitStruct { name: "Inner" },
+            Token::MapEnd,
+        ],
+    );
+    assert_eq!(seed.0, 2);
+}
+
+#[derive(Default)]
+struct Log(Vec<i32>);
+
+#[derive(Debug, PartialEq)]
+struct Logged(i32);
+
+impl<'de> DeserializeState<'de, Log> for Logged {
+    fn deserialize_state<D>(seed: &mut Log, deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let value = i32::deserialize(deserializer)?;
+        seed.0.push(value);
+        Ok(Logged(value))
+    }
+}
+
+// Fields that arrive ahead of a seeded field declared before them are buffered, which asks
+// whether the input is human readable
+fn assert_de_log_tokens<'de, T>(seed: &mut Log, value: &T, tokens: &'de [Token])
+where
+    T: DeserializeState<'de, Log> + PartialEq + std::fmt::Debug,
+{
+    let mut de = serde_test::Deserializer::new(tokens);
+    let deserialized = serde_state::de::Seed::<_, T>::new(seed)
+        .readable()
+        .deserialize(&mut de)
+        .unwrap();
+    assert_eq!(deserialized, *value);
+    assert_eq!(de.remaining(), 0);
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Log")]
+struct LogOrder {
+    #[serde(deserialize_state)]
+    first: Logged,
+    plain: i32,
+    #[serde(deserialize_state)]
+    second: Logged,
+    #[serde(deserialize_state)]
+    third: Logged,
+}
+
+#[test]
+fn test_seed_mutated_in_declaration_order() {
+    let value = LogOrder {
+        first: Logged(1),
+        plain: 0,
+        second: Logged(2),
+        third: Logged(3),
+    };
+    let field = |name, value| vec![Token::Str(name), Token::I32(value)];
+    let orders = [
+        ["first", "plain", "second", "third"],
+        ["third", "second", "plain", "first"],
+        ["second", "first", "third", "plain"],
+        ["plain", "third", "first", "second"],
+    ];
+    for order in &orders {
+        let mut tokens = vec![Token::Map { len: Some(4) }];
+        for &name in order {
+            let n = match name {
+                "first" => 1,
+                "second" => 2,
+                "third" => 3,
+                _ => 0,
+            };
+            tokens.extend(field(name, n));
+        }
+        tokens.push(Token::MapEnd);
+
+        let mut seed = Log::default();
+        assert_de_log_tokens(&mut seed, &value, &tokens);
+        assert_eq!(seed.0, [1, 2, 3], "keys in order {:?}", order);
+    }
+}
+
+// A field which only exists to run a side effect on the seed. The value in the input is consumed
+// and dropped, and the side effect runs in declaration order like any other seeded field.
+fn log_marker<'de, D>(seed: &mut Log, deserializer: D) -> Result<(), D::Error>
+where
+    D: Deserializer<'de>,
+{
+    serde::de::IgnoredAny::deserialize(deserializer)?;
+    seed.0.push(0);
+    Ok(())
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Log")]
+struct LogMarker {
+    #[serde(deserialize_state)]
+    first: Logged,
+    #[serde(deserialize_state_with = "log_marker")]
+    marker: (),
+    #[serde(deserialize_state)]
+    second: Logged,
+}
+
+#[test]
+fn test_unit_field_seed_side_effect() {
+    let value = LogMarker {
+        first: Logged(1),
+        marker: (),
+        second: Logged(2),
+    };
+    let orders = [
+        ["first", "marker", "second"],
+        ["second", "marker", "first"],
+        ["marker", "second", "first"],
+    ];
+    for order in &orders {
+        let mut tokens = vec![Token::Map { len: Some(3) }];
+        for &name in order {
+            tokens.push(Token::Str(name));
+            tokens.push(match name {
+                "first" => Token::I32(1),
+                "second" => Token::I32(2),
+                _ => Token::Str("ignored"),
+            });
+        }
+        tokens.push(Token::MapEnd);
+
+        let mut seed = Log::default();
+        assert_de_log_tokens(&mut seed, &value, &tokens);
+        assert_eq!(seed.0, [1, 0, 2], "keys in order {:?}", order);
+    }
+
+    let mut seed = Log::default();
+    assert_de_seed_tokens(
+        &mut seed,
+        &value,
+        &[
+            Token::Seq { len: Some(3) },
+            Token::I32(1),
+            Token::Unit,
+            Token::I32(2),
+            Token::SeqEnd,
+        ],
+    );
+    assert_eq!(seed.0, [1, 0, 2]);
+}
+
+fn log_default(seed: &mut Log) -> i32 {
+    seed.0.push(0);
+    0
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Log")]
+struct LogDefault {
+    #[serde(deserialize_state)]
+    first: Logged,
+    #[serde(default_state = "log_default")]
+    missing: i32,
+    #[serde(deserialize_state)]
+    second: Logged,
+}
+
+#[test]
+fn test_default_state_in_declaration_order() {
+    let value = LogDefault {
+        first: Logged(1),
+        missing: 0,
+        second: Logged(2),
+    };
+    let orders = [["first", "second"], ["second", "first"]];
+    for order in &orders {
+        let mut tokens = vec![Token::Map { len: Some(2) }];
+        for &name in order {
+            tokens.push(Token::Str(name));
+            tokens.push(Token::I32(if name == "first" { 1 } else { 2 }));
+        }
+        tokens.push(Token::MapEnd);
+
+        let mut seed = Log::default();
+        assert_de_log_tokens(&mut seed, &value, &tokens);
+        assert_eq!(seed.0, [1, 0, 2], "keys in order {:?}", order);
+    }
+}
+
+// Records whether each deserializer it is given is human readable
+#[derive(Default)]
+struct Readability(Vec<bool>);
+
+#[derive(Debug, PartialEq)]
+struct Probe;
+
+impl<'de> DeserializeState<'de, Readability> for Probe {
+    fn deserialize_state<D>(seed: &mut Readability, deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        seed.0.push(deserializer.is_human_readable());
+        <()>::deserialize(deserializer)?;
+        Ok(Probe)
+    }
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Readability")]
+struct Probes {
+    #[serde(deserialize_state)]
+    first: Probe,
+    #[serde(deserialize_state)]
+    second: Probe,
+}
+
+#[test]
+fn test_buffered_field_is_human_readable_like_the_input() {
+    // `second` arrives first, so it is buffered and replayed after `first`
+    let tokens = [
+        Token::Map { len: Some(2) },
+        Token::Str("second"),
+        Token::Unit,
+        Token::Str("first"),
+        Token::Unit,
+        Token::MapEnd,
+    ];
+    for &readable in &[true, false] {
+        let mut seed = Readability::default();
+        let mut de = serde_test::Deserializer::new(&tokens);
+        let probes = serde_state::de::Seed::<_, Probes>::new(&mut seed);
+        let deserialized = if readable {
+            probes.readable().deserialize(&mut de)
+        } else {
+            probes.compact().deserialize(&mut de)
+        };
+        assert_eq!(
+            deserialized.unwrap(),
+            Probes {
+                first: Probe,
+                second: Probe
+            }
+        );
+        assert_eq!(seed.0, [readable, readable]);
+    }
+}
+
+#[test]
+fn test_boxed_slice_and_str() {
+    let mut seed = Seed::default();
+    let value: Box<[Key]> = vec![Key(1), Key(2)].into_boxed_slice();
+    assert_de_seed_tokens(
+        &mut seed,
+        &value,
+        &[
+            Token::Seq { len: Some(2) },
+            Token::NewtypeStruct { name: "Key" },
+            Token::I32(1),
+            Token::NewtypeStruct { name: "Key" },
+            Token::I32(2),
+            Token::SeqEnd,
+        ],
+    );
+    assert_eq!(seed.0, 2);
+
+    let value: Box<str> = "boxed".to_string().into_boxed_str();
+    assert_de_seed_tokens(&mut seed, &value, &[Token::Str("boxed")]);
+    assert_eq!(seed.0, 2);
+}
+
+fn deserialize_counting_unit<'de, D>(mut deserializer: D) -> Result<(), D::Error>
+where
+    D: Deserializer<'de> + serde_state::de::SeedAccess<Seed>,
+{
+    deserializer.seed().0 += 1;
+    <()>::deserialize(deserializer)
+}
+
+#[test]
+fn test_stateful_deserializer() {
+    use serde_state::de::StatefulDeserializer;
+
+    let mut seed = Seed::default();
+    let tokens = [Token::Unit];
+    let mut de = serde_test::Deserializer::new(&tokens);
+    deserialize_counting_unit(StatefulDeserializer::new(&mut de, &mut seed)).unwrap();
+    assert_eq!(de.remaining(), 0);
+    assert_eq!(seed.0, 1);
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed")]
+struct InPlaceKeys {
+    #[serde(deserialize_state)]
+    keys: Vec<Key>,
+    #[serde(deserialize_state)]
+    inner: Inner,
+    name: String,
+}
+
+#[test]
+fn test_deserialize_state_in_place() {
+    let tokens = &[
+        Token::Struct {
+            name: "InPlaceKeys",
+            len: 3,
+        },
+        Token::Str("name"),
+        Token::Str("reused"),
+        Token::Str("keys"),
+        Token::Seq { len: Some(2) },
+        Token::NewtypeStruct { name: "Key" },
+        Token::I32(1),
+        Token::NewtypeStruct { name: "Key" },
+        Token::I32(2),
+        Token::SeqEnd,
+        Token::Str("inner"),
+        Token::Unit,
+        Token::StructEnd,
+    ];
+
+    let mut place = InPlaceKeys {
+        keys: Vec::with_capacity(16),
+        inner: Inner,
+        name: String::new(),
+    };
+    place.keys.push(Key(0));
+    let keys_ptr = place.keys.as_ptr();
+
+    let mut seed = Seed::default();
+    let mut de = serde_test::Deserializer::new(tokens);
+    InPlaceKeys::deserialize_state_in_place(&mut seed, &mut de, &mut place).unwrap();
+    assert_eq!(de.remaining(), 0);
+    assert_eq!(
+        place,
+        InPlaceKeys {
+            keys: vec![Key(1), Key(2)],
+            inner: Inner,
+            name: "reused".to_string(),
+        }
+    );
+    assert_eq!(place.keys.as_ptr(), keys_ptr);
+    assert_eq!(seed.0, 3);
+
+    let tokens = &[
+        Token::Seq { len: Some(3) },
+        Token::Seq { len: Some(1) },
+        Token::NewtypeStruct { name: "Key" },
+        Token::I32(3),
+        Token::SeqEnd,
+        Token::Unit,
+        Token::Str("seq"),
+        Token::SeqEnd,
+    ];
+    let mut de = serde_test::Deserializer::new(tokens);
+    InPlaceKeys::deserialize_state_in_place(&mut seed, &mut de, &mut place).unwrap();
+    assert_eq!(place.keys, vec![Key(3)]);
+    assert_eq!(place.keys.as_ptr(), keys_ptr);
+    assert_eq!(place.name, "seq");
+    assert_eq!(seed.0, 5);
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed")]
+#[serde(bound(deserialize = "T: DeserializeState<'de, Seed>"))]
+struct WhereClauseWrapper<T>
+where
+    T: PartialEq,
+{
+    #[serde(deserialize_state)]
+    values: Vec<T>,
+    #[serde(deserialize_state)]
+    inner: Inner,
+}
+
+#[test]
+fn test_deserialize_state_with_where_clause() {
+    let value = WhereClauseWrapper {
+        values: vec![Counted, Counted],
+        inner: Inner,
+    };
+    let mut seed = Seed::default();
+    assert_de_seed_tokens(
+        &mut seed,
+        &value,
+        &[
+            Token::Struct {
+                name: "WhereClauseWrapper",
+                len: 2,
+            },
+            Token::Str("values"),
+            Token::Seq { len: Some(2) },
+            Token::Unit,
+            Token::Unit,
+            Token::SeqEnd,
+            Token::Str("inner"),
+            Token::Unit,
+            Token::StructEnd,
+        ],
+    );
+    assert_eq!(seed.0, 3);
+}
+
+fn serialize_counted<S>(value: &Counted, serializer: S, seed: &Cell<i32>) -> Result<S::Ok, S::Error>
+where
+    S: Serializer,
+{
+    value.serialize_state(serializer, seed)
+}
+
+fn deserialize_counted<'de, D>(seed: &mut Seed, deserializer: D) -> Result<Counted, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    Counted::deserialize_state(seed, deserializer)
+}
+
+#[derive(SerializeState, DeserializeState, Debug, PartialEq)]
+#[serde(serialize_state = "Cell<i32>", deserialize_state = "Seed")]
+enum SeededVariants {
+    Unit,
+    Newtype(#[serde(state)] Counted),
+    Tuple(
+        u32,
+        #[serde(
+            serialize_state_with = "serialize_counted",
+            deserialize_state_with = "deserialize_counted"
+        )]
+        Counted,
+    ),
+    Struct {
+        plain: u32,
+        #[serde(
+            serialize_state_with = "serialize_counted",
+            deserialize_state_with = "deserialize_counted"
+        )]
+        value: Counted,
+    },
+}
+
+fn assert_seeded_variant(value: &SeededVariants, tokens: &[Token], count: i32) {
+    let ser_seed = Cell::new(0);
+    assert_ser_tokens(&Seeded::new(&ser_seed, value), tokens);
+    assert_eq!(ser_seed.get(), count);
+
+    let mut de_seed = Seed::default();
+    assert_de_seed_tokens(&mut de_seed, value, tokens);
+    assert_eq!(de_seed.0, count);
+}
+
+#[test]
+fn test_seeded_enum_variants_round_trip() {
+    assert_seeded_variant(
+        &SeededVariants::Unit,
+        &[Token::UnitVariant {
+            name: "SeededVariants",
+            variant: "Unit",
+        }],
+        0,
+    );
+    assert_seeded_variant(
+        &SeededVariants::Newtype(Counted),
+        &[
+            Token::NewtypeVariant {
+                name: "SeededVariants",
+                variant: "Newtype",
+            },
+            Token::Unit,
+        ],
+        1,
+    );
+    assert_seeded_variant(
+        &SeededVariants::Tuple(3, Counted),
+        &[
+            Token::TupleVariant {
+                name: "SeededVariants",
+                variant: "Tuple",
+                len: 2,
+            },
+            Token::U32(3),
+            Token::Unit,
+            Token::TupleVariantEnd,
+        ],
+        1,
+    );
+    assert_seeded_variant(
+        &SeededVariants::Struct {
+            plain: 3,
+            value: Counted,
+        },
+        &[
+            Token::StructVariant {
+                name: "SeededVariants",
+                variant: "Struct",
+                len: 2,
+            },
+            Token::Str("plain"),
+            Token::U32(3),
+            Token::Str("value"),
+            Token::Unit,
+            Token::StructVariantEnd,
+        ],
+        1,
+    );
+}
+
+struct IdTable(Vec<&'static str>);
+
+struct UnknownId(u32);
+
+impl std::fmt::Display for UnknownId {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "unknown id {}", self.0)
+    }
+}
+
+impl StateContext for IdTable {
+    type Error = UnknownId;
+}
+
+impl IdTable {
+    fn resolve(&self, id: u32) -> Result<&'static str, UnknownId> {
+        self.0.get(id as usize).cloned().ok_or(UnknownId(id))
+    }
+}
+
+fn deserialize_resolved<'de, D>(seed: &mut IdTable, deserializer: D) -> Result<String, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let id = u32::deserialize(deserializer)?;
+    seed.resolve(id)
+        .map(str::to_string)
+        .map_err(state_error::<IdTable, _>)
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "IdTable")]
+struct Resolved {
+    #[serde(deserialize_state_with = "deserialize_resolved")]
+    name: String,
+}
+
+#[test]
+fn test_state_context_error() {
+    let mut seed = IdTable(vec!["first", "second"]);
+    assert_de_seed_tokens(
+        &mut seed,
+        &Resolved {
+            name: "second".to_string(),
+        },
+        &[
+            Token::Struct {
+                name: "Resolved",
+                len: 1,
+            },
+            Token::Str("name"),
+            Token::U32(1),
+            Token::StructEnd,
+        ],
+    );
+    assert_de_seed_tokens_error::<_, Resolved>(
+        &mut seed,
+        &[
+            Token::Struct {
+                name: "Resolved",
+                len: 1,
+            },
+            Token::Str("name"),
+            Token::U32(3),
+        ],
+        "field `name`: unknown id 3",
+    );
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed")]
+struct AliasedSeeded {
+    #[serde(deserialize_state, alias = "old_value")]
+    value: Counted,
+}
+
+#[test]
+fn test_deserialize_state_alias() {
+    let value = AliasedSeeded { value: Counted };
+    let mut seed = Seed::default();
+    for name in &["value", "old_value"] {
+        assert_de_seed_tokens(
+            &mut seed,
+            &value,
+            &[
+                Token::Struct {
+                    name: "AliasedSeeded",
+                    len: 1,
+                },
+                Token::Str(*name),
+                Token::Unit,
+                Token::StructEnd,
+            ],
+        );
+    }
+    assert_eq!(seed.0, 2);
+}
+
+trait Plugin {
+    fn describe(&self) -> String;
+}
+
+impl Plugin for Counted {
+    fn describe(&self) -> String {
+        "counted".to_string()
+    }
+}
+
+impl Plugin for Key {
+    fn describe(&self) -> String {
+        format!("key {}", self.0)
+    }
+}
+
+type PluginSeed<'a, 'de> = Box<
+    dyn serde_state::de::DeserializeStateSeed<
+            'de,
+            Seed,
+            &'a mut serde_test::Deserializer<'de>,
+            Value = Box<dyn Plugin>,
+        > + 'a,
+>;
+
+fn plugin_seed<'a, 'de, T>() -> PluginSeed<'a, 'de>
+where
+    T: DeserializeState<'de, Seed> + Plugin + 'static,
+{
+    Box::new(
+        |seed: &mut Seed, deserializer: &'a mut serde_test::Deserializer<'de>| {
+            T::deserialize_state(seed, deserializer).map(|value| Box::new(value) as Box<dyn Plugin>)
+        },
+    )
+}
+
+#[test]
+fn test_dynamic_deserialize_state_seed() {
+    let key_tokens = [Token::NewtypeStruct { name: "Key" }, Token::I32(4)];
+    let mut key_de = serde_test::Deserializer::new(&key_tokens);
+    let counted_tokens = [Token::Unit];
+    let mut counted_de = serde_test::Deserializer::new(&counted_tokens);
+
+    // The registry holds the deserializer type, so it must be dropped before the deserializers
+    let mut registry = HashMap::new();
+    registry.insert("counted", plugin_seed::<Counted>());
+    registry.insert("key", plugin_seed::<Key>());
+
+    let mut seed = Seed::default();
+
+    let plugin = registry["key"]
+        .deserialize_state_seed(&mut seed, &mut key_de)
+        .unwrap();
+    assert_eq!(plugin.describe(), "key 4");
+
+    let plugin = registry["counted"]
+        .deserialize_state_seed(&mut seed, &mut counted_de)
+        .unwrap();
+    assert_eq!(plugin.describe(), "counted");
+
+    assert_eq!(seed.0, 2);
+}
+
+#[derive(Default)]
+struct Journal(Vec<u32>);
+
+impl serde_state::de::Checkpoint for Journal {
+    type Checkpoint = usize;
+
+    fn checkpoint(&self) -> usize {
+        self.0.len()
+    }
+
+    fn restore(&mut self, len: usize) {
+        self.0.truncate(len);
+    }
+}
+
+fn record<'de, D>(seed: &mut Journal, deserializer: D) -> Result<u32, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = u32::deserialize(deserializer)?;
+    seed.0.push(value);
+    Ok(value)
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Journal", deserialize_state_checkpoint, untagged)]
+enum Speculative {
+    Pair {
+        #[serde(deserialize_state_with = "record")]
+        first: u32,
+        second: u32,
+    },
+    Single {
+        #[serde(deserialize_state_with = "record")]
+        first: u32,
+    },
+}
+
+#[test]
+fn test_untagged_checkpoint() {
+    let mut seed = Journal::default();
+    assert_de_seed_tokens(
+        &mut seed,
+        &Speculative::Single { first: 1 },
+        &[
+            Token::Map { len: Some(1) },
+            Token::Str("first"),
+            Token::U32(1),
+            Token::MapEnd,
+        ],
+    );
+    assert_eq!(seed.0, vec![1]);
+}
+
+struct Buffering {
+    buffered: usize,
+    max_len: usize,
+}
+
+const COORDINATES: &[&str] = &["x", "y", "z"];
+
+// Buffers a map of named coordinates without allocating a `String` for each key, as the default
+// buffer does for keys which are not borrowed from the input
+struct Coordinates<'de>(Vec<(&'de str, u64)>);
+
+struct CoordinateName(&'static str);
+
+impl<'de> Deserialize<'de> for CoordinateName {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        struct NameVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for NameVisitor {
+            type Value = CoordinateName;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a coordinate")
+            }
+
+            fn visit_str<E>(self, name: &str) -> Result<CoordinateName, E>
+            where
+                E: Error,
+            {
+                match COORDINATES.iter().find(|&&known| known == name) {
+                    Some(known) => Ok(CoordinateName(known)),
+                    None => Err(E::unknown_field(name, COORDINATES)),
+                }
+            }
+        }
+
+        deserializer.deserialize_identifier(NameVisitor)
+    }
+}
+
+struct CoordinatesVisitor {
+    max_len: usize,
+}
+
+impl<'de> serde::de::Visitor<'de> for CoordinatesVisitor {
+    type Value = Coordinates<'de>;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a map of coordinates")
+    }
+
+    fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+    where
+        A: serde::de::MapAccess<'de>,
+    {
+        let mut coordinates = Vec::with_capacity(self.max_len);
+        while let Some(CoordinateName(name)) = map.next_key()? {
+            if coordinates.len() == self.max_len {
+                return Err(A::Error::custom(format_args!(
+                    "more than {} coordinates",
+                    self.max_len
+                )));
+            }
+            coordinates.push((name, map.next_value()?));
+        }
+        Ok(Coordinates(coordinates))
+    }
+}
+
+impl<'a, 'de, E> serde::de::IntoDeserializer<'de, E> for &'a Coordinates<'de>
+where
+    E: Error,
+{
+    type Deserializer = serde::de::value::MapDeserializer<
+        'de,
+        std::iter::Cloned<std::slice::Iter<'a, (&'de str, u64)>>,
+        E,
+    >;
+
+    fn into_deserializer(self) -> Self::Deserializer {
+        serde::de::value::MapDeserializer::new(self.0.iter().cloned())
+    }
+}
+
+// Refuses to hold on to more coordinates than the seed allows
+fn buffer_coordinates<'de, D>(
+    seed: &mut Buffering,
+    deserializer: D,
+) -> Result<Coordinates<'de>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let coordinates = deserializer.deserialize_map(CoordinatesVisitor {
+        max_len: seed.max_len,
+    })?;
+    seed.buffered += 1;
+    Ok(coordinates)
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(
+    deserialize_state = "Buffering",
+    untagged,
+    untagged_buffer = "buffer_coordinates"
+)]
+enum Point {
+    Three { x: u64, y: u64, z: u64 },
+    Two { x: u64, y: u64 },
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Buffering", untagged)]
+enum DefaultBufferPoint {
+    Three { x: u64, y: u64, z: u64 },
+    Two { x: u64, y: u64 },
+}
+
+fn point_tokens(coordinates: &[(&'static str, u64)]) -> Vec<Token> {
+    let mut tokens = vec![Token::Map {
+        len: Some(coordinates.len()),
+    }];
+    for &(name, value) in coordinates {
+        tokens.push(Token::Str(name));
+        tokens.push(Token::U64(value));
+    }
+    tokens.push(Token::MapEnd);
+    tokens
+}
+
+#[test]
+fn test_untagged_buffer() {
+    let mut seed = Buffering {
+        buffered: 0,
+        max_len: 3,
+    };
+    assert_de_seed_tokens(
+        &mut seed,
+        &Point::Three { x: 1, y: 2, z: 3 },
+        &point_tokens(&[("x", 1), ("y", 2), ("z", 3)]),
+    );
+    assert_de_seed_tokens(
+        &mut seed,
+        &Point::Two { x: 1, y: 2 },
+        &point_tokens(&[("x", 1), ("y", 2)]),
+    );
+    // The input is buffered once however many variants are tried against it
+    assert_eq!(seed.buffered, 2);
+
+    seed.max_len = 2;
+    assert_de_seed_tokens_error::<_, Point>(
+        &mut seed,
+        &point_tokens(&[("x", 1), ("y", 2), ("z", 3)]),
+        "more than 2 coordinates",
+    );
+    assert_eq!(seed.buffered, 2);
+}
+
+mod allocations {
+    use std::alloc::{GlobalAlloc, Layout, System};
+    use std::cell::Cell;
+
+    thread_local!(static ALLOCATIONS: Cell<usize> = Cell::new(0));
+
+    struct Counting;
+
+    unsafe impl GlobalAlloc for Counting {
+        unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+            let _ = ALLOCATIONS.try_with(|count| count.set(count.get() + 1));
+            System.alloc(layout)
+        }
+
+        unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+            System.dealloc(ptr, layout)
+        }
+    }
+
+    #[global_allocator]
+    static GLOBAL: Counting = Counting;
+
+    // The number of allocations `f` makes on the current thread
+    pub fn count<F>(f: F) -> usize
+    where
+        F: FnOnce(),
+    {
+        let before = ALLOCATIONS.with(Cell::get);
+        f();
+        ALLOCATIONS.with(Cell::get) - before
+    }
+}
+
+#[test]
+fn test_untagged_buffer_allocations() {
+    let tokens = point_tokens(&[("x", 1), ("y", 2), ("z", 3)]);
+    let mut seed = Buffering {
+        buffered: 0,
+        max_len: 3,
+    };
+
+    let default_buffer = allocations::count(|| {
+        let mut de = serde_test::Deserializer::new(&tokens);
+        let point = DefaultBufferPoint::deserialize_state(&mut seed, &mut de).unwrap();
+        assert_eq!(point, DefaultBufferPoint::Three { x: 1, y: 2, z: 3 });
+    });
+    let custom_buffer = allocations::count(|| {
+        let mut de = serde_test::Deserializer::new(&tokens);
+        let point = Point::deserialize_state(&mut seed, &mut de).unwrap();
+        assert_eq!(point, Point::Three { x: 1, y: 2, z: 3 });
+    });
+    // The default buffer allocates a `String` for each of the three keys on top of the map
+    assert!(
+        custom_buffer + 3 <= default_buffer,
+        "{} allocations with the custom buffer, {} with the default one",
+        custom_buffer,
+        default_buffer
+    );
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Journal", deserialize_state_checkpoint)]
+struct Narrowed {
+    #[serde(deserialize_state_with = "record", try_from = "u32")]
+    small: u8,
+}
+
+#[test]
+fn test_try_from_checkpoint() {
+    let mut seed = Journal::default();
+    let tokens = |value| {
+        vec![
+            Token::Struct {
+                name: "Narrowed",
+                len: 1,
+            },
+            Token::Str("small"),
+            Token::U32(value),
+            Token::StructEnd,
+        ]
+    };
+    assert_de_seed_tokens(&mut seed, &Narrowed { small: 7 }, &tokens(7));
+    assert_eq!(seed.0, vec![7]);
+
+    assert_de_seed_tokens_error::<_, Narrowed>(
+        &mut seed,
+        &tokens(300)[..3],
+        "field `small`: out of range integral type conversion attempted",
+    );
+    assert_eq!(seed.0, vec![7]);
+}
+
+#[test]
+fn test_range_deserialize_state() {
+    let mut seed = Seed::default();
+    assert_de_seed_tokens(
+        &mut seed,
+        &(Key(1)..Key(3)),
+        &[
+            Token::Struct {
+                name: "Range",
+                len: 2,
+            },
+            Token::Str("start"),
+            Token::NewtypeStruct { name: "Key" },
+            Token::I32(1),
+            Token::Str("end"),
+            Token::NewtypeStruct { name: "Key" },
+            Token::I32(3),
+            Token::StructEnd,
+        ],
+    );
+    assert_eq!(seed.0, 2);
+
+    assert_de_seed_tokens(
+        &mut seed,
+        &(Key(1)..=Key(3)),
+        &[
+            Token::Seq { len: Some(2) },
+            Token::NewtypeStruct { name: "Key" },
+            Token::I32(1),
+            Token::NewtypeStruct { name: "Key" },
+            Token::I32(3),
+            Token::SeqEnd,
+        ],
+    );
+    assert_eq!(seed.0, 4);
+}
+
+// The helper types generated by the derive live inside the impl functions, so
+// they can neither clash with each other nor with user types of similar names.
+mod helper_names {
+    use super::{Inner, Seed};
+
+    #[derive(DeserializeState, Debug, PartialEq)]
+    #[serde(deserialize_state = "Seed")]
+    pub struct Field {
+        #[serde(deserialize_state)]
+        pub inner: Inner,
+    }
+
+    #[derive(DeserializeState, Debug, PartialEq)]
+    #[serde(deserialize_state = "Seed")]
+    pub struct Visitor {
+        #[serde(deserialize_state)]
+        pub field: Field,
+        #[serde(deserialize_state)]
+        pub inner: Inner,
+    }
+}
+
+#[test]
+fn test_helper_names_do_not_collide() {
+    use self::helper_names::{Field, Visitor};
+
+    let value = Visitor {
+        field: Field { inner: Inner },
+        inner: Inner,
+    };
+    let mut seed = Seed::default();
+    assert_de_seed_tokens(
+        &mut seed,
+        &value,
+        &[
+            Token::Struct {
+                name: "Visitor",
+                len: 2,
+            },
+            Token::Str("field"),
+            Token::Struct {
+                name: "Field",
+                len: 1,
+            },
+            Token::Str("inner"),
+            Token::UnitStruct { name: "Inner" },
+            Token::StructEnd,
+            Token::Str("inner"),
+            Token::UnitStruct { name: "Inner" },
+            Token::StructEnd,
+        ],
+    );
+    assert_eq!(seed.0, 2);
+}
+
+struct Interner(Vec<&'static str>);
+
+#[derive(Debug, PartialEq)]
+struct Symbol(&'static str);
+
+impl<'de, 'a> DeserializeState<'de, &'a Interner> for Symbol {
+    fn deserialize_state<D>(seed: &mut &'a Interner, deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let index = usize::deserialize(deserializer)?;
+        match seed.0.get(index) {
+            Some(name) => Ok(Symbol(name)),
+            None => Err(D::Error::custom(format_args!("unknown symbol {}", index))),
+        }
+    }
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "&'a Interner")]
+struct InternedPair {
+    #[serde(deserialize_state)]
+    first: Symbol,
+    #[serde(deserialize_state)]
+    second: Symbol,
+}
+
+#[test]
+fn test_reference_seed() {
+    let interner = Interner(vec!["alpha", "beta"]);
+    let value = InternedPair {
+        first: Symbol("beta"),
+        second: Symbol("alpha"),
+    };
+    assert_de_seed_tokens(
+        &mut &interner,
+        &value,
+        &[
+            Token::Tuple { len: 2 },
+            Token::U64(1),
+            Token::U64(0),
+            Token::TupleEnd,
+        ],
+    );
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed")]
+struct SeededPrimitives {
+    #[serde(deserialize_state)]
+    letter: char,
+    #[serde(deserialize_state)]
+    flag: bool,
+    #[serde(deserialize_state)]
+    counted: Counted,
+}
+
+#[test]
+fn test_deserialize_state_primitives() {
+    let value = SeededPrimitives {
+        letter: 'x',
+        flag: true,
+        counted: Counted,
+    };
+    let mut seed = Seed::default();
+    assert_de_seed_tokens(
+        &mut seed,
+        &value,
+        &[
+            Token::Struct {
+                name: "SeededPrimitives",
+                len: 3,
+            },
+            Token::Str("letter"),
+            Token::Char('x'),
+            Token::Str("flag"),
+            Token::Bool(true),
+            Token::Str("counted"),
+            Token::Unit,
+            Token::StructEnd,
+        ],
+    );
+    assert_eq!(seed.0, 1);
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed", deny_unknown_fields)]
+struct DenyUnknownSeeded {
+    #[serde(deserialize_state)]
+    inner: Inner,
+}
+
+#[test]
+fn test_deny_unknown_fields_seeded() {
+    let mut seed = Seed::default();
+    assert_de_seed_tokens(
+        &mut seed,
+        &DenyUnknownSeeded { inner: Inner },
+        &[
+            Token::Struct {
+                name: "DenyUnknownSeeded",
+                len: 1,
+            },
+            Token::Str("inner"),
+            Token::UnitStruct { name: "Inner" },
+            Token::StructEnd,
+        ],
+    );
+    assert_eq!(seed.0, 1);
+
+    let mut seed = Seed::default();
+    let mut de = serde_test::Deserializer::new(&[
+        Token::Struct {
+            name: "DenyUnknownSeeded",
+            len: 2,
+        },
+        Token::Str("inner"),
+        Token::UnitStruct { name: "Inner" },
+        Token::Str("extra"),
+        Token::UnitStruct { name: "Inner" },
+        Token::StructEnd,
+    ]);
+    let err = DenyUnknownSeeded::deserialize_state(&mut seed, &mut de).unwrap_err();
+    assert_eq!(err.to_string(), "unknown field `extra`, expected `inner`");
+    // Only the known field reached the seed
+    assert_eq!(seed.0, 1);
+}
+
+fn next_id(seed: &mut Seed) -> i32 {
+    seed.0 += 1;
+    seed.0 * 100
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed")]
+struct DefaultFromSeed {
+    name: String,
+    #[serde(default_state = "next_id")]
+    id: i32,
+}
+
+#[test]
+fn test_default_state() {
+    let mut seed = Seed::default();
+    assert_de_seed_tokens(
+        &mut seed,
+        &DefaultFromSeed {
+            name: "present".to_owned(),
+            id: 7,
+        },
+        &[
+            Token::Struct {
+                name: "DefaultFromSeed",
+                len: 2,
+            },
+            Token::Str("name"),
+            Token::Str("present"),
+            Token::Str("id"),
+            Token::I32(7),
+            Token::StructEnd,
+        ],
+    );
+    // The seed is only consulted for missing fields
+    assert_eq!(seed.0, 0);
+
+    assert_de_seed_tokens(
+        &mut seed,
+        &DefaultFromSeed {
+            name: "missing".to_owned(),
+            id: 100,
+        },
+        &[
+            Token::Struct {
+                name: "DefaultFromSeed",
+                len: 1,
+            },
+            Token::Str("name"),
+            Token::Str("missing"),
+            Token::StructEnd,
+        ],
+    );
+    assert_eq!(seed.0, 1);
+
+    assert_de_seed_tokens(
+        &mut seed,
+        &DefaultFromSeed {
+            name: "short".to_owned(),
+            id: 200,
+        },
+        &[
+            Token::Seq { len: Some(1) },
+            Token::Str("short"),
+            Token::SeqEnd,
+        ],
+    );
+    assert_eq!(seed.0, 2);
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+struct Ranked(i32);
+
+impl<'de> DeserializeState<'de, Seed> for Ranked {
+    fn deserialize_state<D>(seed: &mut Seed, deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        seed.0 += 1;
+        i32::deserialize(deserializer).map(Ranked)
+    }
+}
+
+#[test]
+fn test_binary_heap_seeded() {
+    let mut seed = Seed::default();
+    let mut de = serde_test::Deserializer::new(&[
+        // The hint is far larger than the input and must not be trusted
+        Token::Seq { len: Some(1 << 40) },
+        Token::I32(2),
+        Token::I32(3),
+        Token::I32(1),
+        Token::SeqEnd,
+    ]);
+    let heap = BinaryHeap::<Ranked>::deserialize_state(&mut seed, &mut de).unwrap();
+    assert_eq!(de.remaining(), 0);
+    assert_eq!(heap.peek(), Some(&Ranked(3)));
+    assert_eq!(heap.into_sorted_vec(), [Ranked(1), Ranked(2), Ranked(3)]);
+    assert_eq!(seed.0, 3);
+}
+
+#[derive(Debug, PartialEq)]
+struct Name<'a>(&'a str);
+
+fn deserialize_name<'de, D>(seed: &mut Seed, deserializer: D) -> Result<Name<'de>, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    seed.0 += 1;
+    <&str>::deserialize(deserializer).map(Name)
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed")]
+struct BorrowedName<'a> {
+    #[serde(borrow, deserialize_state_with = "deserialize_name")]
+    name: Name<'a>,
+}
+
+#[test]
+fn test_deserialize_state_with_borrowed() {
+    let input = "borrowed";
+    let tokens = [
+        Token::Struct {
+            name: "BorrowedName",
+            len: 1,
+        },
+        Token::Str("name"),
+        Token::BorrowedStr(input),
+        Token::StructEnd,
+    ];
+
+    let mut seed = Seed::default();
+    let mut de = serde_test::Deserializer::new(&tokens);
+    let value = BorrowedName::deserialize_state(&mut seed, &mut de).unwrap();
+    assert_eq!(value, BorrowedName { name: Name(input) });
+    // The name points into the input rather than at a copy of it
+    assert_eq!(value.name.0.as_ptr(), input.as_ptr());
+    assert_eq!(seed.0, 1);
+}
+
+#[derive(Debug, PartialEq)]
+struct ArenaNode {
+    value: Cell<i32>,
+}
+
+// Hands out preallocated slots, standing in for an arena such as
+// `typed_arena::Arena` whose allocations outlive the input.
+struct ArenaSeed<'arena> {
+    slots: &'arena [ArenaNode],
+    used: usize,
+}
+
+impl<'arena> ArenaSeed<'arena> {
+    fn alloc(&mut self, value: i32) -> &'arena ArenaNode {
+        let slots = self.slots;
+        let node = &slots[self.used];
+        self.used += 1;
+        node.value.set(value);
+        node
+    }
+}
+
+fn deserialize_in_arena<'de, 'arena, D>(
+    seed: &mut ArenaSeed<'arena>,
+    deserializer: D,
+) -> Result<&'arena ArenaNode, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    let value = i32::deserialize(deserializer)?;
+    Ok(seed.alloc(value))
+}
+
+#[derive(DeserializeState, Debug)]
+#[serde(deserialize_state = "ArenaSeed<'arena>")]
+struct ArenaEdge<'arena> {
+    #[serde(deserialize_state_with = "deserialize_in_arena")]
+    from: &'arena ArenaNode,
+    #[serde(deserialize_state_with = "deserialize_in_arena")]
+    to: &'arena ArenaNode,
+}
+
+#[test]
+fn test_deserialize_into_arena() {
+    let slots: Vec<_> = (0..2).map(|_| ArenaNode { value: Cell::new(0) }).collect();
+    let edge = {
+        let tokens = vec![
+            Token::Struct {
+                name: "ArenaEdge",
+                len: 2,
+            },
+            Token::Str("from"),
+            Token::I32(1),
+            Token::Str("to"),
+            Token::I32(2),
+            Token::StructEnd,
+        ];
+        let mut seed = ArenaSeed {
+            slots: &slots,
+            used: 0,
+        };
+        let mut de = serde_test::Deserializer::new(&tokens);
+        let edge = ArenaEdge::deserialize_state(&mut seed, &mut de).unwrap();
+        assert_eq!(seed.used, 2);
+        edge
+    };
+
+    // The nodes live in the arena, not in the dropped input or seed
+    assert!(std::ptr::eq(edge.from, &slots[0]));
+    assert!(std::ptr::eq(edge.to, &slots[1]));
+    assert_eq!(edge.from.value.get(), 1);
+    assert_eq!(edge.to.value.get(), 2);
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed")]
+struct InteriorMutable {
+    #[serde(deserialize_state)]
+    inner: std::cell::RefCell<Inner>,
+    #[serde(deserialize_state)]
+    count: Cell<i32>,
+}
+
+#[test]
+fn test_cell_and_ref_cell_seeded() {
+    let mut seed = Seed::default();
+    assert_de_seed_tokens(
+        &mut seed,
+        &InteriorMutable {
+            inner: std::cell::RefCell::new(Inner),
+            count: Cell::new(3),
+        },
+        &[
+            Token::Struct {
+                name: "InteriorMutable",
+                len: 2,
+            },
+            Token::Str("inner"),
+            Token::UnitStruct { name: "Inner" },
+            Token::Str("count"),
+            Token::I32(3),
+            Token::StructEnd,
+        ],
+    );
+    assert_eq!(seed.0, 1);
+}
+
+#[derive(DeserializeState)]
+#[serde(deserialize_state = "Seed")]
+struct Locked {
+    #[serde(deserialize_state)]
+    mutex: std::sync::Mutex<Inner>,
+    #[serde(deserialize_state)]
+    rw_lock: std::sync::RwLock<Inner>,
+}
+
+#[test]
+fn test_mutex_and_rw_lock_seeded() {
+    let mut seed = Seed::default();
+    let mut de = serde_test::Deserializer::new(&[
+        Token::Struct {
+            name: "Locked",
+            len: 2,
+        },
+        Token::Str("mutex"),
+        Token::UnitStruct { name: "Inner" },
+        Token::Str("rw_lock"),
+        Token::UnitStruct { name: "Inner" },
+        Token::StructEnd,
+    ]);
+    let locked = Locked::deserialize_state(&mut seed, &mut de).unwrap();
+    assert_eq!(de.remaining(), 0);
+    assert_eq!(locked.mutex.into_inner().unwrap(), Inner);
+    assert_eq!(locked.rw_lock.into_inner().unwrap(), Inner);
+    assert_eq!(seed.0, 2);
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed", deserialize_state_all)]
+enum PartlySeeded {
+    Seeded(Inner),
+    #[serde(deserialize)]
+    Plain(Inner),
+    #[serde(deserialize)]
+    PlainStruct { inner: Inner },
+}
+
+#[test]
+fn test_variant_opts_out_of_seed() {
+    let mut seed = Seed::default();
+    assert_de_seed_tokens(
+        &mut seed,
+        &PartlySeeded::Seeded(Inner),
+        &[
+            Token::NewtypeVariant {
+                name: "PartlySeeded",
+                variant: "Seeded",
+            },
+            Token::UnitStruct { name: "Inner" },
+        ],
+    );
+    assert_eq!(seed.0, 1);
+
+    assert_de_seed_tokens(
+        &mut seed,
+        &PartlySeeded::Plain(Inner),
+        &[
+            Token::NewtypeVariant {
+                name: "PartlySeeded",
+                variant: "Plain",
+            },
+            Token::UnitStruct { name: "Inner" },
+        ],
+    );
+    assert_de_seed_tokens(
+        &mut seed,
+        &PartlySeeded::PlainStruct { inner: Inner },
+        &[
+            Token::StructVariant {
+                name: "PartlySeeded",
+                variant: "PlainStruct",
+                len: 1,
+            },
+            Token::Str("inner"),
+            Token::UnitStruct { name: "Inner" },
+            Token::StructVariantEnd,
+        ],
+    );
+    assert_eq!(seed.0, 1);
+}
+
+// Deserializes `[[inner, ...], {key: inner, ...}]` with a hand-written visitor
+#[derive(Debug, PartialEq)]
+struct ItemsAndEntries {
+    items: Vec<Inner>,
+    entries: Vec<(String, Inner)>,
+}
+
+impl<'de> DeserializeState<'de, Seed> for ItemsAndEntries {
+    fn deserialize_state<D>(seed: &mut Seed, deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        use serde::de::{MapAccess, SeqAccess, Visitor};
+        use serde_state::de::{collect_map_seed, collect_seq_seed};
+        use std::fmt;
+
+        struct Items<'a>(&'a mut Seed);
+
+        impl<'de, 'a> DeserializeSeed<'de> for Items<'a> {
+            type Value = Vec<Inner>;
+
+            fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                deserializer.deserialize_seq(self)
+            }
+        }
+
+        impl<'de, 'a> Visitor<'de> for Items<'a> {
+            type Value = Vec<Inner>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a sequence of items")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                collect_seq_seed(self.0, &mut seq)
+            }
+        }
+
+        struct Entries<'a>(&'a mut Seed);
+
+        impl<'de, 'a> DeserializeSeed<'de> for Entries<'a> {
+            type Value = Vec<(String, Inner)>;
+
+            fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+            where
+                D: Deserializer<'de>,
+            {
+                deserializer.deserialize_map(self)
+            }
+        }
+
+        impl<'de, 'a> Visitor<'de> for Entries<'a> {
+            type Value = Vec<(String, Inner)>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("a map of entries")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                collect_map_seed(self.0, &mut map)
+            }
+        }
+
+        struct Pair<'a>(&'a mut Seed);
+
+        impl<'de, 'a> Visitor<'de> for Pair<'a> {
+            type Value = ItemsAndEntries;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("items and entries")
+            }
+
+            fn visit_seq<A>(self, mut seq: A) -> Result<Self::Value, A::Error>
+            where
+                A: SeqAccess<'de>,
+            {
+                let items = match seq.next_element_seed(Items(&mut *self.0))? {
+                    Some(items) => items,
+                    None => return Err(A::Error::invalid_length(0, &self)),
+                };
+                let entries = match seq.next_element_seed(Entries(&mut *self.0))? {
+                    Some(entries) => entries,
+                    None => return Err(A::Error::invalid_length(1, &self)),
+                };
+                Ok(ItemsAndEntries { items, entries })
+            }
+        }
+
+        deserializer.deserialize_tuple(2, Pair(seed))
+    }
+}
+
+#[test]
+fn test_collect_seq_and_map_seed() {
+    let mut seed = Seed::default();
+    assert_de_seed_tokens(
+        &mut seed,
+        &ItemsAndEntries {
+            items: vec![Inner, Inner],
+            entries: vec![("a".to_owned(), Inner)],
+        },
+        &[
+            Token::Tuple { len: 2 },
+            Token::Seq { len: Some(2) },
+            Token::UnitStruct { name: "Inner" },
+            Token::UnitStruct { name: "Inner" },
+            Token::SeqEnd,
+            Token::Map { len: Some(1) },
+            Token::Str("a"),
+            Token::UnitStruct { name: "Inner" },
+            Token::MapEnd,
+            Token::TupleEnd,
+        ],
+    );
+    assert_eq!(seed.0, 3);
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(
+    deserialize_state = "Seed",
+    rename(serialize = "SplitOut", deserialize = "SplitIn")
+)]
+struct SplitRename {
+    #[serde(deserialize_state, rename(serialize = "out", deserialize = "in"))]
+    inner: Inner,
+}
+
+#[test]
+fn test_split_rename_seeded() {
+    let mut seed = Seed::default();
+    assert_de_seed_tokens(
+        &mut seed,
+        &SplitRename { inner: Inner },
+        &[
+            Token::Struct {
+                name: "SplitIn",
+                len: 1,
+            },
+            Token::Str("in"),
+            Token::UnitStruct { name: "Inner" },
+            Token::StructEnd,
+        ],
+    );
+    assert_eq!(seed.0, 1);
+
+    assert_de_seed_tokens_error::<_, SplitRename>(
+        &mut seed,
+        &[
+            Token::Struct {
+                name: "SplitIn",
+                len: 1,
+            },
+            Token::Str("out"),
+            Token::UnitStruct { name: "Inner" },
+            Token::StructEnd,
+        ],
+        "missing field `in`",
+    );
+}
+
+#[derive(DeserializeState)]
+#[serde(deserialize_state = "Minimum")]
+struct RangeShadow {
+    low: i32,
+    high: i32,
+}
+
+// Each range has to start at or after the end of the previous one
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Minimum", state_from = "RangeShadow")]
+struct CheckedRange {
+    low: i32,
+    high: i32,
+}
+
+impl serde_state::de::FromState<RangeShadow, Minimum> for CheckedRange {
+    type Error = String;
+
+    fn from_state(shadow: RangeShadow, seed: &mut Minimum) -> Result<Self, String> {
+        if shadow.low < seed.0 {
+            return Err(format!("range starts before {}", seed.0));
+        }
+        if shadow.high < shadow.low {
+            return Err("range ends before it starts".to_owned());
+        }
+        seed.0 = shadow.high;
+        Ok(CheckedRange {
+            low: shadow.low,
+            high: shadow.high,
+        })
+    }
+}
+
+#[test]
+fn test_state_from() {
+    let tokens = &[
+        Token::Struct {
+            name: "RangeShadow",
+            len: 2,
+        },
+        Token::Str("low"),
+        Token::I32(1),
+        Token::Str("high"),
+        Token::I32(4),
+        Token::StructEnd,
+    ];
+
+    let mut seed = Minimum(0);
+    assert_de_seed_tokens(&mut seed, &CheckedRange { low: 1, high: 4 }, tokens);
+    assert_eq!(seed.0, 4);
+
+    assert_de_seed_tokens_error::<_, CheckedRange>(&mut seed, tokens, "range starts before 4");
+    assert_eq!(seed.0, 4);
+}
+
+// The container and both fields state the same bound on the seed
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "S", de_parameters = "S")]
+#[serde(bound = "S: AsMut<Seed>")]
+struct OverlappingSeedBounds {
+    #[serde(bound = "S: AsMut<Seed>", deserialize_state_with = "deserialize_inner")]
+    first: Inner,
+    #[serde(bound = "S: AsMut<Seed>", deserialize_state_with = "deserialize_inner")]
+    second: Inner,
+}
+
+#[test]
+fn test_overlapping_seed_bounds() {
+    let mut seed = Seed::default();
+    assert_de_seed_tokens(
+        &mut seed,
+        &OverlappingSeedBounds {
+            first: Inner,
+            second: Inner,
+        },
+        &[
+            Token::Struct {
+                name: "OverlappingSeedBounds",
+                len: 2,
+            },
+            Token::Str("first"),
+            Token::UnitStruct { name: "Inner" },
+            Token::Str("second"),
+            Token::UnitStruct { name: "Inner" },
+            Token::StructEnd,
+        ],
+    );
+    assert_eq!(seed.0, 2);
+}
+
+mod remote {
+    use super::Inner;
+
+    #[derive(Debug, PartialEq)]
+    pub struct Package {
+        pub name: String,
+        pub inner: Inner,
+    }
+}
+
+#[derive(DeserializeState)]
+#[serde(remote = "remote::Package", deserialize_state = "Seed")]
+struct PackageDef {
+    name: String,
+    #[serde(deserialize_state_with = "deserialize_inner")]
+    inner: Inner,
+}
+
+#[derive(DeserializeState, Debug, PartialEq)]
+#[serde(deserialize_state = "Seed")]
+struct PackageIndex {
+    #[serde(deserialize_state_with = "PackageDef::deserialize_state")]
+    package: remote::Package,
+}
+
+#[test]
+fn test_remote_deserialize_state() {
+    let mut seed = Seed::default();
+    assert_de_seed_tokens(
+        &mut seed,
+        &PackageIndex {
+            package: remote::Package {
+                name: "serde".to_owned(),
+                inner: Inner,
+            },
+        },
+        &[
+            Token::Struct {
+                name: "PackageIndex",
+                len: 1,
+            },
+            Token::Str("package"),
+            Token::Struct {
+                name: "Package",
+                len: 2,
+            },
+            Token::Str("name"),
+            Token::Str("serde"),
+            Token::Str("inner"),
+            Token::UnitStruct { name: "Inner" },
+            Token::StructEnd,
+            Token::StructEnd,
+        ],
+    );
+    assert_eq!(seed.0, 1);
+}
+
+// Interns names as integer handles, handle `n` naming `names[n - 1]`
+#[derive(Default)]
+struct Handles {
+    names: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord)]
+struct Handle(NonZeroU32);
+
+impl SerializeState<Handles> for Handle {
+    fn serialize_state<S>(&self, serializer: S, seed: &Handles) -> Result<S::Ok, S::Error>
+    where
+        S: Serializer,
+    {
+        match seed.names.get(self.0.get() as usize - 1) {
+            Some(name) => serializer.serialize_str(name),
+            None => Err(ser::Error::custom(format_args!("unknown handle {}", self.0))),
+        }
+    }
+}
+
+struct HandleVisitor<'a>(&'a mut Handles);
+
+impl<'de, 'a> serde::de::Visitor<'de> for HandleVisitor<'a> {
+    type Value = Handle;
+
+    fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+        formatter.write_str("a name or a handle")
+    }
+
+    fn visit_str<E>(self, name: &str) -> Result<Handle, E>
+    where
+        E: Error,
+    {
+        let index = match self.0.names.iter().position(|known| known == name) {
+            Some(index) => index,
+            None => {
+                self.0.names.push(name.to_owned());
+                self.0.names.len() - 1
+            }
+        };
+        self.visit_u64(index as u64 + 1)
+    }
+
+    // Handles which were already resolved are accepted as is
+    fn visit_u64<E>(self, handle: u64) -> Result<Handle, E>
+    where
+        E: Error,
+    {
+        serde_state::de::nonzero_handle(handle, self.0.names.len()).map(Handle)
+    }
+}
+
+impl<'de> DeserializeState<'de, Handles> for Handle {
+    fn deserialize_state<D>(seed: &mut Handles, deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        deserializer.deserialize_any(HandleVisitor(seed))
+    }
+}
+
+#[test]
+fn test_nonzero_handle_keys() {
+    let handle = |n| Handle(NonZeroU32::new(n).unwrap());
+    let tokens = &[
+        Token::Map { len: Some(2) },
+        Token::Str("left"),
+        Token::I32(1),
+        Token::Str("right"),
+        Token::I32(2),
+        Token::MapEnd,
+    ];
+
+    let mut seed = Handles::default();
+    let mut expected = BTreeMap::new();
+    expected.insert(handle(1), 1);
+    expected.insert(handle(2), 2);
+    assert_de_seed_tokens(&mut seed, &expected, tokens);
+    assert_eq!(seed.names, ["left", "right"]);
+
+    assert_ser_tokens(&Seeded::new(&seed, MapKeySeed(&expected)), tokens);
+
+    assert_de_seed_tokens_error::<_, BTreeMap<Handle, i32>>(
+        &mut seed,
+        &[Token::Map { len: Some(1) }, Token::U32(0), Token::I32(1)],
+        "invalid handle 0",
+    );
+    assert_de_seed_tokens_error::<_, BTreeMap<Handle, i32>>(
+        &mut seed,
+        &[Token::Map { len: Some(1) }, Token::U32(3), Token::I32(1)],
+        "invalid handle 3",
+    );
+    // Truncating to `u32` would turn this into handle `1`
+    assert_de_seed_tokens_error::<_, BTreeMap<Handle, i32>>(
+        &mut seed,
+        &[Token::Map { len: Some(1) }, Token::U64((1 << 32) + 1), Token::I32(1)],
+        "invalid handle 4294967297",
+    );
+}
+
+trait Shape {
+    fn area(&self) -> u32;
+}
+
+#[derive(Deserialize)]
+struct Square {
+    side: u32,
+}
+
+impl Shape for Square {
+    fn area(&self) -> u32 {
+        self.side * self.side
+    }
+}
+
+#[derive(Deserialize)]
+struct Rectangle {
+    width: u32,
+    height: u32,
+}
+
+impl Shape for Rectangle {
+    fn area(&self) -> u32 {
+        self.width * self.height
+    }
+}
+
+// Scales the area of a registered shape, which is deserialized with the same seed
+#[derive(DeserializeState)]
+#[serde(deserialize_state = "Shapes")]
+struct Scaled {
+    factor: u32,
+    #[serde(deserialize_state_with = "serde_state::de::deserialize_registered")]
+    shape: Box<dyn Shape>,
+}
+
+impl Shape for Scaled {
+    fn area(&self) -> u32 {
+        self.factor * self.factor * self.shape.area()
+    }
+}
+
+type ShapeDeserializer<'de> = serde_state::de::RegisteredDeserializer<'de>;
+
+type ShapeResult = Result<Box<dyn Shape>, serde_state::de::RegistryError>;
+
+struct Shapes {
+    registry: serde_state::de::Registry<Shapes, dyn Shape>,
+    constructed: usize,
+}
+
+impl std::borrow::Borrow<serde_state::de::Registry<Shapes, dyn Shape>> for Shapes {
+    fn borrow(&self) -> &serde_state::de::Registry<Shapes, dyn Shape> {
+        &self.registry
+    }
+}
+
+fn square(seed: &mut Shapes, deserializer: ShapeDeserializer) -> ShapeResult {
+    seed.constructed += 1;
+    let square = Square::deserialize(deserializer)?;
+    Ok(Box::new(square))
+}
+
+fn rectangle(seed: &mut Shapes, deserializer: ShapeDeserializer) -> ShapeResult {
+    seed.constructed += 1;
+    let rectangle = Rectangle::deserialize(deserializer)?;
+    Ok(Box::new(rectangle))
+}
+
+fn scaled(seed: &mut Shapes, deserializer: ShapeDeserializer) -> ShapeResult {
+    seed.constructed += 1;
+    let scaled = Scaled::deserialize_state(seed, deserializer)?;
+    Ok(Box::new(scaled))
+}
+
+// Deserializes a single registered shape
+struct RegisteredShape<'a>(&'a mut Shapes);
+
+impl<'a, 'de> DeserializeSeed<'de> for RegisteredShape<'a> {
+    type Value = Box<dyn Shape>;
+
+    fn deserialize<D>(self, deserializer: D) -> Result<Self::Value, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        serde_state::de::deserialize_registered(self.0, deserializer)
+    }
+}
+
+#[derive(DeserializeState)]
+#[serde(deserialize_state = "Shapes")]
+struct Drawing {
+    #[serde(deserialize_state_with = "serde_state::de::deserialize_registered")]
+    first: Box<dyn Shape>,
+    #[serde(deserialize_state_with = "serde_state::de::deserialize_registered")]
+    second: Box<dyn Shape>,
+}
+
+#[test]
+fn test_registry_seed() {
+    let mut shapes = Shapes {
+        registry: serde_state::de::Registry::new(),
+        constructed: 0,
+    };
+    shapes.registry.register("square", square);
+    shapes.registry.register("rectangle", rectangle);
+    shapes.registry.register("scaled", scaled);
+
+    let mut de = serde_test::Deserializer::new(&[
+        Token::Struct {
+            name: "Drawing",
+            len: 2,
+        },
+        Token::Str("first"),
+        Token::Map { len: Some(1) },
+        Token::Str("square"),
+        Token::Struct {
+            name: "Square",
+            len: 1,
+        },
+        Token::Str("side"),
+        Token::U32(3),
+        Token::StructEnd,
+        Token::MapEnd,
+        Token::Str("second"),
+        Token::Map { len: Some(1) },
+        Token::Str("scaled"),
+        Token::Struct {
+            name: "Scaled",
+            len: 2,
+        },
+        Token::Str("factor"),
+        Token::U32(2),
+        Token::Str("shape"),
+        Token::Map { len: Some(1) },
+        Token::Str("rectangle"),
+        Token::Struct {
+            name: "Rectangle",
+            len: 2,
+        },
+        Token::Str("width"),
+        Token::U32(2),
+        Token::Str("height"),
+        Token::U32(5),
+        Token::StructEnd,
+        Token::MapEnd,
+        Token::StructEnd,
+        Token::MapEnd,
+        Token::StructEnd,
+    ]);
+    // The contents of registered values are buffered, which asks whether the input is human
+    // readable
+    let drawing = serde_state::de::Seed::<_, Drawing>::new(&mut shapes)
+        .readable()
+        .deserialize(&mut de)
+        .unwrap();
+    assert_eq!(de.remaining(), 0);
+    assert_eq!(drawing.first.area(), 9);
+    assert_eq!(drawing.second.area(), 40);
+    assert_eq!(shapes.constructed, 3);
+
+    let mut de = serde_test::Deserializer::new(&[
+        Token::Map { len: Some(1) },
+        Token::Str("circle"),
+        Token::U32(1),
+        Token::MapEnd,
+    ]);
+    let result = RegisteredShape(&mut shapes).readable().deserialize(&mut de);
+    assert_eq!(
+        result.err().unwrap().to_string(),
+        "unknown type tag `circle`"
+    );
+
+    // Errors from the constructors are raised again as errors of the input
+    let mut de = serde_test::Deserializer::new(&[
+        Token::Map { len: Some(1) },
+        Token::Str("square"),
+        Token::Map { len: Some(0) },
+        Token::MapEnd,
+        Token::MapEnd,
+    ]);
+    let result = RegisteredShape(&mut shapes).readable().deserialize(&mut de);
+    assert_eq!(result.err().unwrap().to_string(), "missing field `side`");
+
+    let mut de = serde_test::Deserializer::new(&[
+        Token::Map { len: Some(1) },
+        Token::Str("square"),
+        Token::Map { len: Some(1) },
+        Token::Str("side"),
+        Token::Str("three"),
+        Token::MapEnd,
+        Token::MapEnd,
+    ]);
+    let result = RegisteredShape(&mut shapes).readable().deserialize(&mut de);
+    assert_eq!(
+        result.err().unwrap().to_string(),
+        "invalid type: string \"three\", expected u32"
+    );
 }
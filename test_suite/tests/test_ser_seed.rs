@@ -4,12 +4,17 @@ extern crate serde_derive_state;
 extern crate serde_state;
 extern crate serde_test;
 
+use std::borrow::Cow;
 use std::cell::Cell;
+use std::collections::{BTreeMap, BTreeSet, BinaryHeap, HashSet, LinkedList, VecDeque};
+use std::marker::PhantomData;
+
+use fnv::FnvBuildHasher;
 
 use serde::Serialize;
-use serde_state::ser::{Seeded, SerializeState};
+use serde_state::ser::{MapKeySeed, Seeded, SerializeState};
 
-use serde_test::{assert_ser_tokens, Token};
+use serde_test::{assert_de_seed_tokens, assert_ser_tokens, assert_ser_tokens_error, Token};
 
 #[derive(Serialize)]
 struct Inner;
@@ -57,6 +62,43 @@ fn test_serialize_state() {
     assert_eq!(seed.get(), 1);
 }
 
+fn serialize_counted<S, T>(value: &Inner, serializer: S, seed: &T) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+    T: ::std::borrow::Borrow<Cell<i32>>,
+{
+    value.serialize_state(serializer, seed)
+}
+
+#[derive(SerializeState)]
+#[serde(serialize_state = "T")]
+#[serde(ser_parameters = "T")]
+#[serde(bound(serialize = "T: ::std::borrow::Borrow<Cell<i32>>"))]
+struct SeedStructWith {
+    #[serde(serialize_state_with = "serialize_counted")]
+    value: Inner,
+}
+
+#[test]
+fn test_serialize_state_with_ser_parameters() {
+    let value = SeedStructWith { value: Inner };
+    let seed = Cell::new(0);
+    assert_ser_tokens(
+        &Seeded::new(&seed, &value),
+        &[
+            Token::Struct {
+                name: "SeedStructWith",
+                len: 1,
+            },
+            Token::Str("value"),
+            Token::UnitStruct { name: "Inner" },
+            Token::StructEnd,
+        ],
+    );
+
+    assert_eq!(seed.get(), 1);
+}
+
 #[test]
 fn test_serialize_vec_seed() {
     let value = [SeedStruct { value: Inner }, SeedStruct { value: Inner }];
@@ -86,6 +128,51 @@ fn test_serialize_vec_seed() {
     assert_eq!(seed.get(), 2);
 }
 
+#[test]
+fn test_serialize_vec_deque_seed() {
+    let value: VecDeque<_> = vec![Inner, Inner].into_iter().collect();
+    let seed = Cell::new(0);
+    assert_ser_tokens(
+        &Seeded::new(&seed, &value),
+        &[
+            Token::Seq { len: Some(2) },
+            Token::UnitStruct { name: "Inner" },
+            Token::UnitStruct { name: "Inner" },
+            Token::SeqEnd,
+        ],
+    );
+
+    assert_eq!(seed.get(), 2);
+}
+
+#[test]
+fn test_serialize_linked_list_seed() {
+    let value: LinkedList<_> = vec![Inner, Inner].into_iter().collect();
+    let seed = Cell::new(0);
+    assert_ser_tokens(
+        &Seeded::new(&seed, &value),
+        &[
+            Token::Seq { len: Some(2) },
+            Token::UnitStruct { name: "Inner" },
+            Token::UnitStruct { name: "Inner" },
+            Token::SeqEnd,
+        ],
+    );
+
+    assert_eq!(seed.get(), 2);
+}
+
+#[test]
+fn test_serialize_binary_heap_seed() {
+    let mut value = BinaryHeap::new();
+    value.push(1);
+    let seed = Cell::new(0);
+    assert_ser_tokens(
+        &Seeded::new(&seed, &value),
+        &[Token::Seq { len: Some(1) }, Token::I32(1), Token::SeqEnd],
+    );
+}
+
 #[test]
 fn test_serialize_option_some_seed() {
     let value = Some(SeedStruct { value: Inner });
@@ -116,6 +203,143 @@ fn test_serialize_option_none_seed() {
     assert_eq!(seed.get(), 0);
 }
 
+#[test]
+fn test_serialize_hash_map_seed() {
+    let mut value = HashMap::new();
+    value.insert("a".to_string(), Inner);
+    let seed = Cell::new(0);
+    assert_ser_tokens(
+        &Seeded::new(&seed, &value),
+        &[
+            Token::Map { len: Some(1) },
+            Token::Str("a"),
+            Token::UnitStruct { name: "Inner" },
+            Token::MapEnd,
+        ],
+    );
+
+    assert_eq!(seed.get(), 1);
+}
+
+#[test]
+fn test_serialize_btree_map_seed() {
+    let mut value = BTreeMap::new();
+    value.insert(2, Inner);
+    value.insert(1, Inner);
+    let seed = Cell::new(0);
+    assert_ser_tokens(
+        &Seeded::new(&seed, &value),
+        &[
+            Token::Map { len: Some(2) },
+            Token::I32(1),
+            Token::UnitStruct { name: "Inner" },
+            Token::I32(2),
+            Token::UnitStruct { name: "Inner" },
+            Token::MapEnd,
+        ],
+    );
+
+    assert_eq!(seed.get(), 2);
+}
+
+#[derive(Serialize, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+struct Key(i32);
+
+impl SerializeState<Cell<i32>> for Key {
+    fn serialize_state<S>(&self, serializer: S, seed: &Cell<i32>) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        seed.set(seed.get() + 1);
+        self.serialize(serializer)
+    }
+}
+
+#[test]
+fn test_serialize_btree_set_seed() {
+    let value: BTreeSet<Key> = vec![Key(2), Key(1)].into_iter().collect();
+    let seed = Cell::new(0);
+    assert_ser_tokens(
+        &Seeded::new(&seed, &value),
+        &[
+            Token::Seq { len: Some(2) },
+            Token::NewtypeStruct { name: "Key" },
+            Token::I32(1),
+            Token::NewtypeStruct { name: "Key" },
+            Token::I32(2),
+            Token::SeqEnd,
+        ],
+    );
+
+    assert_eq!(seed.get(), 2);
+}
+
+#[test]
+fn test_serialize_hash_set_seed() {
+    let value: HashSet<Key> = vec![Key(1)].into_iter().collect();
+    let seed = Cell::new(0);
+    assert_ser_tokens(
+        &Seeded::new(&seed, &value),
+        &[
+            Token::Seq { len: Some(1) },
+            Token::NewtypeStruct { name: "Key" },
+            Token::I32(1),
+            Token::SeqEnd,
+        ],
+    );
+
+    assert_eq!(seed.get(), 1);
+}
+
+#[test]
+fn test_serialize_hash_map_custom_hasher() {
+    let mut value: HashMap<Key, Key, FnvBuildHasher> = HashMap::default();
+    value.insert(Key(1), Key(2));
+    let seed = Cell::new(0);
+    assert_ser_tokens(
+        &Seeded::new(&seed, &value),
+        &[
+            Token::Map { len: Some(1) },
+            Token::NewtypeStruct { name: "Key" },
+            Token::I32(1),
+            Token::NewtypeStruct { name: "Key" },
+            Token::I32(2),
+            Token::MapEnd,
+        ],
+    );
+
+    // Keys are serialized without the seed
+    assert_eq!(seed.get(), 1);
+}
+
+#[derive(Serialize, PartialEq, Eq, PartialOrd, Ord)]
+struct Label(&'static str);
+
+#[test]
+fn test_serialize_map_with_plain_keys() {
+    let mut value = BTreeMap::new();
+    value.insert(Label("b"), Key(2));
+    value.insert(Label("a"), Key(1));
+    let seed = Cell::new(0);
+    assert_ser_tokens(
+        &Seeded::new(&seed, &value),
+        &[
+            Token::Map { len: Some(2) },
+            Token::NewtypeStruct { name: "Label" },
+            Token::Str("a"),
+            Token::NewtypeStruct { name: "Key" },
+            Token::I32(1),
+            Token::NewtypeStruct { name: "Label" },
+            Token::Str("b"),
+            Token::NewtypeStruct { name: "Key" },
+            Token::I32(2),
+            Token::MapEnd,
+        ],
+    );
+
+    assert_eq!(seed.get(), 2);
+}
+
 #[derive(SerializeState)]
 #[serde(serialize_state = "Cell<i32>")]
 enum SeedEnum {
@@ -493,3 +717,720 @@ where
     };
     node.serialize_state(serializer, map)
 }
+
+#[derive(SerializeState)]
+#[serde(serialize_state = "Cell<i32>")]
+struct PhantomField<T> {
+    #[serde(serialize_state)]
+    value: Inner,
+    #[serde(serialize_state)]
+    marker: PhantomData<T>,
+}
+
+#[test]
+fn test_serialize_phantom_data() {
+    let value = PhantomField::<String> {
+        value: Inner,
+        marker: PhantomData,
+    };
+    let seed = Cell::new(0);
+    assert_ser_tokens(
+        &Seeded::new(&seed, &value),
+        &[
+            Token::Struct {
+                name: "PhantomField",
+                len: 2,
+            },
+            Token::Str("value"),
+            Token::UnitStruct { name: "Inner" },
+            Token::Str("marker"),
+            Token::UnitStruct {
+                name: "PhantomData",
+            },
+            Token::StructEnd,
+        ],
+    );
+
+    assert_eq!(seed.get(), 1);
+}
+
+#[derive(SerializeState)]
+#[serde(serialize_state = "Cell<i32>", transparent)]
+struct TransparentInner {
+    #[serde(serialize_state)]
+    inner: Inner,
+}
+
+#[test]
+fn test_transparent_serialize_state() {
+    let seed = Cell::new(0);
+    assert_ser_tokens(
+        &Seeded::new(&seed, &TransparentInner { inner: Inner }),
+        &[Token::UnitStruct { name: "Inner" }],
+    );
+
+    assert_eq!(seed.get(), 1);
+}
+
+#[test]
+fn test_serialize_borrowed_cow() {
+    let keys = [Key(1)];
+    let value: Cow<[Key]> = Cow::Borrowed(&keys[..]);
+    let seed = Cell::new(0);
+    assert_ser_tokens(
+        &Seeded::new(&seed, &value),
+        &[
+            Token::Seq { len: Some(1) },
+            Token::NewtypeStruct { name: "Key" },
+            Token::I32(1),
+            Token::SeqEnd,
+        ],
+    );
+
+    assert_eq!(seed.get(), 1);
+}
+
+#[test]
+fn test_serialize_cow_str() {
+    let seed = Cell::new(0);
+    let borrowed: Cow<str> = Cow::Borrowed("borrowed");
+    assert_ser_tokens(&Seeded::new(&seed, &borrowed), &[Token::Str("borrowed")]);
+    let owned: Cow<str> = Cow::Owned("owned".to_string());
+    assert_ser_tokens(&Seeded::new(&seed, &owned), &[Token::Str("owned")]);
+}
+
+#[derive(SerializeState)]
+#[serde(serialize_state = "Cell<i32>")]
+struct SeededUnit;
+
+#[test]
+fn test_unit_ignores_seed() {
+    let seed = Cell::new(0);
+    assert_ser_tokens(&Seeded::new(&seed, &()), &[Token::Unit]);
+    assert_ser_tokens(
+        &Seeded::new(&seed, &SeededUnit),
+        &[Token::UnitStruct { name: "SeededUnit" }],
+    );
+
+    assert_eq!(seed.get(), 0);
+}
+
+#[derive(SerializeState)]
+#[serde(serialize_state = "Cell<i32>")]
+struct OptionalInner {
+    #[serde(serialize_state, skip_serializing_if = "Option::is_none")]
+    first: Option<Inner>,
+    #[serde(serialize_state, skip_serializing_if = "Option::is_none")]
+    second: Option<Inner>,
+}
+
+#[test]
+fn test_skip_serializing_none_state() {
+    let seed = Cell::new(0);
+    assert_ser_tokens(
+        &Seeded::new(
+            &seed,
+            &OptionalInner {
+                first: None,
+                second: Some(Inner),
+            },
+        ),
+        &[
+            Token::Struct {
+                name: "OptionalInner",
+                len: 1,
+            },
+            Token::Str("second"),
+            Token::Some,
+            Token::UnitStruct { name: "Inner" },
+            Token::StructEnd,
+        ],
+    );
+    assert_eq!(seed.get(), 1);
+
+    assert_ser_tokens(
+        &Seeded::new(
+            &seed,
+            &OptionalInner {
+                first: None,
+                second: None,
+            },
+        ),
+        &[
+            Token::Struct {
+                name: "OptionalInner",
+                len: 0,
+            },
+            Token::StructEnd,
+        ],
+    );
+    assert_eq!(seed.get(), 1);
+}
+
+#[derive(SerializeState)]
+#[serde(serialize_state = "Cell<i32>")]
+enum Enum {
+    Inner(#[serde(serialize_state_with = "serialize_counted")] Inner),
+    Inner2(u32, #[serde(serialize_state_with = "serialize_counted")] Inner),
+    Inner3 {
+        #[serde(serialize_state_with = "serialize_counted")]
+        value: Inner,
+    },
+}
+
+#[test]
+fn test_enum_serialize_state_with() {
+    let seed = Cell::new(0);
+    assert_ser_tokens(
+        &Seeded::new(&seed, &Enum::Inner(Inner)),
+        &[
+            Token::NewtypeVariant {
+                name: "Enum",
+                variant: "Inner",
+            },
+            Token::UnitStruct { name: "Inner" },
+        ],
+    );
+    assert_eq!(seed.get(), 1);
+
+    assert_ser_tokens(
+        &Seeded::new(&seed, &Enum::Inner2(3, Inner)),
+        &[
+            Token::TupleVariant {
+                name: "Enum",
+                variant: "Inner2",
+                len: 2,
+            },
+            Token::U32(3),
+            Token::UnitStruct { name: "Inner" },
+            Token::TupleVariantEnd,
+        ],
+    );
+    assert_eq!(seed.get(), 2);
+
+    assert_ser_tokens(
+        &Seeded::new(&seed, &Enum::Inner3 { value: Inner }),
+        &[
+            Token::StructVariant {
+                name: "Enum",
+                variant: "Inner3",
+                len: 1,
+            },
+            Token::Str("value"),
+            Token::UnitStruct { name: "Inner" },
+            Token::StructVariantEnd,
+        ],
+    );
+    assert_eq!(seed.get(), 3);
+}
+
+#[test]
+fn test_serialize_boxed_slice_and_str() {
+    let seed = Cell::new(0);
+    let value: Box<[Key]> = vec![Key(1), Key(2)].into_boxed_slice();
+    assert_ser_tokens(
+        &Seeded::new(&seed, &value),
+        &[
+            Token::Seq { len: Some(2) },
+            Token::NewtypeStruct { name: "Key" },
+            Token::I32(1),
+            Token::NewtypeStruct { name: "Key" },
+            Token::I32(2),
+            Token::SeqEnd,
+        ],
+    );
+    assert_eq!(seed.get(), 2);
+
+    let value: Box<str> = "boxed".to_string().into_boxed_str();
+    assert_ser_tokens(&Seeded::new(&seed, &value), &[Token::Str("boxed")]);
+    assert_eq!(seed.get(), 2);
+}
+
+struct SymbolTable(Vec<&'static str>);
+
+#[derive(Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct SymbolId(usize);
+
+impl SerializeState<SymbolTable> for SymbolId {
+    fn serialize_state<S>(&self, serializer: S, seed: &SymbolTable) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        serializer.serialize_str(seed.0[self.0])
+    }
+}
+
+#[derive(Serialize)]
+struct Weight(i32);
+
+#[test]
+fn test_serialize_map_keys_with_seed() {
+    let table = SymbolTable(vec!["alpha", "beta"]);
+
+    let mut weights = BTreeMap::new();
+    weights.insert(SymbolId(1), Weight(20));
+    weights.insert(SymbolId(0), Weight(10));
+    assert_ser_tokens(
+        &Seeded::new(&table, MapKeySeed(&weights)),
+        &[
+            Token::Map { len: Some(2) },
+            Token::Str("alpha"),
+            Token::NewtypeStruct { name: "Weight" },
+            Token::I32(10),
+            Token::Str("beta"),
+            Token::NewtypeStruct { name: "Weight" },
+            Token::I32(20),
+            Token::MapEnd,
+        ],
+    );
+}
+
+#[test]
+fn test_range_serialize_state() {
+    let seed = Cell::new(0);
+    assert_ser_tokens(
+        &Seeded::new(&seed, &(Key(1)..Key(3))),
+        &[
+            Token::Struct {
+                name: "Range",
+                len: 2,
+            },
+            Token::Str("start"),
+            Token::NewtypeStruct { name: "Key" },
+            Token::I32(1),
+            Token::Str("end"),
+            Token::NewtypeStruct { name: "Key" },
+            Token::I32(3),
+            Token::StructEnd,
+        ],
+    );
+    assert_eq!(seed.get(), 2);
+
+    assert_ser_tokens(
+        &Seeded::new(&seed, &(Key(1)..=Key(3))),
+        &[
+            Token::Struct {
+                name: "RangeInclusive",
+                len: 2,
+            },
+            Token::Str("start"),
+            Token::NewtypeStruct { name: "Key" },
+            Token::I32(1),
+            Token::Str("end"),
+            Token::NewtypeStruct { name: "Key" },
+            Token::I32(3),
+            Token::StructEnd,
+        ],
+    );
+    assert_eq!(seed.get(), 4);
+}
+
+#[derive(SerializeState)]
+#[serde(serialize_state = "Cell<i32>")]
+struct SeededPrimitives {
+    #[serde(serialize_state)]
+    letter: char,
+    #[serde(serialize_state)]
+    big: u64,
+    #[serde(serialize_state)]
+    flag: bool,
+    #[serde(serialize_state)]
+    name: String,
+    #[serde(serialize_state)]
+    counted: Inner,
+}
+
+#[test]
+fn test_serialize_state_primitives() {
+    let value = SeededPrimitives {
+        letter: 'x',
+        big: 1 << 40,
+        flag: true,
+        name: "name".to_string(),
+        counted: Inner,
+    };
+    let seed = Cell::new(0);
+    assert_ser_tokens(
+        &Seeded::new(&seed, &value),
+        &[
+            Token::Struct {
+                name: "SeededPrimitives",
+                len: 5,
+            },
+            Token::Str("letter"),
+            Token::Char('x'),
+            Token::Str("big"),
+            Token::U64(1 << 40),
+            Token::Str("flag"),
+            Token::Bool(true),
+            Token::Str("name"),
+            Token::Str("name"),
+            Token::Str("counted"),
+            Token::UnitStruct { name: "Inner" },
+            Token::StructEnd,
+        ],
+    );
+    assert_eq!(seed.get(), 1);
+}
+
+#[derive(SerializeState)]
+#[serde(serialize_state = "Cell<i32>", serialize_state_as_map)]
+struct InnerAsMap {
+    #[serde(serialize_state)]
+    inner: Inner,
+    #[serde(serialize_state, skip_serializing_if = "Option::is_none")]
+    optional: Option<Inner>,
+    count: u8,
+}
+
+#[test]
+fn test_serialize_state_as_map() {
+    let seed = Cell::new(0);
+    assert_ser_tokens(
+        &Seeded::new(
+            &seed,
+            &InnerAsMap {
+                inner: Inner,
+                optional: None,
+                count: 3,
+            },
+        ),
+        &[
+            Token::Map { len: Some(2) },
+            Token::Str("inner"),
+            Token::UnitStruct { name: "Inner" },
+            Token::Str("count"),
+            Token::U8(3),
+            Token::MapEnd,
+        ],
+    );
+    assert_eq!(seed.get(), 1);
+
+    assert_ser_tokens(
+        &Seeded::new(
+            &seed,
+            &InnerAsMap {
+                inner: Inner,
+                optional: Some(Inner),
+                count: 3,
+            },
+        ),
+        &[
+            Token::Map { len: Some(3) },
+            Token::Str("inner"),
+            Token::UnitStruct { name: "Inner" },
+            Token::Str("optional"),
+            Token::Some,
+            Token::UnitStruct { name: "Inner" },
+            Token::Str("count"),
+            Token::U8(3),
+            Token::MapEnd,
+        ],
+    );
+    assert_eq!(seed.get(), 3);
+}
+
+#[derive(SerializeState)]
+#[serde(serialize_state = "Cell<i32>")]
+struct InteriorMutable {
+    #[serde(serialize_state)]
+    inner: RefCell<Inner>,
+    #[serde(serialize_state)]
+    count: Cell<i32>,
+}
+
+#[test]
+fn test_serialize_state_cell_and_ref_cell() {
+    let value = InteriorMutable {
+        inner: RefCell::new(Inner),
+        count: Cell::new(3),
+    };
+    let seed = Cell::new(0);
+    assert_ser_tokens(
+        &Seeded::new(&seed, &value),
+        &[
+            Token::Struct {
+                name: "InteriorMutable",
+                len: 2,
+            },
+            Token::Str("inner"),
+            Token::UnitStruct { name: "Inner" },
+            Token::Str("count"),
+            Token::I32(3),
+            Token::StructEnd,
+        ],
+    );
+    assert_eq!(seed.get(), 1);
+}
+
+#[derive(SerializeState)]
+#[serde(serialize_state = "Cell<i32>")]
+struct Locked {
+    #[serde(serialize_state)]
+    mutex: std::sync::Mutex<Inner>,
+    #[serde(serialize_state)]
+    rw_lock: std::sync::RwLock<Inner>,
+}
+
+#[test]
+fn test_serialize_state_locks() {
+    let value = Locked {
+        mutex: std::sync::Mutex::new(Inner),
+        rw_lock: std::sync::RwLock::new(Inner),
+    };
+    let seed = Cell::new(0);
+    assert_ser_tokens(
+        &Seeded::new(&seed, &value),
+        &[
+            Token::Struct {
+                name: "Locked",
+                len: 2,
+            },
+            Token::Str("mutex"),
+            Token::UnitStruct { name: "Inner" },
+            Token::Str("rw_lock"),
+            Token::UnitStruct { name: "Inner" },
+            Token::StructEnd,
+        ],
+    );
+    assert_eq!(seed.get(), 2);
+}
+
+#[test]
+fn test_serialize_state_poisoned_mutex() {
+    let mutex = std::sync::Mutex::new(Inner);
+    let _ = std::panic::catch_unwind(std::panic::AssertUnwindSafe(|| {
+        let _guard = mutex.lock().unwrap();
+        panic!("poison the mutex");
+    }));
+    assert!(mutex.is_poisoned());
+
+    let seed = Cell::new(0);
+    assert_ser_tokens_error(
+        &Seeded::new(&seed, &mutex),
+        &[],
+        "lock poison error while serializing",
+    );
+    assert_eq!(seed.get(), 0);
+}
+
+#[derive(SerializeState)]
+#[serde(
+    serialize_state = "Cell<i32>",
+    rename(serialize = "SplitOut", deserialize = "SplitIn")
+)]
+struct SplitRename {
+    #[serde(serialize_state, rename(serialize = "out", deserialize = "in"))]
+    inner: Inner,
+}
+
+#[test]
+fn test_serialize_state_split_rename() {
+    let seed = Cell::new(0);
+    assert_ser_tokens(
+        &Seeded::new(&seed, &SplitRename { inner: Inner }),
+        &[
+            Token::Struct {
+                name: "SplitOut",
+                len: 1,
+            },
+            Token::Str("out"),
+            Token::UnitStruct { name: "Inner" },
+            Token::StructEnd,
+        ],
+    );
+    assert_eq!(seed.get(), 1);
+}
+
+#[derive(SerializeState)]
+#[serde(serialize_state = "Cell<i32>")]
+struct BorrowedView<'a> {
+    #[serde(serialize_state)]
+    items: &'a [Inner],
+    #[serde(serialize_state)]
+    first: &'a Inner,
+}
+
+#[test]
+fn test_serialize_state_borrowed_fields() {
+    // Inner is neither Clone nor Copy, so the view can only serialize by reference
+    let items = [Inner, Inner];
+    let view = BorrowedView {
+        items: &items,
+        first: &items[0],
+    };
+    let seed = Cell::new(0);
+    assert_ser_tokens(
+        &Seeded::new(&seed, &view),
+        &[
+            Token::Struct {
+                name: "BorrowedView",
+                len: 2,
+            },
+            Token::Str("items"),
+            Token::Seq { len: Some(2) },
+            Token::UnitStruct { name: "Inner" },
+            Token::UnitStruct { name: "Inner" },
+            Token::SeqEnd,
+            Token::Str("first"),
+            Token::UnitStruct { name: "Inner" },
+            Token::StructEnd,
+        ],
+    );
+    assert_eq!(seed.get(), 3);
+}
+
+mod remote {
+    use super::Inner;
+
+    pub struct Shelf {
+        label: String,
+        item: Inner,
+    }
+
+    impl Shelf {
+        pub fn new(label: &str, item: Inner) -> Self {
+            Shelf {
+                label: label.to_owned(),
+                item: item,
+            }
+        }
+
+        pub fn label(&self) -> &String {
+            &self.label
+        }
+
+        pub fn item(&self) -> &Inner {
+            &self.item
+        }
+    }
+}
+
+#[derive(SerializeState)]
+#[serde(remote = "remote::Shelf", serialize_state = "Cell<i32>")]
+struct ShelfDef {
+    #[serde(getter = "remote::Shelf::label")]
+    label: String,
+    #[serde(getter = "remote::Shelf::item", serialize_state)]
+    item: Inner,
+}
+
+#[derive(SerializeState)]
+#[serde(serialize_state = "Cell<i32>")]
+struct Warehouse {
+    #[serde(serialize_state_with = "ShelfDef::serialize_state")]
+    shelf: remote::Shelf,
+}
+
+#[test]
+fn test_serialize_state_remote_getter() {
+    let value = Warehouse {
+        shelf: remote::Shelf::new("top", Inner),
+    };
+    let seed = Cell::new(0);
+    assert_ser_tokens(
+        &Seeded::new(&seed, &value),
+        &[
+            Token::Struct {
+                name: "Warehouse",
+                len: 1,
+            },
+            Token::Str("shelf"),
+            Token::Struct {
+                name: "Shelf",
+                len: 2,
+            },
+            Token::Str("label"),
+            Token::Str("top"),
+            Token::Str("item"),
+            Token::UnitStruct { name: "Inner" },
+            Token::StructEnd,
+            Token::StructEnd,
+        ],
+    );
+    assert_eq!(seed.get(), 1);
+}
+
+fn serialize_summed<S>(value: &i32, serializer: S, seed: &Cell<i32>) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    seed.set(seed.get() + *value);
+    serializer.serialize_i32(*value)
+}
+
+fn serialize_checksum<S>(_: &(), serializer: S, seed: &Cell<i32>) -> Result<S::Ok, S::Error>
+where
+    S: serde::Serializer,
+{
+    serializer.serialize_i32(seed.get())
+}
+
+#[derive(SerializeState)]
+#[serde(serialize_state = "Cell<i32>")]
+struct Checksummed {
+    // Declared first but emitted last, once the other fields have been summed into the seed
+    #[serde(serialize_state_with = "serialize_checksum", serialize_state_order = 1)]
+    checksum: (),
+    #[serde(serialize_state_with = "serialize_summed")]
+    a: i32,
+    #[serde(serialize_state_with = "serialize_summed")]
+    b: i32,
+}
+
+#[test]
+fn test_serialize_state_order() {
+    let value = Checksummed {
+        checksum: (),
+        a: 2,
+        b: 3,
+    };
+    let seed = Cell::new(0);
+    assert_ser_tokens(
+        &Seeded::new(&seed, &value),
+        &[
+            Token::Struct {
+                name: "Checksummed",
+                len: 3,
+            },
+            Token::Str("a"),
+            Token::I32(2),
+            Token::Str("b"),
+            Token::I32(3),
+            Token::Str("checksum"),
+            Token::I32(5),
+            Token::StructEnd,
+        ],
+    );
+}
+
+#[derive(SerializeState, DeserializeState, Debug, PartialEq)]
+#[serde(serialize_state = "Cell<i32>", deserialize_state = "Cell<i32>")]
+struct Reordered {
+    #[serde(serialize_state_order = 1)]
+    last: i32,
+    first: i32,
+}
+
+#[test]
+fn test_serialize_state_order_round_trip() {
+    let value = Reordered { last: 1, first: 2 };
+    let tokens = &[
+        Token::Struct {
+            name: "Reordered",
+            len: 2,
+        },
+        Token::Str("first"),
+        Token::I32(2),
+        Token::Str("last"),
+        Token::I32(1),
+        Token::StructEnd,
+    ];
+
+    let seed = Cell::new(0);
+    assert_ser_tokens(&Seeded::new(&seed, &value), tokens);
+
+    // The fields are read back by name, so the reordering is undone
+    let mut seed = Cell::new(0);
+    assert_de_seed_tokens(&mut seed, &value, tokens);
+}
@@ -0,0 +1,13 @@
+use serde_derive_state::DeserializeState;
+use std::borrow::Cow;
+
+struct Seed;
+
+#[derive(DeserializeState)]
+#[serde(deserialize_state = "Seed")]
+struct S<'a> {
+    #[serde(borrow, deserialize_state)]
+    name: Cow<'a, str>,
+}
+
+fn main() {}
@@ -0,0 +1,22 @@
+use serde_derive_state::DeserializeState;
+use serde_state::de::Deserializer;
+
+struct Seed;
+
+struct OtherSeed;
+
+fn deserialize_other<'de, D>(_seed: &mut OtherSeed, _deserializer: D) -> Result<u8, D::Error>
+where
+    D: Deserializer<'de>,
+{
+    unimplemented!()
+}
+
+#[derive(DeserializeState)]
+#[serde(deserialize_state = "Seed")]
+struct S {
+    #[serde(deserialize_state_with = "deserialize_other")]
+    value: u8,
+}
+
+fn main() {}